@@ -8,17 +8,32 @@ use tauri_plugin_dialog;
 
 use types::{ConstraintKind, ErrorUnit, QualityMetrics, TransformKind, TransformStack};
 
+/// Pairs a fit was made from, and the fit.
+type CachedFit = (Vec<([f64; 2], [f64; 2])>, TransformKind);
+
 #[derive(Default)]
 struct AppState {
     map_path: Mutex<Option<String>>,
     reference_path: Mutex<Option<String>>,
     constraints: Mutex<Vec<ConstraintKind>>,
     ref_georef: Mutex<Option<io::Georef>>,
+    /// CRS written to export PRJs when the reference has none
+    fallback_crs: Mutex<Option<String>>,
+    /// Stack from the most recent successful `solve_global`
+    last_stack: Mutex<Option<TransformStack>>,
+    /// Metrics from the most recent successful `solve_global`, with the
+    /// constraints they were computed from (stale once those change)
+    last_metrics: Mutex<Option<(Vec<ConstraintKind>, QualityMetrics)>>,
+    /// Last plain least-squares fit per method, with the pairs it was
+    /// fitted to (see `cached_fit`)
+    fit_cache: Mutex<HashMap<String, CachedFit>>,
+    /// Error unit and map scale from `set_error_unit`, used by `solve_global`
+    /// when it is called without a unit
+    error_unit: Mutex<Option<(String, Option<f64>)>>,
 }
 
 thread_local! {
     static TL_TO_WGS84: RefCell<HashMap<String, proj::Proj>> = RefCell::new(HashMap::new());
-    static TL_UTM: RefCell<HashMap<(i32, bool, &'static str), proj::Proj>> = RefCell::new(HashMap::new());
 }
 
 fn convert_to_wgs84(crs: &str, x: f64, y: f64) -> Result<(f64, f64), String> {
@@ -37,57 +52,58 @@ fn convert_to_wgs84(crs: &str, x: f64, y: f64) -> Result<(f64, f64), String> {
     })
 }
 
-fn convert_to_utm(
-    policy: &str,
-    zone: i32,
-    north: bool,
-    lon: f64,
-    lat: f64,
-) -> Result<(f64, f64), String> {
-    let datum_key: &'static str = match policy {
-        "NAD83_2011" => "NAD83_2011",
-        _ => "WGS84",
-    };
-    TL_UTM.with(|cell| {
-        let mut cache = cell.borrow_mut();
-        let key = (zone, north, datum_key);
-        let proj = cache.entry(key).or_insert_with(|| match datum_key {
-            "NAD83_2011" => proj::Proj::new(&format!(
-                "+proj=utm +zone={} +ellps=GRS80 +units=m +no_defs +type=crs",
-                zone
-            ))
-            .unwrap(),
-            _ => {
-                let epsg = if north {
-                    format!("EPSG:326{}", zone)
-                } else {
-                    format!("EPSG:327{}", zone)
-                };
-                proj::Proj::new_known_crs("EPSG:4326", &epsg, None).unwrap()
-            }
-        });
-        proj.convert((lon, lat)).map_err(|e| e.to_string())
-    })
-}
-
 #[tauri::command]
 fn set_map_path(path: String, state: State<AppState>) -> Result<(), String> {
     *state.map_path.lock().map_err(|e| e.to_string())? = Some(path);
     Ok(())
 }
 
+/// Returns the georeferencing warnings: broken sources skipped, or why the
+/// reference is left without georeferencing.
 #[tauri::command]
-fn set_reference_path(path: String, state: State<AppState>) -> Result<(), String> {
+fn set_reference_path(path: String, state: State<AppState>) -> Result<Vec<String>, String> {
+    set_reference_path_in(&state, path)
+}
+
+fn set_reference_path_in(state: &AppState, path: String) -> Result<Vec<String>, String> {
     // Try robust world/prj sidecars, then GeoTIFF tags; tolerate parsing errors
-    let georef = io::read_georeferencing_for_image(&path).ok().flatten();
+    let (georef, warnings) = match io::read_georeferencing_detailed(&path) {
+        Ok(Some(detail)) => (Some(detail.georef), detail.warnings),
+        Ok(None) => (None, Vec::new()),
+        Err(e) => (None, vec![format!("georeferencing ignored: {}", e)]),
+    };
     *state.ref_georef.lock().map_err(|e| e.to_string())? = georef;
     *state.reference_path.lock().map_err(|e| e.to_string())? = Some(path);
-    Ok(())
+    Ok(warnings)
+}
+
+/// Preview PNG depth from the optional `bit_depth` argument (8 or 16).
+fn preview_depth(bit_depth: Option<u8>) -> Result<io::PreviewDepth, String> {
+    match bit_depth {
+        None | Some(8) => Ok(io::PreviewDepth::Eight),
+        Some(16) => Ok(io::PreviewDepth::Sixteen),
+        Some(other) => Err(format!("unsupported bit depth {}", other)),
+    }
 }
 
 #[tauri::command]
-fn load_raster_data(path: String) -> Result<String, String> {
-    io::load_raster(&path).map_err(|e| e.to_string())
+fn load_raster_data(path: String, bit_depth: Option<u8>) -> Result<io::LoadedRaster, String> {
+    io::load_raster_with_depth(&path, preview_depth(bit_depth)?).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn load_raster_preview_data(
+    path: String,
+    max_dim: u32,
+    bit_depth: Option<u8>,
+) -> Result<String, String> {
+    io::load_raster_preview_with_depth(&path, max_dim, preview_depth(bit_depth)?)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn supported_formats() -> Vec<io::FormatInfo> {
+    io::supported_formats()
 }
 
 #[tauri::command]
@@ -95,31 +111,157 @@ fn get_constraints(state: State<AppState>) -> Result<Vec<ConstraintKind>, String
     Ok(state.constraints.lock().map_err(|e| e.to_string())?.clone())
 }
 
+/// Constraint counts for status displays, without shipping the whole list.
+#[derive(Debug, Default, PartialEq, serde::Serialize)]
+struct ConstraintSummary {
+    total: usize,
+    points: usize,
+    point_pairs: usize,
+    polylines: usize,
+    polygons: usize,
+    anisotropic_pins: usize,
+    anchors: usize,
+    /// Point pairs that survive `solver::pairs_from_constraints` filtering
+    usable_pairs: usize,
+    /// Point pairs with `dst_real` populated
+    pairs_with_real: usize,
+}
+
+#[tauri::command]
+fn constraint_summary(state: State<AppState>) -> Result<ConstraintSummary, String> {
+    constraint_summary_in(&state)
+}
+
+fn constraint_summary_in(state: &AppState) -> Result<ConstraintSummary, String> {
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let mut s = ConstraintSummary {
+        total: list.len(),
+        usable_pairs: solver::pairs_from_constraints(&list).len(),
+        ..Default::default()
+    };
+    for c in list.iter() {
+        match c {
+            ConstraintKind::Point { .. } => s.points += 1,
+            ConstraintKind::PointPair { dst_real, .. } => {
+                s.point_pairs += 1;
+                if dst_real.is_some() {
+                    s.pairs_with_real += 1;
+                }
+            }
+            ConstraintKind::Polyline { .. } => s.polylines += 1,
+            ConstraintKind::Polygon { .. } => s.polygons += 1,
+            ConstraintKind::AnisotropicPin { .. } => s.anisotropic_pins += 1,
+            ConstraintKind::Anchor { .. } => s.anchors += 1,
+        }
+    }
+    Ok(s)
+}
+
+/// Convex hull of the point pairs' map (source) pixels, for drawing the
+/// region where the fit is trusted. Empty with fewer than three usable
+/// points or when they are collinear.
+#[tauri::command]
+fn control_point_hull(state: State<AppState>) -> Result<Vec<[f64; 2]>, String> {
+    control_point_hull_in(&state)
+}
+
+fn control_point_hull_in(state: &AppState) -> Result<Vec<[f64; 2]>, String> {
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let src: Vec<[f64; 2]> = solver::pairs_from_constraints(&list)
+        .into_iter()
+        .map(|(src, _)| src)
+        .collect();
+    Ok(solver::convex_hull(&src))
+}
+
 #[tauri::command]
 fn add_constraint(
-    mut c: ConstraintKind,
+    c: ConstraintKind,
     state: State<AppState>,
 ) -> Result<Vec<ConstraintKind>, String> {
+    add_constraints_in(&state, vec![c])
+}
+
+/// Append many constraints at once, e.g. an import of hundreds of points.
+/// The reference PROJ transformers are built once for the whole batch rather
+/// than per point as repeated `add_constraint` calls would.
+#[tauri::command]
+fn add_constraints_batch(
+    constraints: Vec<ConstraintKind>,
+    state: State<AppState>,
+) -> Result<Vec<ConstraintKind>, String> {
+    add_constraints_in(&state, constraints)
+}
+
+fn add_constraints_in(
+    state: &AppState,
+    mut constraints: Vec<ConstraintKind>,
+) -> Result<Vec<ConstraintKind>, String> {
+    if let Some(projector) = enrichment_projector(state)? {
+        for c in constraints.iter_mut() {
+            enrich_point_pair(c, &projector);
+        }
+    }
+    let mut list = state.constraints.lock().map_err(|e| e.to_string())?;
+    list.extend(constraints);
+    Ok(list.clone())
+}
+
+/// Projector for enriching point pairs, or None without a reference georef.
+/// A CRS PROJ rejects still yields `dst_real`, just no `dst_local`.
+fn enrichment_projector(state: &AppState) -> Result<Option<io::ReferenceProjector>, String> {
+    reference_projector(state, false, false)
+}
+
+/// Fill in `dst_real`/`dst_local` of a point pair from the reference georef.
+/// `dst_local` is on the plane centered at reference pixel (0, 0), as
+/// `io::pixel_to_local_meters`.
+fn enrich_point_pair(c: &mut ConstraintKind, projector: &io::ReferenceProjector) {
     if let ConstraintKind::PointPair {
         dst,
         dst_real,
         dst_local,
         ..
-    } = &mut c
+    } = c
     {
-        if let Some(geo) = state.ref_georef.lock().map_err(|e| e.to_string())?.as_ref() {
-            if dst_real.is_none() {
-                *dst_real = Some(io::pixel_to_world(geo, *dst));
-            }
-            if dst_local.is_none() {
-                if let Ok(Some(local)) = io::pixel_to_local_meters(geo, *dst, [0.0, 0.0]) {
-                    *dst_local = Some(local);
-                }
+        if dst_real.is_none() {
+            *dst_real = Some(projector.to_world(*dst));
+        }
+        if dst_local.is_none() {
+            if let Ok(Some(local)) = projector.to_local_meters(*dst) {
+                *dst_local = Some(local);
             }
         }
     }
+}
+
+#[tauri::command]
+fn import_constraints_csv(
+    path: String,
+    mapping: io::ColumnMapping,
+    state: State<AppState>,
+) -> Result<Vec<ConstraintKind>, String> {
+    let points = io::read_control_points_csv(&path, &mapping).map_err(|e| e.to_string())?;
+    let projector = enrichment_projector(&state)?;
     let mut list = state.constraints.lock().map_err(|e| e.to_string())?;
-    list.push(c);
+    let mut next_id = list.iter().map(|c| c.id()).max().map_or(0, |id| id + 1);
+    for p in points {
+        let mut c = ConstraintKind::PointPair {
+            id: next_id,
+            src: p.src,
+            dst: p.dst,
+            dst_real: None,
+            dst_local: None,
+            src_z: None,
+            dst_z: None,
+            weight: p.weight,
+        };
+        if let Some(projector) = &projector {
+            enrich_point_pair(&mut c, projector);
+        }
+        list.push(c);
+        next_id += 1;
+    }
     Ok(list.clone())
 }
 
@@ -130,57 +272,199 @@ fn delete_constraint(id: u64, state: State<AppState>) -> Result<Vec<ConstraintKi
     Ok(list.clone())
 }
 
+/// Remove every constraint, but only when `confirm` is true; otherwise the
+/// current list is returned unchanged.
+#[tauri::command]
+fn clear_constraints(confirm: bool, state: State<AppState>) -> Result<Vec<ConstraintKind>, String> {
+    clear_constraints_in(&state, confirm)
+}
+
+fn clear_constraints_in(state: &AppState, confirm: bool) -> Result<Vec<ConstraintKind>, String> {
+    let mut list = state.constraints.lock().map_err(|e| e.to_string())?;
+    if confirm {
+        list.clear();
+    }
+    Ok(list.clone())
+}
+
+/// Minimum point pairs `method` needs (`solver::min_pairs`), so the UI can
+/// gate the solve button without duplicating the table.
+#[tauri::command]
+fn get_min_pairs(method: String) -> Result<usize, String> {
+    solver::min_pairs(&method).ok_or_else(|| format!("unknown method {}", method))
+}
+
+/// Store the unit `solve_global`, `solve_to_json`, `point_residual` and
+/// `evaluate_transform` report in when called without one. `mapmm`
+/// needs the 1:N map scale to convert, so it is required for that unit.
+#[tauri::command]
+fn set_error_unit(
+    unit: String,
+    map_scale: Option<f64>,
+    state: State<AppState>,
+) -> Result<(), String> {
+    set_error_unit_in(&state, &unit, map_scale)
+}
+
+fn set_error_unit_in(state: &AppState, unit: &str, map_scale: Option<f64>) -> Result<(), String> {
+    if !matches!(unit, "pixels" | "meters" | "mapmm") {
+        return Err(format!(
+            "unknown error unit {} (expected pixels, meters or mapmm)",
+            unit
+        ));
+    }
+    if let Some(s) = map_scale {
+        if !(s.is_finite() && s > 0.0) {
+            return Err(format!("map scale must be positive, got {}", s));
+        }
+    }
+    if unit == "mapmm" && map_scale.is_none() {
+        return Err("mapmm needs a map scale".to_string());
+    }
+    *state.error_unit.lock().map_err(|e| e.to_string())? = Some((unit.to_string(), map_scale));
+    Ok(())
+}
+
+/// `error_unit`, or the `set_error_unit` preference (pixels if none). An
+/// explicit `map_scale` wins over the preferred one.
+fn resolve_error_unit(
+    state: &AppState,
+    error_unit: Option<&str>,
+    map_scale: Option<f64>,
+) -> Result<(String, Option<f64>), String> {
+    let preferred = state.error_unit.lock().map_err(|e| e.to_string())?.clone();
+    Ok(match (error_unit, preferred) {
+        (Some(unit), _) => (unit.to_string(), map_scale),
+        (None, Some((unit, scale))) => (unit, map_scale.or(scale)),
+        (None, None) => ("pixels".to_string(), map_scale),
+    })
+}
+
 #[tauri::command]
 fn solve_global(
     method: String,
-    error_unit: String,
+    error_unit: Option<String>,
     map_scale: Option<f64>,
+    max_residual: Option<f64>,
+    coordinate_space: Option<solver::CoordinateSpace>,
     state: State<AppState>,
 ) -> Result<(TransformStack, QualityMetrics), String> {
-    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let (error_unit, map_scale) = resolve_error_unit(&state, error_unit.as_deref(), map_scale)?;
+    solve_global_in(
+        &state,
+        &method,
+        &error_unit,
+        map_scale,
+        max_residual,
+        coordinate_space.unwrap_or_default(),
+    )
+}
+
+fn solve_global_in(
+    state: &AppState,
+    method: &str,
+    error_unit: &str,
+    map_scale: Option<f64>,
+    max_residual: Option<f64>,
+    space: solver::CoordinateSpace,
+) -> Result<(TransformStack, QualityMetrics), String> {
+    let snapshot = state.constraints.lock().map_err(|e| e.to_string())?.clone();
+    let list = solver::constraints_in_space(&snapshot, space).map_err(|e| e.to_string())?;
+    let in_pixels = space == solver::CoordinateSpace::Pixels;
     let pairs = solver::pairs_from_constraints(&list);
-    if pairs.len() < 2 && method == "similarity" {
-        return Err(format!("need ≥2 pairs; got {}", pairs.len()));
-    }
-    if pairs.len() < 3 && method == "affine" {
-        return Err(format!("need ≥3 pairs; got {}", pairs.len()));
-    }
+    solver::can_solve(method, pairs.len()).map_err(|e| e.to_string())?;
     let mut warnings = Vec::new();
     if variance_low(&pairs) {
         warnings.push("Low variance in source points; results may be unstable".to_string());
     }
-    let pixel_size = state
-        .ref_georef
+    let map_path = state.map_path.lock().map_err(|e| e.to_string())?.clone();
+    let map_wh = map_path
+        .and_then(|p| io::image_dimensions(&p).ok())
+        .map(|(w, h)| [w as f64, h as f64]);
+    if let Some(img_wh) = map_wh {
+        if !solver::well_distributed(&pairs, img_wh) {
+            let empty = solver::empty_quadrants(&pairs, img_wh);
+            let mut msg = format!(
+                "Control points cover {:.0}% of the map; fits may be unreliable away from them",
+                solver::coverage_ratio(&pairs, img_wh) * 100.0
+            );
+            if !empty.is_empty() {
+                msg.push_str(&format!(" (add points in the {})", empty.join(", ")));
+            }
+            warnings.push(msg);
+        }
+    }
+    // Image extents only bound the points pixel to pixel
+    let ref_wh = state
+        .reference_path
         .lock()
         .map_err(|e| e.to_string())?
-        .as_ref()
-        .map(|g| {
-            let ax = (g.affine[0].powi(2) + g.affine[2].powi(2)).sqrt();
-            let ay = (g.affine[1].powi(2) + g.affine[3].powi(2)).sqrt();
-            (ax + ay) / 2.0
-        })
-        .unwrap_or(1.0);
-    let target_unit = match error_unit.as_str() {
-        "meters" => ErrorUnit::Meters,
-        "mapmm" => ErrorUnit::MapMillimeters,
-        _ => ErrorUnit::Pixels,
+        .clone()
+        .and_then(|p| io::image_dimensions(&p).ok())
+        .map(|(w, h)| [w as f64, h as f64]);
+    let swapped = match (map_wh, ref_wh) {
+        (Some(m), Some(r)) if in_pixels => solver::detect_swapped(&pairs, m, r),
+        _ => false,
+    };
+    if swapped {
+        warnings.push(
+            "Point pairs look reversed (reference clicked before map); did you mean to swap src/dst?"
+                .to_string(),
+        );
+    }
+    for ids in solver::duplicate_destinations(&list, DUPLICATE_DST_TOL_PX) {
+        warnings.push(format!(
+            "Points {:?} share a reference location but have different map locations",
+            ids
+        ));
+    }
+    let pixel_size = reference_pixel_size(state)?;
+    let target_unit = parse_error_unit(error_unit);
+    let residual_unit = match space {
+        solver::CoordinateSpace::Pixels => ErrorUnit::Pixels,
+        solver::CoordinateSpace::LocalMeters => ErrorUnit::Meters,
+        solver::CoordinateSpace::World => {
+            let crs = state
+                .ref_georef
+                .lock()
+                .map_err(|e| e.to_string())?
+                .as_ref()
+                .and_then(|g| g.wkt.clone());
+            // Degrees cannot pass for meters; the local plane is the metric
+            // alternative
+            if crs.is_some_and(|c| io::crs_is_geographic(&c)) {
+                return Err(
+                    "World-space residuals would be in degrees for a geographic reference CRS; use local_meters"
+                        .to_string(),
+                );
+            }
+            warnings.push(
+                "World-space residuals are in CRS units and treated as meters; use a projected CRS"
+                    .to_string(),
+            );
+            ErrorUnit::Meters
+        }
     };
 
-    match method.as_str() {
+    // Reference pixel size in the unit the residuals come out in
+    let residual_px = if in_pixels { 1.0 } else { pixel_size };
+    let solved = match method {
         "similarity" => {
-            let t = solver::fit_similarity_from_pairs(&pairs).map_err(|e| e.to_string())?;
-            let (rmse, p90, residuals) = metrics_similarity(&t, &pairs);
-            let residuals_by_id = residuals_by_id_similarity(&t, &list);
-            let mut qm = QualityMetrics {
-                rmse,
-                p90_error: p90,
-                residuals,
-                residuals_by_id,
-                warnings,
-                unit: ErrorUnit::Pixels,
-                map_scale: None,
+            let TransformKind::Similarity(t) = cached_fit(state, method, &pairs)? else {
+                unreachable!("similarity fit")
             };
-            if target_unit != ErrorUnit::Pixels {
+            solver::check_max_residual(&t, &list, max_residual).map_err(|e| e.to_string())?;
+            let mut qm = solver::residual_metrics(&t, &pairs, &list);
+            warnings.extend(axis_imbalance_warning(qm.rmse_x, qm.rmse_y, residual_px));
+            if let Some(img_wh) = map_wh.filter(|_| in_pixels) {
+                warnings.extend(solver::sanity_check(
+                    &TransformKind::Similarity(t.clone()),
+                    img_wh,
+                ));
+            }
+            qm.warnings = warnings;
+            qm.unit = residual_unit;
+            if target_unit != residual_unit {
                 qm.convert_units(pixel_size, map_scale, target_unit);
             } else {
                 qm.map_scale = map_scale;
@@ -193,19 +477,21 @@ fn solve_global(
             ))
         }
         "affine" => {
-            let t = solver::fit_affine_from_pairs(&pairs).map_err(|e| e.to_string())?;
-            let (rmse, p90, residuals) = metrics_affine(&t, &pairs);
-            let residuals_by_id = residuals_by_id_affine(&t, &list);
-            let mut qm = QualityMetrics {
-                rmse,
-                p90_error: p90,
-                residuals,
-                residuals_by_id,
-                warnings,
-                unit: ErrorUnit::Pixels,
-                map_scale: None,
+            let TransformKind::Affine(t) = cached_fit(state, method, &pairs)? else {
+                unreachable!("affine fit")
             };
-            if target_unit != ErrorUnit::Pixels {
+            solver::check_max_residual(&t, &list, max_residual).map_err(|e| e.to_string())?;
+            let mut qm = solver::residual_metrics(&t, &pairs, &list);
+            warnings.extend(axis_imbalance_warning(qm.rmse_x, qm.rmse_y, residual_px));
+            if let Some(img_wh) = map_wh.filter(|_| in_pixels) {
+                warnings.extend(solver::sanity_check(
+                    &TransformKind::Affine(t.clone()),
+                    img_wh,
+                ));
+            }
+            qm.warnings = warnings;
+            qm.unit = residual_unit;
+            if target_unit != residual_unit {
                 qm.convert_units(pixel_size, map_scale, target_unit);
             } else {
                 qm.map_scale = map_scale;
@@ -218,48 +504,416 @@ fn solve_global(
             ))
         }
         _ => Err(format!("unknown method {}", method)),
+    }?;
+    *state.last_stack.lock().map_err(|e| e.to_string())? = Some(solved.0.clone());
+    *state.last_metrics.lock().map_err(|e| e.to_string())? = Some((snapshot, solved.1.clone()));
+    Ok(solved)
+}
+
+/// The transform stack from the most recent successful `solve_global`, or
+/// None before the first solve.
+#[tauri::command]
+fn get_transform_stack(state: State<AppState>) -> Result<Option<TransformStack>, String> {
+    Ok(state.last_stack.lock().map_err(|e| e.to_string())?.clone())
+}
+
+/// Write the most recently solved stack to `path` as JSON, for archiving the
+/// exact transform used; its entries can be fed back to
+/// `evaluate_transform`.
+#[tauri::command]
+fn export_transform_stack(path: String, state: State<AppState>) -> Result<(), String> {
+    export_transform_stack_in(&state, &path)
+}
+
+fn export_transform_stack_in(state: &AppState, path: &str) -> Result<(), String> {
+    let stack = state
+        .last_stack
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or_else(|| "no transform solved yet".to_string())?;
+    let json = serde_json::to_string_pretty(&stack).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// Write the current point pairs as a QGIS `.points` file. Map coordinates
+/// come from `dst_real`, or from the reference georeferencing when a pair
+/// predates it.
+#[tauri::command]
+fn export_gcp_points(path: String, state: State<AppState>) -> Result<(), String> {
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let geo = state.ref_georef.lock().map_err(|e| e.to_string())?.clone();
+    let mut points = Vec::new();
+    for c in list.iter() {
+        if let ConstraintKind::PointPair {
+            id,
+            src,
+            dst,
+            dst_real,
+            ..
+        } = c
+        {
+            let map = dst_real
+                .or_else(|| geo.as_ref().map(|g| io::pixel_to_world(g, *dst)))
+                .ok_or_else(|| {
+                    format!(
+                        "point {} has no map coordinates; set a georeferenced reference",
+                        id
+                    )
+                })?;
+            points.push(io::GcpPoint {
+                map,
+                pixel: *src,
+                enabled: true,
+            });
+        }
     }
+    let crs = geo.as_ref().and_then(|g| g.wkt.as_deref());
+    io::write_gcp_points(&path, &points, crs).map_err(|e| e.to_string())
 }
 
-fn metrics_similarity(
-    t: &types::Similarity,
-    pairs: &[([f64; 2], [f64; 2])],
-) -> (f64, f64, Vec<f64>) {
-    let residuals: Vec<f64> = pairs
-        .iter()
-        .map(|(src, dst)| {
-            let s = nalgebra::Vector2::from(*src);
-            let d = nalgebra::Vector2::from(*dst);
-            let pred = solver::Transform::apply(t, &s);
-            (pred - d).norm()
-        })
-        .collect();
-    summarize_residuals(residuals)
+/// Warp the map image north-up into world coordinates using the fitted
+/// transform (composed as in `preview_export`), writing the raster, its world
+/// file and a PRJ. With `tile_size` the output is warped block by block into a
+/// striped TIFF (`io::warp_raster_tiled`) so large outputs stay in bounded
+/// memory.
+#[tauri::command]
+fn warp_map(
+    method: String,
+    output_path: String,
+    options: io::WarpOptions,
+    tile_size: Option<u32>,
+    state: State<AppState>,
+) -> Result<io::WarpGrid, String> {
+    let preview = build_export_preview(&method, &state)?;
+    let map_path = state
+        .map_path
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or_else(|| "map path not set".to_string())?;
+    let grid = match tile_size {
+        Some(tile) => {
+            io::warp_raster_tiled(&map_path, preview.affine, &output_path, &options, tile)
+        }
+        None => io::warp_raster(&map_path, preview.affine, &output_path, &options),
+    }
+    .map_err(|e| e.to_string())?;
+    let base = std::path::Path::new(&output_path)
+        .with_extension("")
+        .to_string_lossy()
+        .into_owned();
+    io::write_prj(&base, &preview.prj_wkt).map_err(|e| e.to_string())?;
+    Ok(grid)
+}
+
+/// Preview of `warp_map` as a PNG data URI no larger than `max_dim` pixels.
+#[tauri::command]
+fn preview_warp(
+    method: String,
+    max_dim: u32,
+    options: io::WarpOptions,
+    state: State<AppState>,
+) -> Result<String, String> {
+    let preview = build_export_preview(&method, &state)?;
+    let map_path = state
+        .map_path
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or_else(|| "map path not set".to_string())?;
+    io::warp_preview(&map_path, preview.affine, max_dim, &options).map_err(|e| e.to_string())
+}
+
+/// `solve_global` packaged as one `solver::SolveResult` JSON document for
+/// scripting.
+#[tauri::command]
+fn solve_to_json(
+    method: String,
+    error_unit: Option<String>,
+    map_scale: Option<f64>,
+    max_residual: Option<f64>,
+    state: State<AppState>,
+) -> Result<String, String> {
+    let result = solve_to_json_in(
+        &state,
+        &method,
+        error_unit.as_deref(),
+        map_scale,
+        max_residual,
+    )?;
+    serde_json::to_string_pretty(&result).map_err(|e| e.to_string())
+}
+
+fn solve_to_json_in(
+    state: &AppState,
+    method: &str,
+    error_unit: Option<&str>,
+    map_scale: Option<f64>,
+    max_residual: Option<f64>,
+) -> Result<solver::SolveResult, String> {
+    let fit_method = match method {
+        "similarity" => solver::FitMethod::Similarity,
+        "affine" => solver::FitMethod::Affine,
+        _ => return Err(format!("unknown method {}", method)),
+    };
+    let (error_unit, map_scale) = resolve_error_unit(state, error_unit, map_scale)?;
+    let (stack, metrics) = solve_global_in(
+        state,
+        method,
+        &error_unit,
+        map_scale,
+        max_residual,
+        solver::CoordinateSpace::Pixels,
+    )?;
+    // One residual per usable pair
+    let n_pairs = metrics.residuals.len();
+    Ok(solver::SolveResult {
+        method: fit_method,
+        transform: stack
+            .transforms
+            .into_iter()
+            .next()
+            .ok_or_else(|| "solve produced no transform".to_string())?,
+        metrics,
+        n_pairs,
+    })
+}
+
+/// Ground size of a reference pixel in meters (1.0 without georeferencing).
+/// With a CRS it is measured projection-aware at the reference image center
+/// (pixel (0, 0) if the image size is unknown), so lon/lat references report
+/// meters rather than degrees; without one the CRS units are taken as meters.
+fn reference_pixel_size(state: &AppState) -> Result<f64, String> {
+    let Some(g) = state.ref_georef.lock().map_err(|e| e.to_string())?.clone() else {
+        return Ok(1.0);
+    };
+    if g.wkt.is_some() {
+        let ref_path = state
+            .reference_path
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone();
+        let [u, v] = ref_path
+            .and_then(|p| io::image_dimensions(&p).ok())
+            .map(|(w, h)| [w as f64 / 2.0, h as f64 / 2.0])
+            .unwrap_or([0.0, 0.0]);
+        if let Some(mpp) = meters_per_pixel_at(&g, u, v) {
+            return Ok(mpp);
+        }
+    }
+    let ax = (g.affine[0].powi(2) + g.affine[2].powi(2)).sqrt();
+    let ay = (g.affine[1].powi(2) + g.affine[3].powi(2)).sqrt();
+    Ok((ax + ay) / 2.0)
+}
+
+fn parse_error_unit(error_unit: &str) -> ErrorUnit {
+    match error_unit {
+        "meters" => ErrorUnit::Meters,
+        "mapmm" => ErrorUnit::MapMillimeters,
+        _ => ErrorUnit::Pixels,
+    }
+}
+
+/// Residual of point pair `id` under a fresh global fit, in `error_unit` or the
+/// preferred unit (same conversion as `solve_global`). None when `id` is not a
+/// point pair.
+#[tauri::command]
+fn point_residual(
+    id: u64,
+    method: String,
+    error_unit: Option<String>,
+    map_scale: Option<f64>,
+    state: State<AppState>,
+) -> Result<Option<f64>, String> {
+    point_residual_in(&state, id, &method, error_unit.as_deref(), map_scale)
+}
+
+fn point_residual_in(
+    state: &AppState,
+    id: u64,
+    method: &str,
+    error_unit: Option<&str>,
+    map_scale: Option<f64>,
+) -> Result<Option<f64>, String> {
+    let (error_unit, map_scale) = resolve_error_unit(state, error_unit, map_scale)?;
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let pairs = solver::pairs_from_constraints(&list);
+    let t = cached_fit(state, method, &pairs)?;
+    let residuals = solver::residuals_by_id(&t, &list);
+    drop(list);
+    let Some(&(_, r)) = residuals.iter().find(|(rid, _)| *rid == id) else {
+        return Ok(None);
+    };
+    let mut single = QualityMetrics {
+        rmse: r,
+        ..Default::default()
+    };
+    single.convert_units(
+        reference_pixel_size(state)?,
+        map_scale,
+        parse_error_unit(&error_unit),
+    );
+    Ok(Some(single.rmse))
+}
+
+/// Similarity from the current point pairs for fast two-point placement, with
+/// a note when the fit is exact (see `solver::quick_align`).
+#[tauri::command]
+fn quick_align(state: State<AppState>) -> Result<(types::Similarity, Option<String>), String> {
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let pairs = solver::pairs_from_constraints(&list);
+    solver::quick_align(&pairs).map_err(|e| e.to_string())
+}
+
+/// Score a transform from elsewhere (e.g. a prior project) against the current
+/// point pairs without refitting. Metrics are computed as in `solve_global`
+/// and reported in `error_unit` or the preferred unit.
+#[tauri::command]
+fn evaluate_transform(
+    transform: TransformKind,
+    error_unit: Option<String>,
+    map_scale: Option<f64>,
+    state: State<AppState>,
+) -> Result<QualityMetrics, String> {
+    evaluate_transform_in(&state, &transform, error_unit.as_deref(), map_scale)
+}
+
+fn evaluate_transform_in(
+    state: &AppState,
+    transform: &TransformKind,
+    error_unit: Option<&str>,
+    map_scale: Option<f64>,
+) -> Result<QualityMetrics, String> {
+    let (error_unit, map_scale) = resolve_error_unit(state, error_unit, map_scale)?;
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let pairs = solver::pairs_from_constraints(&list);
+    if pairs.is_empty() {
+        return Err("no point pairs to evaluate against".to_string());
+    }
+    let mut qm = solver::residual_metrics(transform, &pairs, &list);
+    drop(list);
+    qm.warnings
+        .extend(axis_imbalance_warning(qm.rmse_x, qm.rmse_y, 1.0));
+    let target_unit = parse_error_unit(&error_unit);
+    if target_unit != ErrorUnit::Pixels {
+        qm.convert_units(reference_pixel_size(state)?, map_scale, target_unit);
+    } else {
+        qm.map_scale = map_scale;
+    }
+    Ok(qm)
 }
 
-fn metrics_affine(t: &types::Affine, pairs: &[([f64; 2], [f64; 2])]) -> (f64, f64, Vec<f64>) {
-    let residuals: Vec<f64> = pairs
+/// Expected error at map pixel (u, v): residuals of a fresh global fit,
+/// interpolated from the map positions of the point pairs by inverse distance
+/// weighting with power `solver::IDW_POWER`. Reference pixels; None without
+/// point pairs.
+#[tauri::command]
+fn residual_at(
+    u: f64,
+    v: f64,
+    method: String,
+    state: State<AppState>,
+) -> Result<Option<f64>, String> {
+    residual_at_in(&state, u, v, &method)
+}
+
+fn residual_at_in(state: &AppState, u: f64, v: f64, method: &str) -> Result<Option<f64>, String> {
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let pairs = solver::pairs_from_constraints(&list);
+    let t = cached_fit(state, method, &pairs)?;
+    let samples: Vec<([f64; 2], f64)> = pairs
         .iter()
-        .map(|(src, dst)| {
-            let s = nalgebra::Vector2::from(*src);
-            let d = nalgebra::Vector2::from(*dst);
-            let pred = solver::Transform::apply(t, &s);
-            (pred - d).norm()
-        })
+        .map(|p| p.0)
+        .zip(solver::pair_residuals(&t, &pairs))
         .collect();
-    summarize_residuals(residuals)
+    Ok(solver::idw_interpolate(&samples, [u, v], solver::IDW_POWER))
 }
 
-fn summarize_residuals(mut residuals: Vec<f64>) -> (f64, f64, Vec<f64>) {
-    if residuals.is_empty() {
-        return (0.0, 0.0, residuals);
+/// Up to `n` map pixel locations for new control points in the areas the
+/// current pairs leave uncovered (`solver::suggest_control_points`). Needs
+/// the map image for its size.
+#[tauri::command]
+fn suggest_control_points(n: usize, state: State<AppState>) -> Result<Vec<[f64; 2]>, String> {
+    suggest_control_points_in(&state, n)
+}
+
+fn suggest_control_points_in(state: &AppState, n: usize) -> Result<Vec<[f64; 2]>, String> {
+    let map_path = state
+        .map_path
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or_else(|| "map path not set".to_string())?;
+    let (w, h) = io::image_dimensions(&map_path).map_err(|e| e.to_string())?;
+    let pairs =
+        solver::pairs_from_constraints(&state.constraints.lock().map_err(|e| e.to_string())?);
+    Ok(solver::suggest_control_points(
+        &pairs,
+        [w as f64, h as f64],
+        n,
+    ))
+}
+
+/// Affine-fit leverage per point-pair id (see `solver::leverages`), so the UI
+/// can flag isolated points that dominate the fit. Pairs dropped by
+/// `pairs_from_constraints` (duplicates, NaN) get no entry.
+#[tauri::command]
+fn point_leverages(state: State<AppState>) -> Result<Vec<(u64, f64)>, String> {
+    point_leverages_in(&state)
+}
+
+fn point_leverages_in(state: &AppState) -> Result<Vec<(u64, f64)>, String> {
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let pairs = solver::pairs_from_constraints(&list);
+    let h = solver::leverages(&pairs).map_err(|e| e.to_string())?;
+    // Kept pairs are an in-order subsequence of the point pairs
+    let mut out = Vec::with_capacity(h.len());
+    let mut k = 0;
+    for c in list.iter() {
+        if let ConstraintKind::PointPair { id, src, dst, .. } = c {
+            if k < pairs.len() && pairs[k] == (*src, *dst) {
+                out.push((*id, h[k]));
+                k += 1;
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Refine an imported prior transform against the current point pairs (see
+/// `solver::refine_from_prior`).
+#[tauri::command]
+fn refine_transform(
+    prior: TransformKind,
+    state: State<AppState>,
+) -> Result<solver::Refinement, String> {
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let pairs = solver::pairs_from_constraints(&list);
+    solver::refine_from_prior(&prior, &pairs).map_err(|e| e.to_string())
+}
+
+/// Reference points closer than this (pixels) count as the same location.
+const DUPLICATE_DST_TOL_PX: f64 = 0.5;
+
+/// Per-axis RMSE (reference pixels) below which `axis_imbalance_warning`
+/// stays quiet: a sub-pixel fit has no aspect problem worth reporting.
+const AXIS_IMBALANCE_FLOOR_PX: f64 = 0.5;
+
+/// Warn when one axis carries much more error than the other, which usually
+/// means the map's aspect ratio is off (e.g. a stretched scan). `pixel_size`
+/// is the size of a reference pixel in the residuals' unit (1 for pixels),
+/// used to scale `AXIS_IMBALANCE_FLOOR_PX`.
+fn axis_imbalance_warning(rmse_x: f64, rmse_y: f64, pixel_size: f64) -> Option<String> {
+    let (lo, hi) = (rmse_x.min(rmse_y), rmse_x.max(rmse_y));
+    if hi > AXIS_IMBALANCE_FLOOR_PX * pixel_size && hi > 2.0 * lo {
+        Some(format!(
+            "Residuals are anisotropic (RMSE x {:.3}, y {:.3}); check the map aspect ratio",
+            rmse_x, rmse_y
+        ))
+    } else {
+        None
     }
-    let rmse = (residuals.iter().map(|r| r * r).sum::<f64>() / residuals.len() as f64).sqrt();
-    residuals.sort_by(|a, b| a.partial_cmp(b).unwrap());
-    let idx = ((residuals.len() as f64) * 0.9).floor() as usize;
-    let idx = idx.min(residuals.len() - 1);
-    let p90 = residuals[idx];
-    (rmse, p90, residuals)
 }
 
 fn variance_low(pairs: &[([f64; 2], [f64; 2])]) -> bool {
@@ -277,113 +931,239 @@ fn variance_low(pairs: &[([f64; 2], [f64; 2])]) -> bool {
     var < 1e-6
 }
 
-fn residuals_by_id_similarity(
-    t: &types::Similarity,
-    constraints: &[ConstraintKind],
-) -> Vec<(u64, f64)> {
-    constraints
-        .iter()
-        .filter_map(|c| match c {
-            ConstraintKind::PointPair { id, src, dst, .. } => {
-                let s = nalgebra::Vector2::from(*src);
-                let d = nalgebra::Vector2::from(*dst);
-                let pred = solver::Transform::apply(t, &s);
-                Some((*id, (pred - d).norm()))
-            }
-            _ => None,
-        })
-        .collect()
+#[tauri::command]
+fn fit_verdict(metrics: QualityMetrics, pixel_size: f64) -> types::Verdict {
+    types::fit_verdict(&metrics, pixel_size)
 }
 
-fn residuals_by_id_affine(t: &types::Affine, constraints: &[ConstraintKind]) -> Vec<(u64, f64)> {
-    constraints
-        .iter()
-        .filter_map(|c| match c {
-            ConstraintKind::PointPair { id, src, dst, .. } => {
-                let s = nalgebra::Vector2::from(*src);
-                let d = nalgebra::Vector2::from(*dst);
-                let pred = solver::Transform::apply(t, &s);
-                Some((*id, (pred - d).norm()))
-            }
-            _ => None,
-        })
-        .collect()
+/// Point ids whose residual exceeds `factor` × RMSE (see
+/// `QualityMetrics::outlier_ids`), for highlighting in the residuals table.
+#[tauri::command]
+fn outlier_ids(metrics: QualityMetrics, factor: f64) -> Vec<u64> {
+    metrics.outlier_ids(factor)
+}
+
+/// Scale at which `extent_m` of ground fills `paper_m` of paper, exact and
+/// rounded up to a standard scale (see `solver::scale_for_paper`).
+#[tauri::command]
+fn scale_for_paper(extent_m: f64, paper_m: f64) -> Result<(f64, f64), String> {
+    if !(extent_m > 0.0 && paper_m > 0.0 && extent_m.is_finite() && paper_m.is_finite()) {
+        return Err("extent and paper size must be positive".to_string());
+    }
+    let exact = solver::scale_for_paper(extent_m, paper_m);
+    Ok((exact, solver::nice_map_scale(exact)))
 }
 
+/// Scale deviation of a fitted similarity from 1, in ppm (see
+/// `solver::scale_deviation_ppm`).
 #[tauri::command]
-fn get_proj_string(method: String, state: State<AppState>) -> Result<String, String> {
+fn scale_deviation_ppm(similarity: types::Similarity) -> f64 {
+    solver::scale_deviation_ppm(&similarity)
+}
+
+#[tauri::command]
+fn get_proj_string(
+    method: String,
+    pixel_origin: Option<String>,
+    state: State<AppState>,
+) -> Result<String, String> {
+    let origin = match pixel_origin.as_deref() {
+        None | Some("center") => solver::PixelOrigin::Center,
+        Some("corner") => solver::PixelOrigin::Corner,
+        Some(other) => return Err(format!("unknown pixel origin {}", other)),
+    };
     let list = state.constraints.lock().map_err(|e| e.to_string())?;
     let pairs = solver::pairs_from_constraints(&list);
-    match method.as_str() {
-        "similarity" => {
-            let t = solver::fit_similarity_from_pairs(&pairs).map_err(|e| e.to_string())?;
-            Ok(solver::similarity_to_proj(&t))
+    match cached_fit(&state, &method, &pairs)? {
+        TransformKind::Similarity(t) => Ok(solver::similarity_to_proj(&t, origin)),
+        TransformKind::Affine(t) => Ok(solver::affine_to_proj(&t, origin)),
+        _ => unreachable!("cached_fit only fits similarities and affines"),
+    }
+}
+
+/// Fit `method` to `pairs` and express the result as affine params [a,b,c,d,tx,ty].
+fn fit_affine(
+    state: &AppState,
+    method: &str,
+    pairs: &[([f64; 2], [f64; 2])],
+) -> Result<types::Affine, String> {
+    match cached_fit(state, method, pairs)? {
+        TransformKind::Similarity(t) => Ok(solver::similarity_to_affine(&t)),
+        TransformKind::Affine(t) => Ok(t),
+        _ => unreachable!("cached_fit only fits similarities and affines"),
+    }
+}
+
+/// Least-squares `method` ("similarity" or "affine") fit of `pairs`, reused
+/// from `AppState::fit_cache` when the last fit for `method` was made from
+/// the same pairs. Comparing the pairs themselves rather than tracking edits
+/// means any change to the constraints (add, delete, move, reproject)
+/// invalidates the entry, whichever command made it; the comparison costs
+/// the same O(n) as hashing would, without the risk of a collision returning
+/// another set's fit.
+fn cached_fit(
+    state: &AppState,
+    method: &str,
+    pairs: &[([f64; 2], [f64; 2])],
+) -> Result<TransformKind, String> {
+    solver::can_solve(method, pairs.len()).map_err(|e| e.to_string())?;
+    let mut cache = state.fit_cache.lock().map_err(|e| e.to_string())?;
+    // f64 equality: 0.0 and -0.0 match, which is fine since they fit the
+    // same; NaN never does, but `pairs_from_constraints` drops those
+    if let Some((fitted, t)) = cache.get(method) {
+        if fitted.as_slice() == pairs {
+            return Ok(t.clone());
         }
+    }
+    let t = match method {
+        "similarity" => TransformKind::Similarity(
+            solver::fit_similarity_from_pairs(pairs).map_err(|e| e.to_string())?,
+        ),
         "affine" => {
-            let t = solver::fit_affine_from_pairs(&pairs).map_err(|e| e.to_string())?;
-            Ok(solver::affine_to_proj(&t))
+            TransformKind::Affine(solver::fit_affine_from_pairs(pairs).map_err(|e| e.to_string())?)
         }
-        _ => Err(format!("unknown method {}", method)),
+        _ => return Err(format!("unknown method {}", method)),
+    };
+    cache.insert(method.to_string(), (pairs.to_vec(), t.clone()));
+    Ok(t)
+}
+
+/// Fit map -> reference pixels, then chain through the reference's stored
+/// georeferencing (pixels -> world) to get map pixels -> world directly. The
+/// reference may itself be an image georeferenced against another source.
+#[tauri::command]
+fn solve_chained(method: String, state: State<AppState>) -> Result<TransformKind, String> {
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let pairs = solver::pairs_from_constraints(&list);
+    let map2ref = cached_fit(&state, &method, &pairs)?;
+    let geo = state
+        .ref_georef
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or_else(|| "reference has no georeferencing".to_string())?;
+    let ref2world = TransformKind::Affine(types::Affine { params: geo.affine });
+    solver::chain_transforms(&map2ref, &ref2world).map_err(|e| e.to_string())
+}
+
+/// Local scale, rotation and shear of the fitted map -> reference transform
+/// at map pixel (u, v) (see `solver::local_jacobian`).
+#[tauri::command]
+fn local_jacobian(
+    method: String,
+    u: f64,
+    v: f64,
+    state: State<AppState>,
+) -> Result<solver::LocalJacobian, String> {
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let pairs = solver::pairs_from_constraints(&list);
+    let t = fit_affine(&state, &method, &pairs)?;
+    Ok(solver::local_jacobian(&t, [u, v]))
+}
+
+/// Push a map-pixel polygon through the fitted map -> reference transform.
+/// `densify_px` splits edges first, for transforms that bend straight lines.
+#[tauri::command]
+fn transform_polygon(
+    method: String,
+    points: Vec<[f64; 2]>,
+    densify_px: Option<f64>,
+    state: State<AppState>,
+) -> Result<Vec<[f64; 2]>, String> {
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let pairs = solver::pairs_from_constraints(&list);
+    let t = fit_affine(&state, &method, &pairs)?;
+    solver::transform_polygon(&t, &points, densify_px).map_err(|e| e.to_string())
+}
+
+/// `affine` as exported under the `y_axis_down` option shared by the export
+/// commands. The default (true) keeps row 0 at the top as world files and
+/// GDAL expect; false rewrites it for bottom-left-origin consumers (see
+/// `io::flip_affine_rows`), using the height of the raster the affine
+/// describes: `image_path`, else the map image.
+fn oriented_affine(
+    state: &AppState,
+    affine: [f64; 6],
+    y_axis_down: Option<bool>,
+    image_path: Option<&str>,
+) -> Result<[f64; 6], String> {
+    if y_axis_down.unwrap_or(true) {
+        return Ok(affine);
     }
+    let path = match image_path {
+        Some(p) => p.to_string(),
+        None => state
+            .map_path
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or_else(|| "map path not set".to_string())?,
+    };
+    let (_, h) = io::image_dimensions(&path).map_err(|e| e.to_string())?;
+    Ok(io::flip_affine_rows(affine, h))
 }
 
 #[tauri::command]
 fn export_world_file(
     path_without_ext: String,
     method: String,
+    y_axis_down: Option<bool>,
+    precision: Option<usize>,
     state: State<AppState>,
+) -> Result<(), String> {
+    export_world_file_in(&state, &path_without_ext, &method, y_axis_down, precision)
+}
+
+fn export_world_file_in(
+    state: &AppState,
+    path_without_ext: &str,
+    method: &str,
+    y_axis_down: Option<bool>,
+    precision: Option<usize>,
 ) -> Result<(), String> {
     let list = state.constraints.lock().map_err(|e| e.to_string())?;
     let pairs = solver::pairs_from_constraints(&list);
-    match method.as_str() {
-        "similarity" => {
-            let t = solver::fit_similarity_from_pairs(&pairs).map_err(|e| e.to_string())?;
-            // Convert similarity to affine params [a,b,c,d,tx,ty]
-            let s = t.params[0];
-            let th = t.params[1];
-            let (c, si) = (th.cos(), th.sin());
-            let a = s * c;
-            let b = -s * si;
-            let d = s * si;
-            let e = s * c;
-            let tx = t.params[2];
-            let ty = t.params[3];
-            io::write_world_file(&path_without_ext, [a, b, d, e, tx, ty]).map_err(|e| e.to_string())
-        }
-        "affine" => {
-            let t = solver::fit_affine_from_pairs(&pairs).map_err(|e| e.to_string())?;
-            let p = t.params;
-            io::write_world_file(&path_without_ext, [p[0], p[1], p[2], p[3], p[4], p[5]])
-                .map_err(|e| e.to_string())
-        }
-        _ => Err(format!("unknown method {}", method)),
-    }
+    let t = fit_affine(state, method, &pairs)?;
+    drop(list);
+    let affine = oriented_affine(state, t.params, y_axis_down, None)?;
+    io::write_world_file_with_precision(path_without_ext, affine, precision)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn export_georeferenced_geotiff(
-    state: State<AppState>,
+fn export_world_file_for_image(
+    image_path: String,
     method: String,
-    output_without_ext: String,
-) -> Result<(), String> {
+    y_axis_down: Option<bool>,
+    precision: Option<usize>,
+    state: State<AppState>,
+) -> Result<String, String> {
+    export_world_file_for_image_in(&state, &image_path, &method, y_axis_down, precision)
+}
+
+fn export_world_file_for_image_in(
+    state: &AppState,
+    image_path: &str,
+    method: &str,
+    y_axis_down: Option<bool>,
+    precision: Option<usize>,
+) -> Result<String, String> {
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let pairs = solver::pairs_from_constraints(&list);
+    let t = fit_affine(state, method, &pairs)?;
+    drop(list);
+    let affine = oriented_affine(state, t.params, y_axis_down, Some(image_path))?;
+    let written = io::write_world_file_for_image_with_precision(image_path, affine, precision)
+        .map_err(|e| e.to_string())?;
+    Ok(written.to_string_lossy().into_owned())
+}
+
+/// Compute what `export_georeferenced_geotiff` would write, without touching disk.
+fn build_export_preview(method: &str, state: &AppState) -> Result<io::ExportPreview, String> {
     // Compose map->ref pixel transform with ref pixel->world from .tfw or default
     let list = state.constraints.lock().map_err(|e| e.to_string())?;
     let pairs = solver::pairs_from_constraints(&list);
-    let map2ref = match method.as_str() {
-        "similarity" => {
-            let t = solver::fit_similarity_from_pairs(&pairs).map_err(|e| e.to_string())?;
-            let s = t.params[0];
-            let th = t.params[1];
-            let (c, si) = (th.cos(), th.sin());
-            [s * c, -s * si, s * si, s * c, t.params[2], t.params[3]]
-        }
-        "affine" => {
-            solver::fit_affine_from_pairs(&pairs)
-                .map_err(|e| e.to_string())?
-                .params
-        }
-        _ => return Err("unknown method".into()),
-    };
+    let map2ref = fit_affine(state, method, &pairs)?;
     let ref_path = state
         .reference_path
         .lock()
@@ -395,32 +1175,281 @@ fn export_georeferenced_geotiff(
         .with_extension("")
         .to_string_lossy()
         .into_owned();
-    // Try reading reference world file
-    let ref_aff = match io::read_world_file(&ref_base) {
-        Ok(a) => a,
-        Err(_) => [1.0, 0.0, 0.0, 1.0, 0.0, 0.0], // identity fallback
+    // Try reading reference world file; identity fallback
+    let ref_aff = types::Affine {
+        params: io::read_world_file(&ref_base).unwrap_or([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]),
+    };
+    // world = ref_aff ∘ map2ref
+    let affine = solver::compose_affine(&map2ref, &ref_aff).params;
+    let map_path = state.map_path.lock().map_err(|e| e.to_string())?.clone();
+    let extent = map_path
+        .and_then(|p| io::image_dimensions(&p).ok())
+        .map(|(w, h)| {
+            let geo = io::Georef { affine, wkt: None };
+            io::image_extent(&geo, w, h)
+        });
+    let reference_crs = state
+        .ref_georef
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .and_then(|g| g.wkt.clone());
+    let fallback_crs = state
+        .fallback_crs
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone();
+    let (prj_wkt, warning) =
+        io::resolve_export_crs(reference_crs.as_deref(), fallback_crs.as_deref());
+    Ok(io::ExportPreview {
+        affine,
+        prj_wkt,
+        extent,
+        warnings: warning.into_iter().collect(),
+    })
+}
+
+#[tauri::command]
+fn preview_export(method: String, state: State<AppState>) -> Result<io::ExportPreview, String> {
+    build_export_preview(&method, &state)
+}
+
+/// Write the composed world file and PRJ for the map (see
+/// `oriented_affine` for `y_axis_down`), world file values rounded to
+/// `precision` significant digits when given. Returns the export warnings.
+#[tauri::command]
+fn export_georeferenced_geotiff(
+    state: State<AppState>,
+    method: String,
+    output_without_ext: String,
+    y_axis_down: Option<bool>,
+    precision: Option<usize>,
+) -> Result<Vec<String>, String> {
+    export_georeferenced_geotiff_in(&state, &method, &output_without_ext, y_axis_down, precision)
+}
+
+fn export_georeferenced_geotiff_in(
+    state: &AppState,
+    method: &str,
+    output_without_ext: &str,
+    y_axis_down: Option<bool>,
+    precision: Option<usize>,
+) -> Result<Vec<String>, String> {
+    let mut preview = build_export_preview(method, state)?;
+    preview.affine = oriented_affine(state, preview.affine, y_axis_down, None)?;
+    io::write_export_with_precision(output_without_ext, &preview, precision)
+        .map_err(|e| e.to_string())?;
+    Ok(preview.warnings)
+}
+
+/// Write the map image as a GeoTIFF with the composed map pixel -> world
+/// affine embedded as a ModelTransformation matrix (rotation preserved) for
+/// consumers that only read embedded tags (see `oriented_affine` for
+/// `y_axis_down`). Returns the export warnings.
+#[tauri::command]
+fn export_embedded_geotiff(
+    method: String,
+    output_path: String,
+    y_axis_down: Option<bool>,
+    state: State<AppState>,
+) -> Result<Vec<String>, String> {
+    export_embedded_geotiff_in(&state, &method, &output_path, y_axis_down)
+}
+
+fn export_embedded_geotiff_in(
+    state: &AppState,
+    method: &str,
+    output_path: &str,
+    y_axis_down: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let mut preview = build_export_preview(method, state)?;
+    let map_path = state
+        .map_path
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or_else(|| "map path not set".to_string())?;
+    let affine = oriented_affine(state, preview.affine, y_axis_down, Some(&map_path))?;
+    let crs = preview.prj_wkt.trim();
+    if io::crs_epsg_code(crs).is_none() {
+        preview.warnings.push(
+            "CRS could not be identified as an EPSG code; it is not embedded in the GeoTIFF"
+                .to_string(),
+        );
+    }
+    io::embed_geotiff(&map_path, output_path, affine, Some(crs)).map_err(|e| e.to_string())?;
+    Ok(preview.warnings)
+}
+
+/// Write a PNG of the map image with every point pair's map location burned
+/// in as a numbered marker (see `io::write_annotated_preview`), colored by
+/// residual when `solve_global` has run on the current constraints, for
+/// sharing as a QC image.
+#[tauri::command]
+fn export_annotated_preview(path: String, state: State<AppState>) -> Result<(), String> {
+    export_annotated_preview_in(&state, &path)
+}
+
+fn export_annotated_preview_in(state: &AppState, path: &str) -> Result<(), String> {
+    let map_path = state
+        .map_path
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or_else(|| "map path not set".to_string())?;
+    let list = state.constraints.lock().map_err(|e| e.to_string())?.clone();
+    // Residuals from a solve of other constraints would color the wrong pins
+    let (residuals, unit): (HashMap<u64, f64>, ErrorUnit) = match state
+        .last_metrics
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+    {
+        Some((solved, qm)) if *solved == list => {
+            (qm.residuals_by_id.iter().cloned().collect(), qm.unit)
+        }
+        _ => (HashMap::new(), ErrorUnit::Pixels),
+    };
+    let markers: Vec<io::PreviewMarker> = list
+        .iter()
+        .filter_map(|c| match c {
+            ConstraintKind::PointPair { id, src, .. } => Some(io::PreviewMarker {
+                id: *id,
+                px: *src,
+                residual: residuals.get(id).copied(),
+            }),
+            _ => None,
+        })
+        .collect();
+    io::write_annotated_preview(&map_path, &markers, unit, path).map_err(|e| e.to_string())
+}
+
+/// Write the point pairs and a `method` fit of them to a GeoPackage at
+/// `path`: a point layer in the reference CRS carrying each pair's map and
+/// reference pixels and residual, plus the fitted parameters as a table.
+#[tauri::command]
+fn export_geopackage(path: String, method: String, state: State<AppState>) -> Result<(), String> {
+    export_geopackage_in(&state, &path, &method)
+}
+
+fn export_geopackage_in(state: &AppState, path: &str, method: &str) -> Result<(), String> {
+    let geo = state.ref_georef.lock().map_err(|e| e.to_string())?.clone();
+    let list = state.constraints.lock().map_err(|e| e.to_string())?;
+    let pairs = solver::pairs_from_constraints(&list);
+    let t = cached_fit(state, method, &pairs)?;
+    let residuals: HashMap<u64, f64> = solver::residuals_by_id(&t, &list).into_iter().collect();
+    let mut points = Vec::new();
+    for c in list.iter() {
+        if let ConstraintKind::PointPair {
+            id,
+            src,
+            dst,
+            dst_real,
+            ..
+        } = c
+        {
+            let world = dst_real
+                .or_else(|| geo.as_ref().map(|g| io::pixel_to_world(g, *dst)))
+                .ok_or_else(|| {
+                    format!(
+                        "point {} has no world coordinates; load a georeferenced reference",
+                        id
+                    )
+                })?;
+            points.push(io::GpkgControlPoint {
+                id: *id,
+                map_px: *src,
+                ref_px: *dst,
+                world,
+                residual: residuals.get(id).copied(),
+            });
+        }
+    }
+    drop(list);
+    let names: &[&str] = match &t {
+        TransformKind::Similarity(_) => &["scale", "rotation_rad", "tx", "ty"],
+        _ => &["a", "b", "c", "d", "tx", "ty"],
+    };
+    let values: Vec<f64> = match &t {
+        TransformKind::Similarity(s) => s.params.to_vec(),
+        TransformKind::Affine(a) => a.params.to_vec(),
+        _ => unreachable!("cached_fit only fits similarities and affines"),
     };
-    // Compose: world = ref_aff ∘ map2ref; 2x2 linear and translation
-    let a = ref_aff;
-    let b = map2ref;
-    let lin = [
-        a[0] * b[0] + a[1] * b[2],
-        a[0] * b[1] + a[1] * b[3],
-        a[2] * b[0] + a[3] * b[2],
-        a[2] * b[1] + a[3] * b[3],
+    let rmse =
+        (residuals.values().map(|r| r * r).sum::<f64>() / residuals.len().max(1) as f64).sqrt();
+    let mut parameters = vec![
+        ("method".to_string(), method.to_string()),
+        ("n_pairs".to_string(), pairs.len().to_string()),
+        ("rmse_ref_px".to_string(), rmse.to_string()),
     ];
-    let trans = [
-        a[0] * b[4] + a[1] * b[5] + a[4],
-        a[2] * b[4] + a[3] * b[5] + a[5],
-    ];
-    io::write_world_file(
-        &output_without_ext,
-        [lin[0], lin[1], lin[2], lin[3], trans[0], trans[1]],
-    )
-    .map_err(|e| e.to_string())?;
-    // Write PRJ with NAD83(2011) as a reasonable default if no reference .prj found
-    let prj_wkt = "GEOGCS[\"NAD83(2011)\",DATUM[\"NAD83_National_Spatial_Reference_System_2011\",SPHEROID[\"GRS 1980\",6378137,298.257222101]],PRIMEM[\"Greenwich\",0],UNIT[\"degree\",0.0174532925199433]]";
-    let _ = io::write_prj(&output_without_ext, prj_wkt);
+    parameters.extend(
+        names
+            .iter()
+            .zip(values)
+            .map(|(n, v)| (format!("map_to_ref_{}", n), v.to_string())),
+    );
+    let crs = geo.as_ref().and_then(|g| g.wkt.clone());
+    if let Some(crs) = &crs {
+        parameters.push(("crs".to_string(), crs.clone()));
+    }
+    io::write_geopackage(path, &points, crs.as_deref(), &parameters).map_err(|e| e.to_string())
+}
+
+/// Write `<image>.kml`, a Google Earth ground overlay of the map image. Its
+/// corners go map pixel -> fitted transform -> reference world -> WGS84, so a
+/// rotated map is placed with a `gx:LatLonQuad`. Returns the KML path.
+#[tauri::command]
+fn export_kml_overlay(
+    image_path: String,
+    method: String,
+    state: State<AppState>,
+) -> Result<String, String> {
+    let preview = build_export_preview(&method, &state)?;
+    let (w, h) = io::image_dimensions(&image_path).map_err(|e| e.to_string())?;
+    let quad = lon_lat_quad(preview.affine, &preview.prj_wkt, w, h)?;
+    let out = io::write_kml_ground_overlay(&image_path, &quad).map_err(|e| e.to_string())?;
+    Ok(out.to_string_lossy().into_owned())
+}
+
+/// Image corners (see `io::image_corners`) as (lon, lat) in WGS84.
+fn lon_lat_quad(affine: [f64; 6], crs: &str, w: u32, h: u32) -> Result<[[f64; 2]; 4], String> {
+    let to_wgs84 = proj::Proj::new_known_crs(crs, "EPSG:4326", None).map_err(|e| e.to_string())?;
+    let geo = io::Georef { affine, wkt: None };
+    let mut quad = io::image_corners(&geo, w, h);
+    for p in &mut quad {
+        let (lon, lat) = to_wgs84.convert((p[0], p[1])).map_err(|e| e.to_string())?;
+        *p = [lon, lat];
+    }
+    Ok(quad)
+}
+
+#[tauri::command]
+fn set_fallback_crs(crs: Option<String>, state: State<AppState>) -> Result<(), String> {
+    if let Some(c) = &crs {
+        proj::Proj::new_known_crs(c, "EPSG:4326", None)
+            .map_err(|e| format!("invalid CRS {}: {}", c, e))?;
+    }
+    *state.fallback_crs.lock().map_err(|e| e.to_string())? = crs;
+    Ok(())
+}
+
+/// Assign a CRS to a reference whose georeference came without one (e.g. a
+/// bare world file). Validated with PROJ; accepts EPSG codes, PROJ strings,
+/// or WKT. Replaces any CRS already read from a `.prj` or GeoTIFF keys.
+#[tauri::command]
+fn set_reference_crs(crs: String, state: State<AppState>) -> Result<(), String> {
+    set_reference_crs_in(&state, crs)
+}
+
+fn set_reference_crs_in(state: &AppState, crs: String) -> Result<(), String> {
+    let crs = crs.trim().to_string();
+    proj::Proj::new_known_crs(&crs, "EPSG:4326", None)
+        .map_err(|e| format!("invalid CRS {}: {}", crs, e))?;
+    let mut geo = state.ref_georef.lock().map_err(|e| e.to_string())?;
+    let geo = geo.as_mut().ok_or_else(|| {
+        "reference has no georeferencing (world file) to attach a CRS to".to_string()
+    })?;
+    geo.wkt = Some(crs);
     Ok(())
 }
 
@@ -432,21 +1461,63 @@ fn main() {
             set_map_path,
             set_reference_path,
             load_raster_data,
+            load_raster_preview_data,
+            supported_formats,
             get_constraints,
+            constraint_summary,
+            control_point_hull,
             add_constraint,
+            add_constraints_batch,
             delete_constraint,
+            clear_constraints,
+            import_constraints_csv,
+            export_gcp_points,
+            warp_map,
+            preview_warp,
+            quick_align,
+            fit_verdict,
+            outlier_ids,
+            scale_deviation_ppm,
+            scale_for_paper,
+            get_min_pairs,
             solve_global,
+            set_error_unit,
+            solve_to_json,
+            get_transform_stack,
+            export_transform_stack,
+            evaluate_transform,
+            refine_transform,
+            solve_chained,
+            point_residual,
+            residual_at,
+            point_leverages,
+            suggest_control_points,
             get_proj_string,
             export_world_file,
+            export_world_file_for_image,
             export_georeferenced_geotiff,
+            export_embedded_geotiff,
+            export_kml_overlay,
+            export_annotated_preview,
+            export_geopackage,
+            preview_export,
+            set_fallback_crs,
+            set_reference_crs,
             get_reference_georef,
+            get_reference_inverse_affine,
             get_reference_crs,
+            axis_order,
+            transformation_accuracy,
             suggest_output_epsg,
             pixel_to,
             pixels_to,
             pixel_to_projected,
             pixels_to_projected,
             metric_scale_at,
+            local_jacobian,
+            transform_polygon,
+            scale_report,
+            measure_bearing,
             save_debug_log,
         ])
         .run(tauri::generate_context!())
@@ -458,71 +1529,91 @@ fn get_reference_georef(state: State<AppState>) -> Result<Option<io::Georef>, St
     Ok(state.ref_georef.lock().map_err(|e| e.to_string())?.clone())
 }
 
+/// World -> reference pixel affine [A,B,D,E,C,F] (the inverse of the
+/// georef's), so the frontend can map many world coordinates to pixels
+/// without a call per point. None without a georef; errors when the affine
+/// is singular.
+#[tauri::command]
+fn get_reference_inverse_affine(state: State<AppState>) -> Result<Option<[f64; 6]>, String> {
+    get_reference_inverse_affine_in(&state)
+}
+
+fn get_reference_inverse_affine_in(state: &AppState) -> Result<Option<[f64; 6]>, String> {
+    let geo = state.ref_georef.lock().map_err(|e| e.to_string())?;
+    let Some(geo) = geo.as_ref() else {
+        return Ok(None);
+    };
+    io::invert_world_affine(geo.affine)
+        .map(Some)
+        .ok_or_else(|| "reference affine is singular".to_string())
+}
+
 fn _convert_reference_pixel(
     px: f64,
     py: f64,
     mode: &str,
     policy: &str,
-    state: &State<AppState>,
+    state: &AppState,
 ) -> Result<Option<[f64; 2]>, String> {
-    let geo = match state.ref_georef.lock().map_err(|e| e.to_string())?.clone() {
-        Some(g) => g,
-        None => return Ok(None),
+    let Some(projector) = reference_projector(state, mode == "local_m", mode_needs_crs(mode))?
+    else {
+        return Ok(None);
     };
     match mode {
-        "lonlat" => {
-            let world = io::pixel_to_world(&geo, [px, py]);
-            if let Some(wkt) = &geo.wkt {
-                let (lon, lat) = convert_to_wgs84(wkt, world[0], world[1])?;
-                Ok(Some([lon, lat]))
-            } else {
-                Ok(None)
-            }
-        }
-        "local_m" => {
-            let ref_path = state
-                .reference_path
-                .lock()
-                .map_err(|e| e.to_string())?
-                .clone()
-                .ok_or_else(|| "reference path not set".to_string())?;
-            let (w, h) = io::image_dimensions(&ref_path).map_err(|e| e.to_string())?;
-            let center = [(w as f64) / 2.0, (h as f64) / 2.0];
-            match io::pixel_to_local_meters(&geo, [px, py], center) {
-                Ok(Some(m)) => Ok(Some(m)),
-                _ => Ok(None),
-            }
-        }
-        "utm" | "projected_m" => {
-            if let Some(wkt) = &geo.wkt {
-                let world = io::pixel_to_world(&geo, [px, py]);
-                let to_wgs84 =
-                    proj::Proj::new_known_crs(wkt, "EPSG:4326", None).map_err(|e| e.to_string())?;
-                let (lon, lat) = to_wgs84
-                    .convert((world[0], world[1]))
-                    .map_err(|e| e.to_string())?;
-                let zone = (((lon + 180.0) / 6.0).floor() as i32).clamp(1, 60);
-                match policy {
-                    "NAD83_2011" => {
-                        let (x, y) = convert_to_utm(policy, zone, true, lon, lat)?;
-                        Ok(Some([x, y]))
-                    }
-                    _ => {
-                        // WGS84
-                        let north = lat >= 0.0;
-                        let (x, y) = convert_to_utm("WGS84", zone, north, lon, lat)?;
-                        Ok(Some([x, y]))
-                    }
-                }
-            } else {
-                Ok(None)
-            }
-        }
+        "lonlat" => projector.to_wgs84([px, py]).map_err(|e| e.to_string()),
+        "local_m" => Ok(projector.to_local_meters([px, py]).ok().flatten()),
+        "utm" | "projected_m" => projector
+            .to_utm([px, py], policy)
+            .map_err(|e| e.to_string()),
         "pixel" => Ok(Some([px, py])),
         _ => Ok(None),
     }
 }
 
+/// Conversion modes of `pixel_to`/`pixels_to` that fail, rather than give
+/// None, when PROJ rejects the reference CRS.
+fn mode_needs_crs(mode: &str) -> bool {
+    matches!(mode, "lonlat" | "utm" | "projected_m")
+}
+
+/// One `io::ReferenceProjector` for the current reference, or None without a
+/// georef. With `centered`, the local meter plane is centered on the
+/// reference image, which then must be set. A CRS PROJ rejects is an error
+/// only with `require_crs`; otherwise the projector is built without it, so
+/// CRS-free conversions keep working and the others give None.
+fn reference_projector(
+    state: &AppState,
+    centered: bool,
+    require_crs: bool,
+) -> Result<Option<io::ReferenceProjector>, String> {
+    let geo = match state.ref_georef.lock().map_err(|e| e.to_string())?.clone() {
+        Some(g) => g,
+        None => return Ok(None),
+    };
+    let ref_wh = if centered {
+        let ref_path = state
+            .reference_path
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone()
+            .ok_or_else(|| "reference path not set".to_string())?;
+        Some(io::image_dimensions(&ref_path).map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+    let projector = match io::ReferenceProjector::new(&geo, ref_wh) {
+        Err(_) if !require_crs => {
+            let bare = io::Georef {
+                affine: geo.affine,
+                wkt: None,
+            };
+            io::ReferenceProjector::new(&bare, ref_wh)
+        }
+        projector => projector,
+    };
+    projector.map(Some).map_err(|e| e.to_string())
+}
+
 #[derive(serde::Serialize)]
 struct CrsInfo {
     name: String,
@@ -533,6 +1624,10 @@ struct CrsInfo {
 
 #[tauri::command]
 fn get_reference_crs(state: State<AppState>) -> Result<Option<CrsInfo>, String> {
+    get_reference_crs_in(&state)
+}
+
+fn get_reference_crs_in(state: &AppState) -> Result<Option<CrsInfo>, String> {
     let g = match state.ref_georef.lock().map_err(|e| e.to_string())?.clone() {
         Some(v) => v,
         None => return Ok(None),
@@ -558,6 +1653,54 @@ fn get_reference_crs(state: State<AppState>) -> Result<Option<CrsInfo>, String>
     }))
 }
 
+/// Axis order of the reference CRS: what its definition `declared` (None if
+/// PROJ cannot tell) and the order every command actually uses, which is
+/// always east/north (lon, lat for geographic CRSs).
+#[derive(Debug, PartialEq, serde::Serialize)]
+struct AxisOrderInfo {
+    declared: Option<io::AxisOrder>,
+    output: io::AxisOrder,
+}
+
+/// Report the reference CRS's declared axis order so the UI can label
+/// coordinates (e.g. warn that EPSG:4326 values are shown lon, lat). None
+/// without a reference CRS.
+#[tauri::command]
+fn axis_order(state: State<AppState>) -> Result<Option<AxisOrderInfo>, String> {
+    axis_order_in(&state)
+}
+
+fn axis_order_in(state: &AppState) -> Result<Option<AxisOrderInfo>, String> {
+    let geo = state.ref_georef.lock().map_err(|e| e.to_string())?;
+    Ok(geo
+        .as_ref()
+        .and_then(|g| g.wkt.as_deref())
+        .map(|crs| AxisOrderInfo {
+            declared: io::crs_axis_order(crs),
+            output: io::AxisOrder::EastNorth,
+        }))
+}
+
+/// Stated accuracy in meters of the datum transformation from the reference
+/// CRS to `target_crs` (e.g. the export CRS), so the UI can show the floor
+/// on achievable ground accuracy next to the RMSE. None without a reference
+/// CRS or when PROJ gives no accuracy.
+#[tauri::command]
+fn transformation_accuracy(
+    target_crs: String,
+    state: State<AppState>,
+) -> Result<Option<f64>, String> {
+    transformation_accuracy_in(&state, &target_crs)
+}
+
+fn transformation_accuracy_in(state: &AppState, target_crs: &str) -> Result<Option<f64>, String> {
+    let geo = state.ref_georef.lock().map_err(|e| e.to_string())?;
+    Ok(geo
+        .as_ref()
+        .and_then(|g| g.wkt.as_deref())
+        .and_then(|crs| io::transformation_accuracy(crs, target_crs.trim())))
+}
+
 fn extract_wkt_name(wkt: &str) -> Option<String> {
     // Grab the first quoted name token, e.g., GEOGCS["WGS 84", ...]
     let bytes = wkt.as_bytes();
@@ -670,7 +1813,7 @@ fn suggest_output_epsg(
     if let Some(wkt) = &geo.wkt {
         let world = io::pixel_to_world(&geo, [(w as f64) / 2.0, (h as f64) / 2.0]);
         let (lon, lat) = convert_to_wgs84(wkt, world[0], world[1])?;
-        let zone = (((lon + 180.0) / 6.0).floor() as i32).clamp(1, 60);
+        let zone = io::utm_zone(lon);
         match policy.as_str() {
             "NAD83_2011" => {
                 let proj_str = format!(
@@ -689,11 +1832,7 @@ fn suggest_output_epsg(
             }
             _ => {
                 let north = lat >= 0.0;
-                let epsg = if north {
-                    format!("EPSG:326{}", zone)
-                } else {
-                    format!("EPSG:327{}", zone)
-                };
+                let epsg = io::utm_epsg(zone, north);
                 let proj_str = format!(
                     "+proj=utm +zone={} +datum=WGS84 +units=m +no_defs +type=crs",
                     zone
@@ -732,49 +1871,19 @@ fn pixels_to(
     pts: Vec<[f64; 2]>,
     state: State<AppState>,
 ) -> Result<Vec<Option<XY>>, String> {
-    let geo = match state.ref_georef.lock().map_err(|e| e.to_string())?.clone() {
-        Some(g) => g,
-        None => return Ok(vec![None; pts.len()]),
+    let Some(projector) = reference_projector(&state, mode == "local_m", mode_needs_crs(&mode))?
+    else {
+        return Ok(vec![None; pts.len()]);
     };
+    let xy = |p: Option<[f64; 2]>| p.map(|[x, y]| XY { x, y });
     let mut out = Vec::with_capacity(pts.len());
-    match mode.as_str() {
-        "lonlat" => {
-            if let Some(wkt) = &geo.wkt {
-                // cached per-thread
-                let to_wgs84_crs = wkt.clone();
-                for [u, v] in pts {
-                    let world = io::pixel_to_world(&geo, [u, v]);
-                    let (lon, lat) = convert_to_wgs84(&to_wgs84_crs, world[0], world[1])?;
-                    out.push(Some(XY { x: lon, y: lat }));
-                }
-            } else {
-                out.resize(pts.len(), None);
-            }
-        }
-        "local_m" => {
-            let ref_path = state
-                .reference_path
-                .lock()
-                .map_err(|e| e.to_string())?
-                .clone()
-                .ok_or_else(|| "reference path not set".to_string())?;
-            let (w, h) = io::image_dimensions(&ref_path).map_err(|e| e.to_string())?;
-            let center = [(w as f64) / 2.0, (h as f64) / 2.0];
-            for [u, v] in pts {
-                match io::pixel_to_local_meters(&geo, [u, v], center) {
-                    Ok(Some(m)) => out.push(Some(XY { x: m[0], y: m[1] })),
-                    _ => out.push(None),
-                }
-            }
-        }
-        "pixel" => {
-            for [u, v] in pts {
-                out.push(Some(XY { x: u, y: v }));
-            }
-        }
-        _ => {
-            out.resize(pts.len(), None);
-        }
+    for px in pts {
+        out.push(match mode.as_str() {
+            "lonlat" => xy(projector.to_wgs84(px).map_err(|e| e.to_string())?),
+            "local_m" => xy(projector.to_local_meters(px).ok().flatten()),
+            "pixel" => xy(Some(px)),
+            _ => None,
+        });
     }
     Ok(out)
 }
@@ -796,29 +1905,15 @@ fn pixels_to_projected(
     pts: Vec<[f64; 2]>,
     state: State<AppState>,
 ) -> Result<Vec<Option<XY>>, String> {
-    use std::collections::HashMap;
-    let geo = match state.ref_georef.lock().map_err(|e| e.to_string())?.clone() {
-        Some(g) => g,
-        None => return Ok(vec![None; pts.len()]),
-    };
-    let Some(wkt) = &geo.wkt else {
+    let Some(projector) = reference_projector(&state, false, true)? else {
         return Ok(vec![None; pts.len()]);
     };
-    let mut cache: HashMap<(i32, bool), ()> = HashMap::new();
-    let mut out = Vec::with_capacity(pts.len());
-    for [u, v] in pts {
-        let world = io::pixel_to_world(&geo, [u, v]);
-        let (lon, lat) = convert_to_wgs84(wkt, world[0], world[1])?;
-        let zone = (((lon + 180.0) / 6.0).floor() as i32).clamp(1, 60);
-        let north = lat >= 0.0;
-        let key = (zone, north);
-        if !cache.contains_key(&key) {
-            cache.insert(key, ());
-        }
-        let (x, y) = convert_to_utm(&policy, zone, north, lon, lat)?;
-        out.push(Some(XY { x, y }));
-    }
-    Ok(out)
+    pts.into_iter()
+        .map(|px| {
+            let utm = projector.to_utm(px, &policy).map_err(|e| e.to_string())?;
+            Ok(utm.map(|[x, y]| XY { x, y }))
+        })
+        .collect()
 }
 
 #[derive(serde::Serialize)]
@@ -832,15 +1927,1190 @@ fn metric_scale_at(u: f64, v: f64, state: State<AppState>) -> Result<Option<Mpp>
         Some(v) => v,
         None => return Ok(None),
     };
+    Ok(meters_per_pixel_at(&geo, u, v).map(|mpp| Mpp { mpp }))
+}
+
+/// Mean ground size of a reference pixel at (u, v), measured in a local
+/// meter plane so projection scale distortion is accounted for.
+fn meters_per_pixel_at(geo: &io::Georef, u: f64, v: f64) -> Option<f64> {
     if let (Ok(Some(l0)), Ok(Some(l1)), Ok(Some(l2))) = (
-        io::pixel_to_local_meters(&geo, [u, v], [u, v]),
-        io::pixel_to_local_meters(&geo, [u + 1.0, v], [u, v]),
-        io::pixel_to_local_meters(&geo, [u, v + 1.0], [u, v]),
+        io::pixel_to_local_meters(geo, [u, v], [u, v]),
+        io::pixel_to_local_meters(geo, [u + 1.0, v], [u, v]),
+        io::pixel_to_local_meters(geo, [u, v + 1.0], [u, v]),
     ) {
         let du = ((l1[0] - l0[0]).powi(2) + (l1[1] - l0[1]).powi(2)).sqrt();
         let dv = ((l2[0] - l0[0]).powi(2) + (l2[1] - l0[1]).powi(2)).sqrt();
-        let mpp = 0.5 * (du + dv);
-        return Ok(Some(Mpp { mpp }));
+        return Some(0.5 * (du + dv));
+    }
+    None
+}
+
+#[tauri::command]
+fn scale_report(
+    u: f64,
+    v: f64,
+    map_scale: Option<f64>,
+    state: State<AppState>,
+) -> Result<Option<types::ScaleReport>, String> {
+    scale_report_in(&state, u, v, map_scale)
+}
+
+fn scale_report_in(
+    state: &AppState,
+    u: f64,
+    v: f64,
+    map_scale: Option<f64>,
+) -> Result<Option<types::ScaleReport>, String> {
+    let geo = match state.ref_georef.lock().map_err(|e| e.to_string())?.clone() {
+        Some(g) => g,
+        None => return Ok(None),
+    };
+    let Some(mpp) = meters_per_pixel_at(&geo, u, v) else {
+        return Ok(None);
+    };
+    // Affine is linear, so CRS units per pixel are the same everywhere
+    let [a, b, d, e, _, _] = geo.affine;
+    let crs = 0.5 * (a.hypot(d) + b.hypot(e));
+    Ok(Some(types::ScaleReport::new(mpp, crs, map_scale)))
+}
+
+/// Forward geodesic azimuth (degrees from true north) from reference pixel
+/// `(u1, v1)` to `(u2, v2)`. None without a georeferenced CRS.
+#[tauri::command]
+fn measure_bearing(
+    u1: f64,
+    v1: f64,
+    u2: f64,
+    v2: f64,
+    state: State<AppState>,
+) -> Result<Option<f64>, String> {
+    measure_bearing_in(&state, [u1, v1], [u2, v2])
+}
+
+fn measure_bearing_in(
+    state: &AppState,
+    from: [f64; 2],
+    to: [f64; 2],
+) -> Result<Option<f64>, String> {
+    let geo = match state.ref_georef.lock().map_err(|e| e.to_string())?.clone() {
+        Some(g) => g,
+        None => return Ok(None),
+    };
+    io::pixel_bearing_deg(&geo, from, to).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pp(id: u64, src: [f64; 2], dst: [f64; 2]) -> ConstraintKind {
+        ConstraintKind::PointPair {
+            id,
+            src,
+            dst,
+            dst_real: None,
+            dst_local: None,
+            src_z: None,
+            dst_z: None,
+            weight: 1.0,
+        }
+    }
+
+    fn state_with_points(n: u64) -> AppState {
+        let state = AppState::default();
+        state
+            .constraints
+            .lock()
+            .unwrap()
+            .extend((0..n).map(|id| pp(id, [id as f64, 0.0], [0.0, id as f64])));
+        state
+    }
+
+    #[test]
+    fn test_clear_constraints_requires_confirm() {
+        let state = state_with_points(3);
+        assert_eq!(clear_constraints_in(&state, false).unwrap().len(), 3);
+        assert_eq!(state.constraints.lock().unwrap().len(), 3);
+        assert!(clear_constraints_in(&state, true).unwrap().is_empty());
+        assert!(state.constraints.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scale_report_units_agree() {
+        let state = AppState::default();
+        assert!(scale_report_in(&state, 0.0, 0.0, Some(10000.0))
+            .unwrap()
+            .is_none());
+        // 2 m pixels in UTM 10N: CRS units are meters, so all three agree
+        *state.ref_georef.lock().unwrap() = Some(io::Georef {
+            affine: [2.0, 0.0, 0.0, -2.0, 500000.0, 4200000.0],
+            wkt: Some("EPSG:32610".to_string()),
+        });
+        let r = scale_report_in(&state, 100.0, 100.0, Some(10000.0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(r.crs_units_per_pixel, 2.0);
+        // Near the central meridian UTM scale error is under 0.1%
+        assert!((r.meters_per_pixel - 2.0).abs() < 2e-3);
+        let mm = r.map_mm_per_pixel.unwrap();
+        assert!((mm - r.meters_per_pixel * 1000.0 / 10000.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_set_reference_crs_enables_conversions() {
+        let state = AppState::default();
+        assert!(set_reference_crs_in(&state, "EPSG:32610".into()).is_err());
+        // Bare world file: affine but no CRS
+        *state.ref_georef.lock().unwrap() = Some(io::Georef {
+            affine: [2.0, 0.0, 0.0, -2.0, 500000.0, 4200000.0],
+            wkt: None,
+        });
+        assert!(scale_report_in(&state, 0.0, 0.0, None).unwrap().is_none());
+        assert!(set_reference_crs_in(&state, "not a crs".into()).is_err());
+        assert!(state
+            .ref_georef
+            .lock()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .wkt
+            .is_none());
+
+        set_reference_crs_in(&state, " EPSG:32610 ".into()).unwrap();
+        let info = get_reference_crs_in(&state).unwrap().unwrap();
+        assert_eq!(info.code.as_deref(), Some("EPSG:32610"));
+        assert_eq!(info.wkt.as_deref(), Some("EPSG:32610"));
+        assert!(scale_report_in(&state, 0.0, 0.0, None).unwrap().is_some());
+        let geo = state.ref_georef.lock().unwrap().clone().unwrap();
+        let world = io::pixel_to_world(&geo, [0.0, 0.0]);
+        let (lon, lat) = convert_to_wgs84(geo.wkt.as_ref().unwrap(), world[0], world[1]).unwrap();
+        // Central meridian of zone 10 is 123 W
+        assert!(
+            (lon + 123.0).abs() < 1e-3 && (lat - 37.9).abs() < 0.1,
+            "{} {}",
+            lon,
+            lat
+        );
+    }
+
+    #[test]
+    fn test_evaluate_transform_scores_without_refit() {
+        let state = AppState::default();
+        assert!(evaluate_transform_in(
+            &state,
+            &TransformKind::Affine(types::Affine {
+                params: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+            }),
+            Some("pixels"),
+            None,
+        )
+        .is_err());
+        let truth = types::Affine {
+            params: [0.5, 0.1, -0.1, 0.5, 30.0, -12.0],
+        };
+        let srcs = [[0.0, 0.0], [400.0, 10.0], [20.0, 300.0], [380.0, 320.0]];
+        state
+            .constraints
+            .lock()
+            .unwrap()
+            .extend(srcs.iter().enumerate().map(|(i, s)| {
+                let d = solver::Transform::apply(&truth, &nalgebra::Vector2::from(*s));
+                pp(i as u64, *s, [d.x, d.y])
+            }));
+        let exact =
+            evaluate_transform_in(&state, &TransformKind::Affine(truth), Some("pixels"), None)
+                .unwrap();
+        assert!(exact.rmse < 1e-9);
+        assert_eq!(exact.residuals_by_id.len(), 4);
+        // No shear/rotation and a 10 px shift: far off at every point
+        let wrong = TransformKind::Similarity(types::Similarity {
+            params: [0.5, 0.0, 40.0, -12.0],
+        });
+        let qm = evaluate_transform_in(&state, &wrong, Some("pixels"), None).unwrap();
+        assert!(qm.rmse > 10.0, "{}", qm.rmse);
+    }
+
+    #[test]
+    fn test_geographic_reference_reports_meters() {
+        let state = AppState::default();
+        // 0.1 degree pixels, pixel (0, 0) at 45 N
+        *state.ref_georef.lock().unwrap() = Some(io::Georef {
+            affine: [0.1, 0.0, 0.0, -0.1, -93.0, 45.0],
+            wkt: Some("EPSG:4326".to_string()),
+        });
+        // WGS84 meridional (M) and prime-vertical (N) radii at 45 degrees
+        let (a, e2) = (6378137.0f64, 0.00669437999014f64);
+        let phi = 45f64.to_radians();
+        let w = 1.0 - e2 * phi.sin().powi(2);
+        let m = a * (1.0 - e2) / w.powf(1.5);
+        let n = a / w.sqrt();
+        let step = 0.1f64.to_radians();
+        let expected = 0.5 * (m * step + n * phi.cos() * step);
+        let size = reference_pixel_size(&state).unwrap();
+        assert!(
+            (size - expected).abs() / expected < 5e-3,
+            "{} vs {}",
+            size,
+            expected
+        );
+
+        // Meter residuals are pixel residuals times that size, not degrees
+        state.constraints.lock().unwrap().extend(
+            [[0.0, 0.0], [100.0, 0.0], [0.0, 80.0], [90.0, 70.0]]
+                .iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    pp(
+                        i as u64,
+                        *s,
+                        [0.01 * s[0] + 0.3 * (i % 2) as f64, 0.01 * s[1]],
+                    )
+                }),
+        );
+        let (_, px) = solve_global_in(
+            &state,
+            "similarity",
+            "pixels",
+            None,
+            None,
+            solver::CoordinateSpace::Pixels,
+        )
+        .unwrap();
+        let (_, m) = solve_global_in(
+            &state,
+            "similarity",
+            "meters",
+            None,
+            None,
+            solver::CoordinateSpace::Pixels,
+        )
+        .unwrap();
+        assert!(px.rmse > 0.0);
+        assert!((m.rmse - px.rmse * size).abs() < 1e-9 * m.rmse);
+    }
+
+    #[test]
+    fn test_residual_at_control_point_is_its_residual() {
+        let state = AppState::default();
+        let srcs = [
+            [0.0, 0.0],
+            [400.0, 10.0],
+            [20.0, 300.0],
+            [380.0, 320.0],
+            [200.0, 150.0],
+        ];
+        let offsets = [
+            [0.4, -0.2],
+            [-0.3, 0.5],
+            [0.1, 0.1],
+            [-0.6, -0.2],
+            [2.0, 1.5],
+        ];
+        state.constraints.lock().unwrap().extend(
+            srcs.iter()
+                .zip(offsets)
+                .enumerate()
+                .map(|(i, (s, o))| pp(i as u64, *s, [0.5 * s[0] + o[0], 0.5 * s[1] + o[1]])),
+        );
+        let (_, qm) = solve_global_in(
+            &state,
+            "affine",
+            "pixels",
+            None,
+            None,
+            solver::CoordinateSpace::Pixels,
+        )
+        .unwrap();
+        let (id, worst) = qm
+            .residuals_by_id
+            .iter()
+            .cloned()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        let [u, v] = srcs[id as usize];
+        let at = residual_at_in(&state, u, v, "affine").unwrap().unwrap();
+        assert!((at - worst).abs() < 1e-9);
+        // Just off the point, still dominated by it
+        let near = residual_at_in(&state, u + 0.5, v, "affine")
+            .unwrap()
+            .unwrap();
+        assert!((near - worst).abs() < 0.01 * worst, "{} vs {}", near, worst);
+    }
+
+    #[test]
+    fn test_local_meters_fit_reports_meter_residuals() {
+        let state = AppState::default();
+        // 2x2 grid: a ±e checkerboard in x is orthogonal to every affine, so
+        // the fit recovers the truth and each residual is exactly e meters
+        let e = 0.75;
+        let srcs = [[0.0, 0.0], [100.0, 0.0], [0.0, 100.0], [100.0, 100.0]];
+        state
+            .constraints
+            .lock()
+            .unwrap()
+            .extend(srcs.iter().enumerate().map(|(i, s)| {
+                let sign = if i == 0 || i == 3 { 1.0 } else { -1.0 };
+                ConstraintKind::PointPair {
+                    id: i as u64,
+                    src: *s,
+                    // Pixel destinations are deliberately unrelated
+                    dst: [7.0 * i as f64, 3.0 * (i * i) as f64],
+                    dst_real: None,
+                    dst_local: Some([2.0 * s[0] - 150.0 + sign * e, -2.0 * s[1] + 40.0]),
+                    src_z: None,
+                    dst_z: None,
+                    weight: 1.0,
+                }
+            }));
+        let (stack, qm) = solve_global_in(
+            &state,
+            "affine",
+            "meters",
+            None,
+            None,
+            solver::CoordinateSpace::LocalMeters,
+        )
+        .unwrap();
+        assert_eq!(qm.unit, ErrorUnit::Meters);
+        assert!((qm.rmse - e).abs() < 1e-9, "{}", qm.rmse);
+        for (_, r) in &qm.residuals_by_id {
+            assert!((r - e).abs() < 1e-9);
+        }
+        let TransformKind::Affine(t) = &stack.transforms[0] else {
+            panic!("expected affine");
+        };
+        let truth = [2.0, 0.0, 0.0, -2.0, -150.0, 40.0];
+        for (a, b) in t.params.iter().zip(truth) {
+            assert!((a - b).abs() < 1e-9);
+        }
+        // No dst_real on any point: World space is refused
+        let err = solve_global_in(
+            &state,
+            "affine",
+            "meters",
+            None,
+            None,
+            solver::CoordinateSpace::World,
+        )
+        .unwrap_err();
+        assert!(err.contains("[0, 1, 2, 3]"), "{}", err);
+    }
+
+    #[test]
+    fn test_world_space_refuses_geographic_crs() {
+        let state = AppState::default();
+        state.constraints.lock().unwrap().extend((0..4).map(|i| {
+            let s = [(i % 2) as f64 * 100.0, (i / 2) as f64 * 100.0 + i as f64];
+            ConstraintKind::PointPair {
+                id: i,
+                src: s,
+                dst: s,
+                dst_real: Some([10.0 + s[0] * 1e-4, 50.0 - s[1] * 1e-4]),
+                dst_local: None,
+                src_z: None,
+                dst_z: None,
+                weight: 1.0,
+            }
+        }));
+        let solve = || {
+            solve_global_in(
+                &state,
+                "affine",
+                "meters",
+                None,
+                None,
+                solver::CoordinateSpace::World,
+            )
+        };
+        let georef = |crs: &str| io::Georef {
+            affine: [1.0, 0.0, 0.0, -1.0, 0.0, 0.0],
+            wkt: Some(crs.to_string()),
+        };
+        *state.ref_georef.lock().unwrap() = Some(georef("EPSG:4326"));
+        let err = solve().unwrap_err();
+        assert!(err.contains("degrees"), "{}", err);
+        // A projected CRS is fitted as before
+        *state.ref_georef.lock().unwrap() = Some(georef("EPSG:32632"));
+        assert_eq!(solve().unwrap().1.unit, ErrorUnit::Meters);
+    }
+
+    #[test]
+    fn test_point_leverages_keyed_by_id() {
+        let state = AppState::default();
+        let srcs = [
+            [0.0, 0.0],
+            [10.0, 0.0],
+            [0.0, 10.0],
+            [10.0, 10.0],
+            [0.0, 0.0],
+        ];
+        state.constraints.lock().unwrap().extend(
+            srcs.iter()
+                .enumerate()
+                .map(|(i, s)| pp(100 + i as u64, *s, [s[0] + 1.0, s[1]])),
+        );
+        // The last pair duplicates the first and is dropped
+        let h = point_leverages_in(&state).unwrap();
+        assert_eq!(
+            h.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            vec![100, 101, 102, 103]
+        );
+        assert!(h.iter().all(|(_, v)| (v - 0.75).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_point_residual_matches_full_metrics() {
+        let state = AppState::default();
+        let offsets = [
+            [0.4, -0.2],
+            [-0.3, 0.5],
+            [0.1, 0.1],
+            [-0.6, -0.2],
+            [0.2, 0.7],
+        ];
+        let srcs = [
+            [0.0, 0.0],
+            [400.0, 10.0],
+            [20.0, 300.0],
+            [380.0, 320.0],
+            [200.0, 150.0],
+        ];
+        state
+            .constraints
+            .lock()
+            .unwrap()
+            .extend(srcs.iter().zip(offsets).enumerate().map(|(i, (s, o))| {
+                pp(
+                    i as u64 + 10,
+                    *s,
+                    [0.5 * s[0] + 30.0 + o[0], 0.5 * s[1] - 12.0 + o[1]],
+                )
+            }));
+        *state.ref_georef.lock().unwrap() = Some(io::Georef {
+            affine: [2.0, 0.0, 0.0, -2.0, 500000.0, 4200000.0],
+            wkt: None,
+        });
+        for method in ["similarity", "affine"] {
+            for unit in ["pixels", "meters", "mapmm"] {
+                let (_, qm) = solve_global_in(
+                    &state,
+                    method,
+                    unit,
+                    Some(25000.0),
+                    None,
+                    solver::CoordinateSpace::Pixels,
+                )
+                .unwrap();
+                for &(id, expected) in &qm.residuals_by_id {
+                    let r = point_residual_in(&state, id, method, Some(unit), Some(25000.0))
+                        .unwrap()
+                        .unwrap();
+                    assert!((r - expected).abs() < 1e-12, "{} {} {}", method, unit, id);
+                }
+            }
+        }
+        assert_eq!(
+            point_residual_in(&state, 99, "affine", Some("pixels"), None).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_kml_quad_is_lon_lat() {
+        // 10 m pixels near San Francisco in UTM 10N, rotated 30 degrees
+        let (c, si) = (
+            30f64.to_radians().cos() * 10.0,
+            30f64.to_radians().sin() * 10.0,
+        );
+        let affine = [c, si, si, -c, 550000.0, 4180000.0];
+        let quad = lon_lat_quad(affine, "EPSG:32610", 200, 100).unwrap();
+        for [lon, lat] in quad {
+            assert!((-123.0..-122.0).contains(&lon), "lon {}", lon);
+            assert!((37.0..38.0).contains(&lat), "lat {}", lat);
+        }
+        // Bottom-left is south of top-left for this north-up-ish map
+        assert!(quad[0][1] < quad[3][1]);
+    }
+
+    #[test]
+    fn test_constraint_summary_breakdown() {
+        // Ids 0..3: id 0 has src == dst and is filtered as degenerate
+        let state = state_with_points(4);
+        {
+            let mut list = state.constraints.lock().unwrap();
+            if let ConstraintKind::PointPair { dst_real, .. } = &mut list[1] {
+                *dst_real = Some([500000.0, 4200000.0]);
+            }
+            // Exact duplicate of id 2
+            let dup = list[2].clone();
+            list.push(dup);
+            list.push(ConstraintKind::Polyline {
+                id: 10,
+                points: vec![[0.0, 0.0], [5.0, 5.0]],
+                weight: 1.0,
+            });
+            list.push(ConstraintKind::Anchor {
+                id: 11,
+                point: [1.0, 1.0],
+            });
+        }
+        let s = constraint_summary_in(&state).unwrap();
+        assert_eq!(
+            s,
+            ConstraintSummary {
+                total: 7,
+                point_pairs: 5,
+                polylines: 1,
+                anchors: 1,
+                usable_pairs: 3,
+                pairs_with_real: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_solve_global_reports_method_minimum() {
+        let state = AppState::default();
+        for (id, u) in [(1, 0.0), (2, 10.0)] {
+            state
+                .constraints
+                .lock()
+                .unwrap()
+                .push(pp(id, [u, 0.0], [u, 5.0]));
+        }
+        let err = solve_global_in(
+            &state,
+            "affine",
+            "pixels",
+            None,
+            None,
+            solver::CoordinateSpace::Pixels,
+        )
+        .unwrap_err();
+        assert_eq!(err, "affine needs ≥3 pairs; got 2");
+        assert_eq!(get_min_pairs("homography".into()), Ok(4));
+        assert!(get_min_pairs("bogus".into()).is_err());
+    }
+
+    #[test]
+    fn test_transformation_accuracy_from_reference_crs() {
+        let state = AppState::default();
+        assert_eq!(
+            transformation_accuracy_in(&state, "EPSG:4326").unwrap(),
+            None
+        );
+        // British National Grid on OSGB36 to WGS 84 needs a datum shift
+        *state.ref_georef.lock().unwrap() = Some(io::Georef {
+            affine: [1.0, 0.0, 0.0, -1.0, 400000.0, 300000.0],
+            wkt: Some("EPSG:27700".into()),
+        });
+        let acc = transformation_accuracy_in(&state, "EPSG:4326")
+            .unwrap()
+            .unwrap();
+        assert!(acc > 0.0, "{}", acc);
+    }
+
+    #[test]
+    fn test_axis_order_reports_lat_lon_definition() {
+        let state = AppState::default();
+        assert_eq!(axis_order_in(&state).unwrap(), None);
+        *state.ref_georef.lock().unwrap() = Some(io::Georef {
+            affine: [0.1, 0.0, 0.0, -0.1, 10.0, 50.0],
+            wkt: Some("EPSG:4326".into()),
+        });
+        assert_eq!(
+            axis_order_in(&state).unwrap(),
+            Some(AxisOrderInfo {
+                declared: Some(io::AxisOrder::NorthEast),
+                output: io::AxisOrder::EastNorth,
+            })
+        );
+    }
+
+    #[test]
+    fn test_solved_stack_round_trips_through_json() {
+        let state = AppState::default();
+        assert!(export_transform_stack_in(&state, "unused.json").is_err());
+        for (id, [u, v]) in [[0.0, 0.0], [100.0, 0.0], [0.0, 80.0], [90.0, 70.0]]
+            .into_iter()
+            .enumerate()
+        {
+            state.constraints.lock().unwrap().push(pp(
+                id as u64,
+                [u, v],
+                [
+                    2.0 * u + 0.1 * v + 5.0,
+                    -0.2 * u + 1.9 * v + 3.0 + id as f64,
+                ],
+            ));
+        }
+        let (stack, metrics) = solve_global_in(
+            &state,
+            "affine",
+            "pixels",
+            None,
+            None,
+            solver::CoordinateSpace::Pixels,
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("stack-{}.json", std::process::id()));
+        export_transform_stack_in(&state, path.to_str().unwrap()).unwrap();
+        let loaded: TransformStack =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        // serde_json's default float parsing may differ in the last ulp
+        let (TransformKind::Affine(a), TransformKind::Affine(b)) =
+            (&loaded.transforms[0], &stack.transforms[0])
+        else {
+            panic!("expected one affine in both stacks");
+        };
+        for (x, y) in a.params.iter().zip(&b.params) {
+            assert!((x - y).abs() <= 1e-12 * y.abs().max(1.0));
+        }
+        let again =
+            evaluate_transform_in(&state, &loaded.transforms[0], Some("pixels"), None).unwrap();
+        assert!((again.rmse - metrics.rmse).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_reference_inverse_affine_round_trips() {
+        let state = AppState::default();
+        assert_eq!(get_reference_inverse_affine_in(&state).unwrap(), None);
+        *state.ref_georef.lock().unwrap() = Some(io::Georef {
+            affine: [2.0, 0.3, -0.4, -2.5, 500000.0, 4200000.0],
+            wkt: None,
+        });
+        let inv = get_reference_inverse_affine_in(&state).unwrap().unwrap();
+        let geo = state.ref_georef.lock().unwrap().clone().unwrap();
+        let inv_geo = io::Georef {
+            affine: inv,
+            wkt: None,
+        };
+        for px in [[0.0, 0.0], [123.5, -7.0], [4000.0, 3000.0]] {
+            let back = io::pixel_to_world(&inv_geo, io::pixel_to_world(&geo, px));
+            assert!((back[0] - px[0]).abs() < 1e-6 && (back[1] - px[1]).abs() < 1e-6);
+        }
+        state.ref_georef.lock().unwrap().as_mut().unwrap().affine = [1.0, 2.0, 2.0, 4.0, 0.0, 0.0];
+        assert!(get_reference_inverse_affine_in(&state).is_err());
+    }
+
+    #[test]
+    fn test_measure_bearing_north_and_east() {
+        let state = AppState::default();
+        assert_eq!(
+            measure_bearing_in(&state, [0.0, 0.0], [0.0, -1.0]).unwrap(),
+            None
+        );
+        // UTM 10N, 1 m pixels on the central meridian: grid north is true north
+        *state.ref_georef.lock().unwrap() = Some(io::Georef {
+            affine: [1.0, 0.0, 0.0, -1.0, 500000.0, 4100000.0],
+            wkt: Some("EPSG:32610".to_string()),
+        });
+        let north = measure_bearing_in(&state, [0.0, 500.0], [0.0, 0.0])
+            .unwrap()
+            .unwrap();
+        assert!(north.min(360.0 - north) < 1e-6, "{}", north);
+        let east = measure_bearing_in(&state, [0.0, 0.0], [500.0, 0.0])
+            .unwrap()
+            .unwrap();
+        assert!((east - 90.0).abs() < 0.01, "{}", east);
+    }
+
+    #[test]
+    fn test_batch_add_matches_one_by_one() {
+        let geo = io::Georef {
+            affine: [2.0, 0.0, 0.0, -2.0, 500000.0, 4200000.0],
+            wkt: Some("EPSG:32610".to_string()),
+        };
+        let pairs: Vec<_> = (0..5)
+            .map(|i| {
+                pp(
+                    i,
+                    [i as f64, 0.0],
+                    [10.0 * i as f64, 300.0 - 7.0 * i as f64],
+                )
+            })
+            .collect();
+        let single = AppState::default();
+        let batch = AppState::default();
+        *single.ref_georef.lock().unwrap() = Some(geo.clone());
+        *batch.ref_georef.lock().unwrap() = Some(geo.clone());
+        for c in pairs.clone() {
+            add_constraints_in(&single, vec![c]).unwrap();
+        }
+        let batched = add_constraints_in(&batch, pairs).unwrap();
+        assert_eq!(
+            serde_json::to_value(&batched).unwrap(),
+            serde_json::to_value(&*single.constraints.lock().unwrap()).unwrap()
+        );
+        for c in &batched {
+            let ConstraintKind::PointPair {
+                dst,
+                dst_real,
+                dst_local,
+                ..
+            } = c
+            else {
+                unreachable!()
+            };
+            assert_eq!(*dst_real, Some(io::pixel_to_world(&geo, *dst)));
+            let local = io::pixel_to_local_meters(&geo, *dst, [0.0, 0.0])
+                .unwrap()
+                .unwrap();
+            let got = dst_local.unwrap();
+            assert!((got[0] - local[0]).abs() < 1e-6 && (got[1] - local[1]).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_control_point_hull_of_source_pixels() {
+        let state = state_with_points(2);
+        assert!(control_point_hull_in(&state).unwrap().is_empty());
+        let src = [
+            [10.0, 10.0],
+            [90.0, 10.0],
+            [50.0, 40.0],
+            [90.0, 70.0],
+            [10.0, 70.0],
+        ];
+        *state.constraints.lock().unwrap() = src
+            .iter()
+            .enumerate()
+            .map(|(i, s)| pp(i as u64, *s, [s[0] * 3.0, s[1] * 3.0]))
+            .collect();
+        assert_eq!(
+            control_point_hull_in(&state).unwrap(),
+            vec![[10.0, 10.0], [90.0, 10.0], [90.0, 70.0], [10.0, 70.0]]
+        );
+    }
+
+    #[test]
+    fn test_fit_cache_invalidated_by_added_constraint() {
+        let state = state_with_points(3);
+        let pairs =
+            |state: &AppState| solver::pairs_from_constraints(&state.constraints.lock().unwrap());
+        let before = pairs(&state);
+        let TransformKind::Similarity(t1) = cached_fit(&state, "similarity", &before).unwrap()
+        else {
+            panic!("expected a similarity");
+        };
+        let cached_pairs =
+            |state: &AppState| state.fit_cache.lock().unwrap()["similarity"].0.clone();
+        assert_eq!(cached_pairs(&state), before);
+
+        add_constraints_in(&state, vec![pp(3, [1.0, 1.0], [4.0, 9.0])]).unwrap();
+        let after = pairs(&state);
+        assert_ne!(after, before);
+        let TransformKind::Similarity(t2) = cached_fit(&state, "similarity", &after).unwrap()
+        else {
+            panic!("expected a similarity");
+        };
+        assert_eq!(cached_pairs(&state), after);
+        assert_ne!(t1.params, t2.params);
+        // The refit is what solving from scratch gives
+        assert_eq!(
+            t2.params,
+            solver::fit_similarity_from_pairs(&after).unwrap().params
+        );
+
+        // The entry for `after` is not returned for other pairs, as a hash
+        // collision could have done
+        let TransformKind::Similarity(t3) = cached_fit(&state, "similarity", &before).unwrap()
+        else {
+            panic!("expected a similarity");
+        };
+        assert_eq!(t3.params, t1.params);
+    }
+
+    #[test]
+    fn test_export_geopackage_writes_sqlite() {
+        let state = state_with_points(3);
+        let path = std::env::temp_dir().join(format!("desktop-gpkg-{}.gpkg", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        // Reference pixels only: no ground coordinates to place points at
+        assert!(export_geopackage_in(&state, &path, "similarity").is_err());
+        *state.ref_georef.lock().unwrap() = Some(io::Georef {
+            affine: [2.0, 0.0, 0.0, -2.0, 500000.0, 4200000.0],
+            wkt: Some("EPSG:32610".into()),
+        });
+        export_geopackage_in(&state, &path, "similarity").unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.starts_with(b"SQLite format 3\0"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_error_unit_preference_applies_to_solve() {
+        let state = state_with_points(3);
+        assert!(set_error_unit_in(&state, "feet", None).is_err());
+        assert!(set_error_unit_in(&state, "mapmm", None).is_err());
+        assert!(set_error_unit_in(&state, "mapmm", Some(0.0)).is_err());
+        // Rejected calls leave no preference behind
+        assert_eq!(
+            resolve_error_unit(&state, None, None).unwrap(),
+            ("pixels".to_string(), None)
+        );
+
+        set_error_unit_in(&state, "mapmm", Some(25000.0)).unwrap();
+        let (unit, scale) = resolve_error_unit(&state, None, None).unwrap();
+        let (_, qm) = solve_global_in(
+            &state,
+            "similarity",
+            &unit,
+            scale,
+            None,
+            solver::CoordinateSpace::Pixels,
+        )
+        .unwrap();
+        assert_eq!(qm.unit, ErrorUnit::MapMillimeters);
+        assert_eq!(qm.map_scale, Some(25000.0));
+
+        // An explicit unit still wins
+        assert_eq!(
+            resolve_error_unit(&state, Some("meters"), None).unwrap(),
+            ("meters".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_error_unit_preference_applies_to_every_metrics_command() {
+        let state = state_with_points(3);
+        state
+            .constraints
+            .lock()
+            .unwrap()
+            .push(pp(3, [1.0, 1.0], [-1.2, 1.1]));
+        // 2 m reference pixels, so meters read twice the pixel values
+        *state.ref_georef.lock().unwrap() = Some(io::Georef {
+            affine: [2.0, 0.0, 0.0, -2.0, 500000.0, 4200000.0],
+            wkt: None,
+        });
+        let prior = TransformKind::Similarity(types::Similarity {
+            params: [1.0, std::f64::consts::FRAC_PI_2, 0.0, 0.0],
+        });
+        let json_px = solve_to_json_in(&state, "similarity", None, None, None).unwrap();
+        let point_px = point_residual_in(&state, 3, "similarity", None, None)
+            .unwrap()
+            .unwrap();
+        let eval_px = evaluate_transform_in(&state, &prior, None, None).unwrap();
+        assert_eq!(json_px.metrics.unit, ErrorUnit::Pixels);
+        assert!(point_px > 0.0 && eval_px.rmse > 0.0);
+
+        set_error_unit_in(&state, "meters", None).unwrap();
+        let json_m = solve_to_json_in(&state, "similarity", None, None, None).unwrap();
+        assert_eq!(json_m.metrics.unit, ErrorUnit::Meters);
+        assert!((json_m.metrics.rmse - 2.0 * json_px.metrics.rmse).abs() < 1e-9);
+        let point_m = point_residual_in(&state, 3, "similarity", None, None)
+            .unwrap()
+            .unwrap();
+        assert!((point_m - 2.0 * point_px).abs() < 1e-9);
+        let eval_m = evaluate_transform_in(&state, &prior, None, None).unwrap();
+        assert_eq!(eval_m.unit, ErrorUnit::Meters);
+        assert!((eval_m.rmse - 2.0 * eval_px.rmse).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pixel_modes_survive_unparseable_crs() {
+        let state = AppState::default();
+        *state.ref_georef.lock().unwrap() = Some(io::Georef {
+            affine: [2.0, 0.0, 0.0, -2.0, 100.0, 200.0],
+            wkt: Some("not a CRS".to_string()),
+        });
+        assert_eq!(
+            _convert_reference_pixel(3.0, 4.0, "pixel", "WGS84", &state).unwrap(),
+            Some([3.0, 4.0])
+        );
+        assert!(_convert_reference_pixel(3.0, 4.0, "lonlat", "WGS84", &state).is_err());
+        assert!(_convert_reference_pixel(3.0, 4.0, "utm", "WGS84", &state).is_err());
+        // Enrichment still fills in world coordinates
+        let mut c = pp(0, [0.0, 0.0], [1.0, 1.0]);
+        enrich_point_pair(&mut c, &enrichment_projector(&state).unwrap().unwrap());
+        let ConstraintKind::PointPair {
+            dst_real,
+            dst_local,
+            ..
+        } = c
+        else {
+            unreachable!()
+        };
+        assert_eq!(dst_real, Some([102.0, 198.0]));
+        assert_eq!(dst_local, None);
+    }
+
+    #[test]
+    fn test_solve_to_json_matches_cli_solve() {
+        let state = AppState::default();
+        state.constraints.lock().unwrap().extend(
+            [
+                [0.0, 0.0],
+                [100.0, 0.0],
+                [0.0, 80.0],
+                [90.0, 70.0],
+                [40.0, 30.0],
+            ]
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                pp(
+                    i as u64,
+                    *s,
+                    [2.0 * s[0] + 0.3 * (i % 2) as f64, 2.0 * s[1] + 5.0],
+                )
+            }),
+        );
+        let desktop = solve_to_json_in(&state, "affine", Some("pixels"), None, None).unwrap();
+        let cli = solver::solve_constraints(
+            &state.constraints.lock().unwrap(),
+            solver::FitMethod::Affine,
+        )
+        .unwrap();
+        assert_eq!(desktop.n_pairs, 5);
+        assert_eq!(desktop.n_pairs, cli.n_pairs);
+        let (d, c) = (&desktop.metrics, &cli.metrics);
+        assert!((d.rmse - c.rmse).abs() < 1e-12 && (d.p90_error - c.p90_error).abs() < 1e-12);
+        assert_eq!(d.residuals.len(), c.residuals.len());
+        for ((di, dr), (ci, cr)) in d.residuals_by_id.iter().zip(&c.residuals_by_id) {
+            assert_eq!(di, ci);
+            assert!((dr - cr).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_sub_pixel_fit_has_no_axis_warning() {
+        assert!(axis_imbalance_warning(0.002, 0.0009, 1.0).is_none());
+        assert!(axis_imbalance_warning(3.0, 1.0, 1.0).is_some());
+        // 3 m against 10 m reference pixels is still sub-pixel
+        assert!(axis_imbalance_warning(3.0, 1.0, 10.0).is_none());
+
+        // Tiny x-only errors: anisotropic, but far below a pixel
+        let state = AppState::default();
+        state.constraints.lock().unwrap().extend((0..6).map(|i| {
+            let s = [(i % 3) as f64 * 50.0, (i / 3) as f64 * 80.0];
+            let e = if i % 2 == 0 { 0.002 } else { -0.002 };
+            pp(i, s, [s[0] + e, s[1]])
+        }));
+        let (_, qm) = solve_global_in(
+            &state,
+            "affine",
+            "pixels",
+            None,
+            None,
+            solver::CoordinateSpace::Pixels,
+        )
+        .unwrap();
+        assert!(qm.rmse_x > 2.0 * qm.rmse_y);
+        assert!(
+            !qm.warnings.iter().any(|w| w.contains("anisotropic")),
+            "{:?}",
+            qm.warnings
+        );
+    }
+
+    /// Write a black 24-bit BMP of `w` x `h` pixels (the desktop crate has no
+    /// image encoder of its own).
+    fn write_test_bmp(path: &std::path::Path, w: u32, h: u32) {
+        let row = (w * 3).div_ceil(4) * 4;
+        let size = 54 + row * h;
+        let mut bytes = b"BM".to_vec();
+        for v in [size, 0, 54, 40] {
+            bytes.extend(v.to_le_bytes());
+        }
+        bytes.extend((w as i32).to_le_bytes());
+        bytes.extend((h as i32).to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(24u16.to_le_bytes());
+        for v in [0, row * h, 2835, 2835, 0, 0] {
+            bytes.extend(v.to_le_bytes());
+        }
+        bytes.resize(size as usize, 0);
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_annotated_preview_ignores_stale_residuals() {
+        let dir = std::env::temp_dir().join(format!("desktop-annotate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let map = dir.join("map.bmp");
+        write_test_bmp(&map, 40, 30);
+        let state = state_with_points(3);
+        *state.map_path.lock().unwrap() = Some(map.to_string_lossy().into_owned());
+        let out = dir.join("qc.png").to_string_lossy().into_owned();
+        let render = |state: &AppState| {
+            export_annotated_preview_in(state, &out).unwrap();
+            std::fs::read(&out).unwrap()
+        };
+        let plain = render(&state);
+        solve_global_in(
+            &state,
+            "similarity",
+            "pixels",
+            None,
+            None,
+            solver::CoordinateSpace::Pixels,
+        )
+        .unwrap();
+        assert_ne!(render(&state), plain);
+        // Editing the constraints (here a weight, so the pins stay put)
+        // retires the solve's residuals
+        if let ConstraintKind::PointPair { weight, .. } = &mut state.constraints.lock().unwrap()[0]
+        {
+            *weight = 2.0;
+        }
+        assert_eq!(render(&state), plain);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_every_export_honors_y_axis_down() {
+        let dir = std::env::temp_dir().join(format!("desktop-yaxis-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (map, reference) = (dir.join("map.bmp"), dir.join("ref.bmp"));
+        write_test_bmp(&map, 40, 30);
+        write_test_bmp(&reference, 80, 60);
+        let state = AppState::default();
+        *state.map_path.lock().unwrap() = Some(map.to_string_lossy().into_owned());
+        *state.reference_path.lock().unwrap() = Some(reference.to_string_lossy().into_owned());
+        // Reference without a world file: world = reference pixels, so every
+        // export writes the map -> reference fit itself
+        state.constraints.lock().unwrap().extend(
+            [[0.0, 0.0], [30.0, 0.0], [0.0, 20.0], [30.0, 20.0]]
+                .iter()
+                .enumerate()
+                .map(|(i, s)| pp(i as u64, *s, [2.0 * s[0] + 5.0, 2.0 * s[1] + 7.0])),
+        );
+        let fitted = [2.0, 0.0, 0.0, 2.0, 5.0, 7.0];
+        let flipped = io::flip_affine_rows(fitted, 30);
+        let assert_affine = |got: [f64; 6], expected: [f64; 6], what: &str| {
+            for (g, e) in got.iter().zip(expected) {
+                assert!(
+                    (g - e).abs() < 1e-6,
+                    "{}: {:?} vs {:?}",
+                    what,
+                    got,
+                    expected
+                );
+            }
+        };
+        let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+
+        export_world_file_in(&state, &path("wf"), "affine", None, None).unwrap();
+        assert_affine(io::read_world_file(&path("wf")).unwrap(), fitted, "default");
+        export_world_file_in(&state, &path("wf"), "affine", Some(false), None).unwrap();
+        assert_affine(
+            io::read_world_file(&path("wf")).unwrap(),
+            flipped,
+            "world file",
+        );
+
+        let map_str = map.to_string_lossy().into_owned();
+        export_world_file_for_image_in(&state, &map_str, "affine", Some(false), None).unwrap();
+        let sidecar = io::read_world_file_for_image(&map_str).unwrap().unwrap();
+        assert_affine(sidecar, flipped, "sidecar");
+
+        export_georeferenced_geotiff_in(&state, "affine", &path("geo"), Some(false), None).unwrap();
+        assert_affine(
+            io::read_world_file(&path("geo")).unwrap(),
+            flipped,
+            "export",
+        );
+
+        export_embedded_geotiff_in(&state, "affine", &path("emb.tif"), Some(false)).unwrap();
+        let embedded = io::read_geotiff_georeferencing(&path("emb.tif"))
+            .unwrap()
+            .unwrap();
+        assert_affine(embedded.affine, flipped, "embedded");
+
+        // Every world file export takes the significant-digit precision
+        let first_line = |p: &str| {
+            std::fs::read_to_string(p)
+                .unwrap()
+                .lines()
+                .next()
+                .unwrap()
+                .to_string()
+        };
+        export_world_file_in(&state, &path("wf"), "affine", None, Some(3)).unwrap();
+        assert_eq!(first_line(&path("wf.tfw")), "2.00");
+        let written =
+            export_world_file_for_image_in(&state, &map_str, "affine", None, Some(3)).unwrap();
+        assert_eq!(first_line(&written), "2.00");
+        export_georeferenced_geotiff_in(&state, "affine", &path("geo"), None, Some(3)).unwrap();
+        assert_eq!(first_line(&path("geo.tfw")), "2.00");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_preview_composes_reference_world_file() {
+        let dir = std::env::temp_dir().join(format!("desktop-preview-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (map, reference) = (dir.join("map.bmp"), dir.join("ref.bmp"));
+        write_test_bmp(&map, 40, 30);
+        write_test_bmp(&reference, 80, 60);
+        // Reference pixels -> world: 0.5 units/px, north up, origin (1000, 2000)
+        io::write_world_file(
+            &dir.join("ref").to_string_lossy(),
+            [0.5, 0.0, 0.0, -0.5, 1000.0, 2000.0],
+        )
+        .unwrap();
+        let state = AppState::default();
+        *state.reference_path.lock().unwrap() = Some(reference.to_string_lossy().into_owned());
+        state.constraints.lock().unwrap().extend(
+            [[0.0, 0.0], [30.0, 0.0], [0.0, 20.0], [30.0, 20.0]]
+                .iter()
+                .enumerate()
+                .map(|(i, s)| pp(i as u64, *s, [2.0 * s[0] + 5.0, 2.0 * s[1] + 7.0])),
+        );
+
+        // Without the map image its size, and so the extent, is unknown
+        let preview = build_export_preview("affine", &state).unwrap();
+        assert!(preview.extent.is_none());
+        assert_eq!(preview.warnings.len(), 1);
+
+        // world = ref_aff ∘ (2 px + (5, 7)): x + 1002.5, -y + 1996.5
+        *state.map_path.lock().unwrap() = Some(map.to_string_lossy().into_owned());
+        *state.fallback_crs.lock().unwrap() = Some("EPSG:32633".to_string());
+        let preview = build_export_preview("affine", &state).unwrap();
+        for (got, expected) in preview
+            .affine
+            .iter()
+            .zip([1.0, 0.0, 0.0, -1.0, 1002.5, 1996.5])
+        {
+            assert!((got - expected).abs() < 1e-6, "{:?}", preview.affine);
+        }
+        // 40x30 px with pixel edges at -0.5 and 39.5 / 29.5
+        let extent = preview.extent.unwrap();
+        for (got, expected) in extent.iter().zip([1002.0, 1967.0, 1042.0, 1997.0]) {
+            assert!((got - expected).abs() < 1e-6, "{:?}", extent);
+        }
+        assert_eq!(preview.prj_wkt, "EPSG:32633");
+        assert!(preview.warnings.is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reference_falls_through_a_broken_world_file() {
+        let dir = std::env::temp_dir().join(format!("desktop-refsrc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let reference = dir.join("ref.png");
+        std::fs::write(dir.join("ref.pgw"), "0\n0\n0\n-1\n500\n900\n").unwrap();
+        let state = AppState::default();
+        let path = reference.to_string_lossy().into_owned();
+        // Only the singular world file: no georeferencing, and a warning why
+        let warnings = set_reference_path_in(&state, path.clone()).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(state.ref_georef.lock().unwrap().is_none());
+        std::fs::write(
+            dir.join("ref.png.aux.xml"),
+            "<PAMDataset><GeoTransform>100, 2, 0, 900, 0, -2</GeoTransform></PAMDataset>",
+        )
+        .unwrap();
+        let warnings = set_reference_path_in(&state, path).unwrap();
+        assert!(warnings[0].contains("ref.pgw"), "{:?}", warnings);
+        assert_eq!(
+            state.ref_georef.lock().unwrap().as_ref().unwrap().affine,
+            [2.0, 0.0, 0.0, -2.0, 101.0, 899.0]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
     }
-    Ok(None)
 }
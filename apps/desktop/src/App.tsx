@@ -4,6 +4,8 @@ import { open } from '@tauri-apps/plugin-dialog';
 import { invoke } from '@tauri-apps/api/core';
 import { downloadLogs, clearLogs, log, setLoggingEnabled, isLoggingEnabled, getLogs } from './logger';
 
+type LoadedRaster = { data_uri: string; width: number; height: number; affine: [number, number, number, number, number, number] | null };
+
 function App() {
   const buildTag = useMemo(() => new Date().toISOString(), []);
   const isDev = (import.meta as any)?.env?.DEV === true;
@@ -90,9 +92,9 @@ function App() {
         setMapPath(path);
         await invoke('set_map_path', { path });
         console.log('[map] set_map_path OK');
-        const data: string = await invoke('load_raster_data', { path });
-        console.log('[map] load_raster_data OK, bytes:', data?.length || 0);
-        setMapImg(data);
+        const raster: LoadedRaster = await invoke('load_raster_data', { path });
+        console.log('[map] load_raster_data OK, bytes:', raster.data_uri?.length || 0, 'affine:', raster.affine);
+        setMapImg(raster.data_uri);
       }
     } catch (e) {
       console.error('[map] failed:', e);
@@ -109,9 +111,9 @@ function App() {
         setRefPath(path);
         await invoke('set_reference_path', { path });
         console.log('[reference] set_reference_path OK');
-        const data: string = await invoke('load_raster_data', { path });
-        console.log('[reference] load_raster_data OK, bytes:', data?.length || 0);
-        setRefImg(data);
+        const raster: LoadedRaster = await invoke('load_raster_data', { path });
+        console.log('[reference] load_raster_data OK, bytes:', raster.data_uri?.length || 0, 'affine:', raster.affine);
+        setRefImg(raster.data_uri);
         try {
           const geo = await invoke('get_reference_georef');
           console.log('[reference] georef:', geo);
@@ -1,16 +1,127 @@
-use clap::Parser;
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use solver::{FitMethod, PixelOrigin};
+use std::io::Read;
+use std::path::{Path, PathBuf};
 
 /// A simple CLI for the ML-assisted georeferencer
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    // Placeholder for CLI arguments
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-fn main() {
-    let _args = Args::parse();
-    println!(
-        "ML-Assisted Georeferencer CLI v{}",
-        env!("CARGO_PKG_VERSION")
-    );
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Fit a global transform to a JSON array of constraints and print the
+    /// result (transform, metrics, method, pair count) as JSON
+    Solve {
+        /// JSON file holding `ConstraintKind[]`, or `-` for stdin
+        constraints: PathBuf,
+        /// `similarity` or `affine`
+        #[arg(long, default_value = "affine")]
+        method: String,
+    },
+    /// Print the PROJ pipeline equivalent to a world file
+    Proj {
+        /// World file (`.tfw`, `.jgw`, `.wld`, ...)
+        #[arg(long)]
+        world: PathBuf,
+        /// `center` (world-file convention) or `corner` for GDAL pixel/line input
+        #[arg(long, default_value = "center")]
+        pixel_origin: String,
+    },
+    /// Convert a pipeline of `+proj=affine` steps into world file lines
+    World {
+        /// PROJ pipeline, e.g. the output of `proj`
+        #[arg(long)]
+        proj: String,
+        /// Pixel convention of the pipeline's input (`center` or `corner`)
+        #[arg(long, default_value = "center")]
+        pixel_origin: String,
+        /// Write the world file here instead of printing it
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Round values to this many significant digits (default: full precision)
+        #[arg(long)]
+        digits: Option<usize>,
+    },
+}
+
+fn pixel_origin(name: &str) -> anyhow::Result<PixelOrigin> {
+    match name {
+        "center" => Ok(PixelOrigin::Center),
+        "corner" => Ok(PixelOrigin::Corner),
+        other => anyhow::bail!("unknown pixel origin {}", other),
+    }
+}
+
+/// Parse `ConstraintKind[]` JSON from `path`, or from stdin when it is `-`.
+fn read_constraints(path: &Path) -> anyhow::Result<Vec<types::ConstraintKind>> {
+    let (name, text) = if path == Path::new("-") {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .context("reading constraints from stdin")?;
+        ("stdin".to_string(), text)
+    } else {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        (path.display().to_string(), text)
+    };
+    serde_json::from_str(&text).with_context(|| {
+        format!(
+            "{}: invalid constraints JSON (expected ConstraintKind[])",
+            name
+        )
+    })
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    match args.command {
+        Some(Command::Solve {
+            constraints,
+            method,
+        }) => {
+            let method = match method.as_str() {
+                "similarity" => FitMethod::Similarity,
+                "affine" => FitMethod::Affine,
+                other => anyhow::bail!("unknown method {}", other),
+            };
+            let list = read_constraints(&constraints)?;
+            let result = solver::solve_constraints(&list, method)?;
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+        Some(Command::Proj {
+            world,
+            pixel_origin: origin,
+        }) => {
+            let params = io::parse_world_file(&std::fs::read_to_string(&world)?)?;
+            let affine = types::Affine { params };
+            println!(
+                "{}",
+                solver::affine_to_proj(&affine, pixel_origin(&origin)?)
+            );
+        }
+        Some(Command::World {
+            proj,
+            pixel_origin: origin,
+            output,
+            digits,
+        }) => {
+            let affine = solver::proj_to_affine(&proj, pixel_origin(&origin)?)?;
+            let text = io::world_file_contents_with_precision(affine.params, digits);
+            match output {
+                Some(path) => std::fs::write(path, text)?,
+                None => print!("{}", text),
+            }
+        }
+        None => println!(
+            "ML-Assisted Georeferencer CLI v{}",
+            env!("CARGO_PKG_VERSION")
+        ),
+    }
+    Ok(())
 }
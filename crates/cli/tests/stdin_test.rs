@@ -0,0 +1,55 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn solve_stdin(input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cli"))
+        .args(["solve", "--method", "similarity", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_solve_reads_constraints_from_stdin() {
+    // dst = 2 * src + (5, -3)
+    let pairs: Vec<_> = [[0.0, 0.0], [10.0, 0.0], [0.0, 10.0]]
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            serde_json::json!({"PointPair": {
+                "id": i, "src": s, "dst": [2.0 * s[0] + 5.0, 2.0 * s[1] - 3.0],
+                "dst_real": null, "dst_local": null, "src_z": null, "dst_z": null,
+                "weight": 1.0
+            }})
+        })
+        .collect();
+    let out = solve_stdin(&serde_json::to_string(&pairs).unwrap());
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(result["n_pairs"], 3);
+    let params = result["transform"]["Similarity"]["params"]
+        .as_array()
+        .unwrap();
+    assert!((params[0].as_f64().unwrap() - 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_malformed_stdin_names_the_problem() {
+    let out = solve_stdin("[{\"PointPair\": ");
+    assert!(!out.status.success());
+    let err = String::from_utf8_lossy(&out.stderr);
+    assert!(err.contains("stdin: invalid constraints JSON"), "{}", err);
+}
@@ -0,0 +1,104 @@
+use crate::draw::{draw_text, fill_rect, pin_head, text_width, PIN_WHITE};
+use anyhow::Result;
+use image::ImageFormat;
+use types::ErrorUnit;
+
+/// A control point to draw with `write_annotated_preview`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PreviewMarker {
+    pub id: u64,
+    /// Map pixel (pixel centers at integer coordinates)
+    pub px: [f64; 2],
+    /// Residual from the latest solve, if any
+    pub residual: Option<f64>,
+}
+
+/// Write `image_path` as a PNG at `out_path` with each marker burned in as a
+/// map pin head (`draw::pin_head`, as in the app icon) with a colored center
+/// and its id alongside. When markers carry residuals the centers run green to
+/// red from 0 to the largest residual, with that scale and the residuals'
+/// `unit` drawn bottom left; otherwise they are all the icon's purple.
+/// Markers scale with the image.
+pub fn write_annotated_preview(
+    image_path: &str,
+    markers: &[PreviewMarker],
+    unit: ErrorUnit,
+    out_path: &str,
+) -> Result<()> {
+    let mut img = image::open(image_path)?.to_rgba8();
+    let (w, h) = img.dimensions();
+    let radius = (w.min(h) as f64 / 80.0).max(4.0);
+    // Pixel size of one font cell, so labels grow with the markers
+    let cell = ((radius / 4.0).round() as u32).max(1);
+    let max_residual = markers
+        .iter()
+        .filter_map(|m| m.residual)
+        .fold(0.0f64, f64::max);
+    let has_residuals = markers.iter().any(|m| m.residual.is_some());
+    let black = image::Rgba([20, 20, 20, 255]);
+    for m in markers {
+        let center = match m.residual {
+            Some(r) if has_residuals => residual_color(r / max_residual.max(f64::MIN_POSITIVE)),
+            _ => image::Rgba([100, 60, 150, 255]),
+        };
+        pin_head(&mut img, m.px, radius, PIN_WHITE, |_, _| center);
+        let label = m.id.to_string();
+        let x = (m.px[0] + radius + 2.0).round() as i64;
+        let y = (m.px[1] - 2.5 * cell as f64).round() as i64;
+        fill_rect(
+            &mut img,
+            x - 1,
+            y - 1,
+            text_width(&label, cell) + 2,
+            5 * cell + 2,
+            PIN_WHITE,
+        );
+        draw_text(&mut img, x, y, &label, cell, black);
+    }
+    if has_residuals {
+        // Legend: a gradient bar from 0 to the largest residual
+        let (bar_w, bar_h) = (32 * cell, 3 * cell);
+        let (x0, y0) = (4 * cell as i64, h as i64 - (bar_h + 10 * cell) as i64);
+        let min_label = "0";
+        let max_label = format!("{:.2} {}", max_residual, unit_label(unit));
+        let total_w = bar_w + text_width(&max_label, cell) + 4 * cell;
+        fill_rect(
+            &mut img,
+            x0 - 2,
+            y0 - 2,
+            total_w,
+            bar_h + 8 * cell + 4,
+            PIN_WHITE,
+        );
+        for i in 0..bar_w {
+            let color = residual_color(i as f64 / (bar_w - 1).max(1) as f64);
+            fill_rect(&mut img, x0 + i as i64, y0, 1, bar_h, color);
+        }
+        let text_y = y0 + (bar_h + 2 * cell) as i64;
+        draw_text(&mut img, x0, text_y, min_label, cell, black);
+        let max_x = x0 + bar_w as i64 - text_width(&max_label, cell) as i64;
+        draw_text(&mut img, max_x.max(x0), text_y, &max_label, cell, black);
+    }
+    img.save_with_format(out_path, ImageFormat::Png)?;
+    Ok(())
+}
+
+fn unit_label(unit: ErrorUnit) -> &'static str {
+    match unit {
+        ErrorUnit::Pixels => "px",
+        ErrorUnit::Meters => "m",
+        ErrorUnit::MapMillimeters => "mm",
+    }
+}
+
+/// Green (t = 0) through yellow to red (t = 1).
+fn residual_color(t: f64) -> image::Rgba<u8> {
+    let t = if t.is_finite() {
+        t.clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let r = (510.0 * t).min(255.0);
+    let g = (510.0 * (1.0 - t)).min(255.0);
+    image::Rgba([r as u8, g as u8, 40, 255])
+}
@@ -0,0 +1,117 @@
+//! Raster drawing shared by the annotated preview (`write_annotated_preview`)
+//! and the app icon generator (tools/icon-gen): the map pin and a 3x5 bitmap
+//! font. Coordinates are image pixels; anything outside the image is clipped.
+
+use image::{Rgba, RgbaImage};
+
+/// White of the pin body, as in the app icon.
+pub const PIN_WHITE: Rgba<u8> = Rgba([250, 250, 250, 255]);
+
+/// Radius of the pin's hole relative to its head.
+pub const PIN_HOLE: f64 = 0.45;
+
+/// Head of a map pin: a `body` disk of radius `r` around `c` with a hole of
+/// radius `PIN_HOLE * r` painted by `hole` (called per pixel, so the icon
+/// can show its background gradient through it).
+pub fn pin_head(
+    img: &mut RgbaImage,
+    c: [f64; 2],
+    r: f64,
+    body: Rgba<u8>,
+    hole: impl Fn(u32, u32) -> Rgba<u8>,
+) {
+    fill_disk_with(img, c, r, |_, _| body);
+    fill_disk_with(img, c, r * PIN_HOLE, hole);
+}
+
+/// Tail of a map pin with head radius `r` at `c`: a triangle pointing down,
+/// half the radius tall, tucked slightly under the head.
+pub fn pin_tail(img: &mut RgbaImage, c: [f64; 2], r: f64, color: Rgba<u8>) {
+    let (tail_h, tail_w) = (r * 0.5, r * 5.0 / 14.0);
+    let base = (c[1] + r - r / 14.0).round() as i64;
+    for i in 0..tail_h.round() as i64 {
+        let half = (tail_w * (1.0 - i as f64 / tail_h)).round();
+        let x = (c[0] - half).round() as i64;
+        fill_rect(img, x, base + i, 2 * half as u32 + 1, 1, color);
+    }
+}
+
+/// Fill the pixels whose centers lie within `r` of `c` with `color(x, y)`.
+fn fill_disk_with(img: &mut RgbaImage, c: [f64; 2], r: f64, color: impl Fn(u32, u32) -> Rgba<u8>) {
+    let (w, h) = img.dimensions();
+    let (x0, x1) = (
+        (c[0] - r).floor().max(0.0),
+        (c[0] + r).ceil().min(w as f64 - 1.0),
+    );
+    let (y0, y1) = (
+        (c[1] - r).floor().max(0.0),
+        (c[1] + r).ceil().min(h as f64 - 1.0),
+    );
+    if x0 > x1 || y0 > y1 {
+        return;
+    }
+    for y in y0 as u32..=y1 as u32 {
+        for x in x0 as u32..=x1 as u32 {
+            if (x as f64 - c[0]).powi(2) + (y as f64 - c[1]).powi(2) <= r * r {
+                img.put_pixel(x, y, color(x, y));
+            }
+        }
+    }
+}
+
+/// Fill the `w`x`h` rectangle with its top-left pixel at (`x`, `y`).
+pub fn fill_rect(img: &mut RgbaImage, x: i64, y: i64, w: u32, h: u32, color: Rgba<u8>) {
+    let (iw, ih) = img.dimensions();
+    for yy in y.max(0)..(y + h as i64).min(ih as i64) {
+        for xx in x.max(0)..(x + w as i64).min(iw as i64) {
+            img.put_pixel(xx as u32, yy as u32, color);
+        }
+    }
+}
+
+/// 3x5 bitmap glyphs: rows top to bottom, bit 2 the leftmost column. Digits,
+/// '.', and the letters of the residual unit labels; anything else (e.g. a
+/// space) is left blank.
+fn glyph(c: char) -> Option<[u8; 5]> {
+    Some(match c {
+        '0' => [7, 5, 5, 5, 7],
+        '1' => [2, 6, 2, 2, 7],
+        '2' => [7, 1, 7, 4, 7],
+        '3' => [7, 1, 7, 1, 7],
+        '4' => [5, 5, 7, 1, 1],
+        '5' => [7, 4, 7, 1, 7],
+        '6' => [7, 4, 7, 5, 7],
+        '7' => [7, 1, 1, 1, 1],
+        '8' => [7, 5, 7, 5, 7],
+        '9' => [7, 5, 7, 1, 7],
+        '.' => [0, 0, 0, 0, 2],
+        'm' => [0, 0, 7, 7, 5],
+        'p' => [0, 6, 5, 6, 4],
+        'x' => [0, 0, 5, 2, 5],
+        _ => return None,
+    })
+}
+
+/// Width in pixels of `text` drawn by `draw_text` with `cell`-pixel dots
+/// (glyphs are 3 cells wide with a 1-cell gap; the text is 5 cells tall).
+pub fn text_width(text: &str, cell: u32) -> u32 {
+    (text.chars().count() as u32 * 4).saturating_sub(1) * cell
+}
+
+/// Draw `text` in the 3x5 font with its top-left corner at (`x`, `y`), each
+/// font dot a `cell`x`cell` square.
+pub fn draw_text(img: &mut RgbaImage, x: i64, y: i64, text: &str, cell: u32, color: Rgba<u8>) {
+    for (i, c) in text.chars().enumerate() {
+        let Some(rows) = glyph(c) else { continue };
+        let gx = x + (i as i64) * 4 * cell as i64;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (4 >> col) != 0 {
+                    let px = gx + col * cell as i64;
+                    let py = y + row as i64 * cell as i64;
+                    fill_rect(img, px, py, cell, cell, color);
+                }
+            }
+        }
+    }
+}
@@ -5,6 +5,10 @@ use proj::Proj;
 use std::io::Cursor;
 use std::path::Path; // kept for potential future use; ignore if unused
 
+mod annotate;
+pub mod draw;
+pub use annotate::{write_annotated_preview, PreviewMarker};
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Georef {
     pub affine: [f64; 6],
@@ -18,35 +22,434 @@ pub fn read_georeferencing(path_without_ext: &str) -> Result<Georef> {
     let affine = read_world_file(path_without_ext)?;
     let mut prj = PathBuf::from(path_without_ext);
     prj.set_extension("prj");
-    let wkt = read_to_string(&prj).ok();
+    let wkt = read_to_string(&prj).ok().map(|s| match is_esri_wkt(&s) {
+        true => normalize_esri_wkt(&s).unwrap_or(s),
+        false => s,
+    });
     Ok(Georef { affine, wkt })
 }
 
 /// Read georeferencing for an image path by trying common sidecar world/PRJ files,
-/// then falling back to embedded GeoTIFF tags when the input is TIFF.
+/// then a GDAL `.aux.xml` sidecar, then a MapInfo `.tab`, then falling back to
+/// embedded GeoTIFF tags when the input is TIFF.
 /// Returns Ok(Some(Georef)) on success, Ok(None) if nothing found.
 pub fn read_georeferencing_for_image(image_path: &str) -> Result<Option<Georef>> {
-    if let Some(aff) = read_world_file_for_image(image_path)? {
-        let wkt = read_prj_for_image(image_path);
-        return Ok(Some(Georef { affine: aff, wkt }));
+    Ok(read_georeferencing_detailed(image_path)?.map(|d| d.georef))
+}
+
+/// Where `read_georeferencing_detailed` found an image's affine.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GeorefSource {
+    /// A world-file sidecar (e.g. `map.tfw`, `map.wld`).
+    WorldFile { path: std::path::PathBuf },
+    /// A GDAL PAM sidecar (`map.png.aux.xml`).
+    AuxXml { path: std::path::PathBuf },
+    /// A MapInfo registration sidecar (`map.tab`), fitted from its control
+    /// points.
+    MapInfoTab { path: std::path::PathBuf },
+    /// The GeoTIFF ModelTransformation tag.
+    GeoTiffTransform,
+    /// The GeoTIFF ModelPixelScale + ModelTiepoint tags.
+    GeoTiffTiepoint,
+}
+
+/// Where `read_georeferencing_detailed` found an image's CRS, which need not
+/// be where the affine came from (e.g. `map.tfw` plus `map.prj`).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CrsSource {
+    /// A `.prj` (or `.wkt`) sidecar.
+    Prj { path: std::path::PathBuf },
+    /// The `<SRS>` element of a GDAL PAM sidecar.
+    AuxXml { path: std::path::PathBuf },
+    /// The `CoordSys` clause of a MapInfo `.tab`.
+    MapInfoTab { path: std::path::PathBuf },
+    /// The GeoTIFF GeoKey directory.
+    GeoTiffKeys,
+}
+
+/// Georeferencing found by `read_georeferencing_detailed`, with where its
+/// affine and its CRS came from. `crs_source` is None exactly when
+/// `georef.wkt` is. `warnings` holds the errors of sources tried before this
+/// one and skipped (e.g. a singular `.tfw` in front of a valid `.aux.xml`).
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GeorefDetail {
+    pub georef: Georef,
+    pub source: GeorefSource,
+    pub crs_source: Option<CrsSource>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// `read_georeferencing_for_image`, also reporting which sources supplied the
+/// affine and the CRS (so the UI can show "georeferencing from map.tfw, CRS
+/// from map.prj"). The same order is tried; a `.prj` sidecar only ever
+/// supplies the CRS. A source that fails to parse or whose affine fails
+/// `validate_world_affine` is skipped and its error kept in `warnings`; the
+/// read errors only when every source present failed.
+pub fn read_georeferencing_detailed(image_path: &str) -> Result<Option<GeorefDetail>> {
+    let sources: [DetailReader; 4] = [
+        world_file_detail,
+        aux_xml_detail,
+        tab_detail,
+        geotiff_detail,
+    ];
+    let mut errors = Vec::new();
+    for source in sources {
+        match source(image_path) {
+            Ok(Some(mut detail)) => {
+                detail.warnings = errors;
+                return Ok(Some(detail));
+            }
+            Ok(None) => {}
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+    if errors.is_empty() {
+        Ok(None)
+    } else {
+        Err(anyhow::anyhow!(
+            "no usable georeferencing: {}",
+            errors.join("; ")
+        ))
     }
-    // Fallback: TIFF/GeoTIFF tags
+}
+
+/// One georeferencing source tried by `read_georeferencing_detailed`.
+type DetailReader = fn(&str) -> Result<Option<GeorefDetail>>;
+
+/// The CRS from a `.prj` sidecar, for sources that carry none of their own.
+fn prj_crs(image_path: &str) -> (Option<String>, Option<CrsSource>) {
+    find_prj_for_image(image_path)
+        .map(|(wkt, path)| (Some(wkt), Some(CrsSource::Prj { path })))
+        .unwrap_or((None, None))
+}
+
+fn world_file_detail(image_path: &str) -> Result<Option<GeorefDetail>> {
+    let Some((affine, path)) = find_world_file_for_image(image_path)? else {
+        return Ok(None);
+    };
+    let (wkt, crs_source) = prj_crs(image_path);
+    Ok(Some(GeorefDetail {
+        georef: Georef { affine, wkt },
+        source: GeorefSource::WorldFile { path },
+        crs_source,
+        warnings: Vec::new(),
+    }))
+}
+
+fn aux_xml_detail(image_path: &str) -> Result<Option<GeorefDetail>> {
+    let Some(mut georef) = read_aux_xml_for_image(image_path)? else {
+        return Ok(None);
+    };
+    let path = std::path::PathBuf::from(format!("{}.aux.xml", image_path));
+    validate_world_affine(georef.affine)
+        .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+    let crs_source = match georef.wkt {
+        Some(_) => Some(CrsSource::AuxXml { path: path.clone() }),
+        None => {
+            let (wkt, crs_source) = prj_crs(image_path);
+            georef.wkt = wkt;
+            crs_source
+        }
+    };
+    Ok(Some(GeorefDetail {
+        georef,
+        source: GeorefSource::AuxXml { path },
+        crs_source,
+        warnings: Vec::new(),
+    }))
+}
+
+fn tab_detail(image_path: &str) -> Result<Option<GeorefDetail>> {
+    let Some((mut georef, path)) = find_tab_for_image(image_path)? else {
+        return Ok(None);
+    };
+    let crs_source = match georef.wkt {
+        Some(_) => Some(CrsSource::MapInfoTab { path: path.clone() }),
+        None => {
+            let (wkt, crs_source) = prj_crs(image_path);
+            georef.wkt = wkt;
+            crs_source
+        }
+    };
+    Ok(Some(GeorefDetail {
+        georef,
+        source: GeorefSource::MapInfoTab { path },
+        crs_source,
+        warnings: Vec::new(),
+    }))
+}
+
+/// Fallback: TIFF/GeoTIFF tags
+fn geotiff_detail(image_path: &str) -> Result<Option<GeorefDetail>> {
     let ext = Path::new(image_path)
         .extension()
         .and_then(|e| e.to_str())
         .map(|s| s.to_ascii_lowercase())
         .unwrap_or_default();
-    if ext == "tif" || ext == "tiff" {
-        if let Some(g) = read_geotiff_georeferencing(image_path)? {
-            return Ok(Some(g));
-        }
+    if ext != "tif" && ext != "tiff" {
+        return Ok(None);
+    }
+    let Some((georef, source)) = read_geotiff_detailed(image_path)? else {
+        return Ok(None);
+    };
+    validate_world_affine(georef.affine)
+        .map_err(|e| anyhow::anyhow!("{}: GeoTIFF tags: {}", image_path, e))?;
+    let crs_source = georef.wkt.as_ref().map(|_| CrsSource::GeoTiffKeys);
+    Ok(Some(GeorefDetail {
+        georef,
+        source,
+        crs_source,
+        warnings: Vec::new(),
+    }))
+}
+
+/// Read a GDAL PAM sidecar (`<image>.aux.xml`, e.g. `map.png.aux.xml`):
+/// `<GeoTransform>` and, when present, `<SRS>` WKT. Ok(None) when there is no
+/// sidecar or it has no GeoTransform.
+pub fn read_aux_xml_for_image(image_path: &str) -> Result<Option<Georef>> {
+    let path = format!("{}.aux.xml", image_path);
+    let Ok(xml) = std::fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+    let Some(gt_text) = xml_element_text(&xml, "GeoTransform") else {
+        return Ok(None);
+    };
+    let gt: Vec<f64> = gt_text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.parse::<f64>())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|e| anyhow::anyhow!("{}: invalid GeoTransform: {}", path, e))?;
+    let gt: [f64; 6] = gt.try_into().map_err(|v: Vec<f64>| {
+        anyhow::anyhow!("{}: GeoTransform has {} values, expected 6", path, v.len())
+    })?;
+    let wkt = xml_element_text(&xml, "SRS")
+        .map(xml_unescape)
+        .filter(|w| !w.trim().is_empty())
+        .map(|w| match is_esri_wkt(&w) {
+            true => normalize_esri_wkt(&w).unwrap_or(w),
+            false => w,
+        });
+    Ok(Some(Georef {
+        affine: geotransform_to_world_file(gt),
+        wkt,
+    }))
+}
+
+/// Read a MapInfo raster registration sidecar (`map.tab` next to `map.tif`):
+/// the affine is a least-squares fit to its control points, the CRS comes
+/// from its `CoordSys` clause (see `mapinfo_coordsys_to_proj`, None when not
+/// understood). Ok(None) when there is no `.tab`; errors when it has fewer
+/// than 3 control points, or they are collinear.
+pub fn read_tab_for_image(image_path: &str) -> Result<Option<Georef>> {
+    Ok(find_tab_for_image(image_path)?.map(|(g, _)| g))
+}
+
+fn find_tab_for_image(image_path: &str) -> Result<Option<(Georef, std::path::PathBuf)>> {
+    let base = Path::new(image_path).with_extension("");
+    for ext in ["tab", "TAB"] {
+        let mut cand = base.clone();
+        cand.set_extension(ext);
+        let Ok(text) = std::fs::read_to_string(&cand) else {
+            continue;
+        };
+        let geo = parse_tab(&text).map_err(|e| anyhow::anyhow!("{}: {}", cand.display(), e))?;
+        return Ok(Some((geo, cand)));
     }
     Ok(None)
 }
 
+/// Georef from `.tab` text. Control points are lines like
+/// `(431000,4270000) (0,0) Label "Pt 1"`: map (x, y) then raster (column,
+/// row). MapInfo raster coordinates count from the top-left corner of the
+/// image, as GDAL's do, so they are moved half a pixel to pixel centers. The
+/// fitted affine must pass `validate_world_affine`, like every other source.
+fn parse_tab(text: &str) -> Result<Georef> {
+    fn pair(s: &str) -> Option<([f64; 2], &str)> {
+        let s = s.trim_start().strip_prefix('(')?;
+        let end = s.find(')')?;
+        let (a, b) = s[..end].split_once(',')?;
+        Some((
+            [a.trim().parse().ok()?, b.trim().parse().ok()?],
+            &s[end + 1..],
+        ))
+    }
+    let mut points = Vec::new();
+    let mut coordsys = None;
+    for line in text.lines() {
+        let t = line.trim();
+        if let Some((world, rest)) = pair(t) {
+            if let Some((px, _)) = pair(rest) {
+                points.push(([px[0] - 0.5, px[1] - 0.5], world));
+            }
+        } else if t.to_ascii_lowercase().starts_with("coordsys") {
+            coordsys = Some(t);
+        }
+    }
+    if points.len() < 3 {
+        anyhow::bail!("need at least 3 control points, found {}", points.len());
+    }
+    let affine = fit_control_points(&points)
+        .ok_or_else(|| anyhow::anyhow!("control points are collinear"))?;
+    // Raster points can be spread out while the map points are collinear
+    validate_world_affine(affine)?;
+    Ok(Georef {
+        affine,
+        wkt: coordsys.and_then(mapinfo_coordsys_to_proj),
+    })
+}
+
+/// Least-squares world-file affine [A,B,D,E,C,F] through (pixel, world)
+/// points, or None when the pixels are collinear. Each world axis is a
+/// separate plane fit over centered pixel coordinates.
+fn fit_control_points(points: &[([f64; 2], [f64; 2])]) -> Option<[f64; 6]> {
+    let n = points.len() as f64;
+    let [mu, mv, mx, my] = points.iter().fold([0.0; 4], |m, ([u, v], [x, y])| {
+        [m[0] + u / n, m[1] + v / n, m[2] + x / n, m[3] + y / n]
+    });
+    let (mut suu, mut suv, mut svv) = (0.0, 0.0, 0.0);
+    let (mut sux, mut svx, mut suy, mut svy) = (0.0, 0.0, 0.0, 0.0);
+    for ([u, v], [x, y]) in points {
+        let (u, v, x, y) = (u - mu, v - mv, x - mx, y - my);
+        suu += u * u;
+        suv += u * v;
+        svv += v * v;
+        sux += u * x;
+        svx += v * x;
+        suy += u * y;
+        svy += v * y;
+    }
+    let det = suu * svv - suv * suv;
+    if det.is_nan() || det.abs() <= 1e-12 * suu * svv {
+        return None;
+    }
+    // Normal equations [suu suv; suv svv] [p; q] = [su*; sv*] per axis
+    let a = (sux * svv - svx * suv) / det;
+    let b = (svx * suu - sux * suv) / det;
+    let d = (suy * svv - svy * suv) / det;
+    let e = (svy * suu - suy * suv) / det;
+    Some([a, b, d, e, mx - a * mu - b * mv, my - d * mu - e * mv])
+}
+
+/// PROJ string for a MapInfo `CoordSys Earth Projection ...` clause, for the
+/// common cases: longitude/latitude (1), Lambert conformal conic (3),
+/// Mercator (10) and transverse Mercator (8) on the WGS84 (104), NAD83 (74)
+/// or NAD27 (62) datums. None for anything else, including `NonEarth`.
+/// MapInfo gives the false easting/northing in the clause's units, PROJ
+/// always takes `+x_0`/`+y_0` in meters, so they are converted.
+pub fn mapinfo_coordsys_to_proj(clause: &str) -> Option<String> {
+    let lower = clause.to_ascii_lowercase();
+    let start = lower.find("projection")? + "projection".len();
+    if !lower.trim_start().starts_with("coordsys earth") {
+        return None;
+    }
+    // Drop trailing `Bounds (...)` / `Affine ...` parts
+    let end = ["bounds", "affine"]
+        .iter()
+        .filter_map(|k| lower[start..].find(k))
+        .min()
+        .map_or(clause.len(), |i| start + i);
+    let fields: Vec<&str> = clause[start..end].split(',').map(|f| f.trim()).collect();
+    let num = |i: usize| fields.get(i).and_then(|f| f.parse::<f64>().ok());
+    let datum = match fields.get(1)?.parse::<u32>().ok()? {
+        104 => "+datum=WGS84",
+        74 => "+datum=NAD83",
+        62 => "+datum=NAD27",
+        _ => return None,
+    };
+    // PROJ unit name and its length in meters
+    let (units, to_m) = match fields
+        .get(2)
+        .map(|u| u.trim_matches('"').to_ascii_lowercase())
+    {
+        Some(u) if u == "m" => ("m", 1.0),
+        Some(u) if u == "km" => ("km", 1000.0),
+        Some(u) if u == "ft" => ("ft", 0.3048),
+        Some(u) if u == "survey ft" => ("us-ft", 1200.0 / 3937.0),
+        _ => ("m", 1.0),
+    };
+    let false_en = |i: usize| num(i).map(|v| v * to_m);
+    let body = match fields.first()?.parse::<u32>().ok()? {
+        1 => "+proj=longlat".to_string(),
+        3 => format!(
+            "+proj=lcc +lon_0={} +lat_0={} +lat_1={} +lat_2={} +x_0={} +y_0={} +units={}",
+            num(3)?,
+            num(4)?,
+            num(5)?,
+            num(6)?,
+            false_en(7)?,
+            false_en(8)?,
+            units
+        ),
+        8 => format!(
+            "+proj=tmerc +lon_0={} +lat_0={} +k={} +x_0={} +y_0={} +units={}",
+            num(3)?,
+            num(4)?,
+            num(5)?,
+            false_en(6)?,
+            false_en(7)?,
+            units
+        ),
+        10 => format!("+proj=merc +lon_0={} +units={}", num(3)?, units),
+        _ => return None,
+    };
+    Some(format!("{} {} +no_defs +type=crs", body, datum))
+}
+
+/// Convert a GDAL GeoTransform to this crate's world-file affine.
+///
+/// GDAL orders the terms [originX, pixelWidth, rowRotation, originY,
+/// colRotation, pixelHeight] with (0, 0) at the outer top-left corner:
+/// X = GT0 + col*GT1 + row*GT2, Y = GT3 + col*GT4 + row*GT5. World files
+/// order them [A, B, D, E, C, F] = [GT1, GT2, GT4, GT5, ...] and put (0, 0)
+/// at the first pixel's center, so C/F are the origin moved half a pixel
+/// along both axes.
+pub fn geotransform_to_world_file(gt: [f64; 6]) -> [f64; 6] {
+    let [x0, a, b, y0, d, e] = gt;
+    [a, b, d, e, x0 + 0.5 * a + 0.5 * b, y0 + 0.5 * d + 0.5 * e]
+}
+
+/// Text of the first `<tag ...>...</tag>` element (attributes ignored).
+fn xml_element_text<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}", tag);
+    let mut from = 0;
+    while let Some(i) = xml[from..].find(&open) {
+        let start = from + i + open.len();
+        // Skip longer tag names sharing the prefix (e.g. `<SRSName>`)
+        match xml[start..].chars().next() {
+            Some('>') | Some(' ') | Some('\t') | Some('\n') | Some('\r') => {
+                let body = start + xml[start..].find('>')? + 1;
+                let end = body + xml[body..].find(&format!("</{}>", tag))?;
+                return Some(&xml[body..end]);
+            }
+            _ => from = start,
+        }
+    }
+    None
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
 /// Try common world-file sidecar names for a given raster path.
 /// Returns Ok(Some([a,b,d,e,c,f])) when a usable world file is found.
+/// Errors when sidecars parse but none passes `validate_world_affine`.
 pub fn read_world_file_for_image(image_path: &str) -> Result<Option<[f64; 6]>> {
+    Ok(find_world_file_for_image(image_path)?.map(|(aff, _)| aff))
+}
+
+/// First sidecar in `world_file_extensions` order that parses and passes
+/// `validate_world_affine`, with its path. Invalid candidates are skipped; the
+/// first one's error is returned only when no candidate is usable.
+fn find_world_file_for_image(image_path: &str) -> Result<Option<([f64; 6], std::path::PathBuf)>> {
     use std::fs::read_to_string;
     let path = Path::new(image_path);
     let stem = path.with_extension("");
@@ -55,46 +458,85 @@ pub fn read_world_file_for_image(image_path: &str) -> Result<Option<[f64; 6]>> {
         .and_then(|e| e.to_str())
         .map(|s| s.to_ascii_lowercase())
         .unwrap_or_default();
-    let mut candidates: Vec<String> = Vec::new();
-    // Extension-specific world file mappings
-    match ext.as_str() {
-        "tif" | "tiff" => candidates.push("tfw".into()),
-        "jpg" | "jpeg" => {
-            candidates.push("jgw".into());
-            candidates.push("j2w".into());
-        }
-        "png" => candidates.push("pgw".into()),
-        "gif" => candidates.push("gfw".into()),
-        "bmp" => candidates.push("bpw".into()),
-        _ => {}
-    }
-    candidates.push("wld".into()); // generic fallback
-    for wext in candidates {
+    let mut first_error = None;
+    for wext in world_file_extensions(&ext) {
         let mut cand = stem.clone();
-        cand.set_extension(&wext);
+        cand.set_extension(wext);
         if let Ok(s) = read_to_string(&cand) {
-            let mut vals = [0.0f64; 6];
-            let mut ok = true;
-            for (i, line) in s.lines().enumerate().take(6) {
-                match line.trim().parse::<f64>() {
-                    Ok(v) => vals[i] = v,
-                    Err(_) => {
-                        ok = false;
-                        break;
+            // If parse or validation fails, try next candidate instead of
+            // erroring out
+            if let Ok(vals) = parse_world_values(&s) {
+                match validate_world_affine(vals) {
+                    Ok(()) => return Ok(Some((vals, cand))),
+                    Err(e) => {
+                        first_error
+                            .get_or_insert_with(|| anyhow::anyhow!("{}: {}", cand.display(), e));
                     }
                 }
             }
-            if ok {
-                return Ok(Some(vals));
-            }
-            // If parse fails, try next candidate instead of erroring out
         }
     }
-    Ok(None)
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(None),
+    }
+}
+
+/// World-file sidecar extensions for a raster extension (lowercase), most
+/// conventional first. Always ends with the generic `wld` fallback.
+fn world_file_extensions(image_ext: &str) -> Vec<&'static str> {
+    let mut candidates = Vec::new();
+    // Three-letter form first, then the "extension + w" and other aliases
+    // some tools write instead
+    let names: &[&'static str] = match image_ext {
+        "tif" | "tiff" => &["tfw", "tifw", "tiffw", "twf"],
+        "jpg" | "jpeg" => &["jgw", "j2w", "jpgw", "jpegw"],
+        "png" => &["pgw", "pngw"],
+        "gif" => &["gfw", "gifw"],
+        "bmp" => &["bpw", "bmpw"],
+        _ => &[],
+    };
+    candidates.extend_from_slice(names);
+    candidates.push("wld"); // generic fallback
+    candidates
+}
+
+/// Write a world file next to `image_path` using the sidecar extension that
+/// `read_world_file_for_image` looks for first (e.g. `.pgw` for PNG, `.wld`
+/// for unknown formats). Returns the path written.
+pub fn write_world_file_for_image(
+    image_path: &str,
+    affine: [f64; 6],
+) -> Result<std::path::PathBuf> {
+    write_world_file_for_image_with_precision(image_path, affine, None)
+}
+
+/// `write_world_file_for_image` with values rounded to `digits` significant
+/// digits (see `world_file_contents_with_precision`).
+pub fn write_world_file_for_image_with_precision(
+    image_path: &str,
+    affine: [f64; 6],
+    digits: Option<usize>,
+) -> Result<std::path::PathBuf> {
+    let path = Path::new(image_path);
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|s| s.to_ascii_lowercase())
+        .unwrap_or_default();
+    let mut out = path.with_extension("");
+    out.set_extension(world_file_extensions(&ext)[0]);
+    std::fs::write(&out, world_file_contents_with_precision(affine, digits))?;
+    Ok(out)
 }
 
 /// Try to read a sidecar PRJ file (`.prj`) next to the image.
+/// ESRI-flavored WKT is normalized via `normalize_esri_wkt`.
 pub fn read_prj_for_image(image_path: &str) -> Option<String> {
+    find_prj_for_image(image_path).map(|(wkt, _)| wkt)
+}
+
+fn find_prj_for_image(image_path: &str) -> Option<(String, std::path::PathBuf)> {
     use std::fs::read_to_string;
     let base = Path::new(image_path).with_extension("");
     let candidates = ["prj", "PRJ", "Prj", "wkt", "WKT"];
@@ -102,15 +544,80 @@ pub fn read_prj_for_image(image_path: &str) -> Option<String> {
         let mut cand = base.clone();
         cand.set_extension(ext);
         if let Ok(s) = read_to_string(&cand) {
-            return Some(s);
+            if is_esri_wkt(&s) {
+                if let Some(norm) = normalize_esri_wkt(&s) {
+                    return Some((norm, cand));
+                }
+            }
+            return Some((s, cand));
         }
     }
     None
 }
 
+/// Heuristic for ESRI-flavored WKT1 (as written alongside shapefiles): a
+/// WKT1 root with an ESRI `D_`-prefixed datum name and no AUTHORITY nodes.
+pub fn is_esri_wkt(wkt: &str) -> bool {
+    let t = wkt.trim_start();
+    (t.starts_with("PROJCS[") || t.starts_with("GEOGCS["))
+        && t.contains("DATUM[\"D_")
+        && !t.contains("AUTHORITY[")
+}
+
+/// Rewrite ESRI WKT as OGC WKT1 (GDAL flavor) using PROJ's ESRI dialect
+/// support. When PROJ identifies the CRS in the EPSG registry with full
+/// confidence, the registry definition (with its AUTHORITY code) is used.
+/// Returns None if PROJ cannot parse the input.
+pub fn normalize_esri_wkt(wkt: &str) -> Option<String> {
+    use proj_sys::*;
+    use std::ffi::{CStr, CString};
+    let input = CString::new(wkt.trim()).ok()?;
+    let epsg = CString::new("EPSG").ok()?;
+    // SAFETY: every PROJ object created here is destroyed before returning,
+    // and returned strings are copied while their owner is still alive.
+    unsafe {
+        let ctx = proj_context_create();
+        proj_log_level(ctx, PJ_LOG_LEVEL_PJ_LOG_NONE);
+        let crs = proj_create(ctx, input.as_ptr());
+        let mut out = None;
+        if !crs.is_null() {
+            let mut confidence: *mut i32 = std::ptr::null_mut();
+            let matches = proj_identify(ctx, crs, epsg.as_ptr(), std::ptr::null(), &mut confidence);
+            let mut target = crs;
+            let mut identified = std::ptr::null_mut();
+            if !matches.is_null() && proj_list_get_count(matches) > 0 && *confidence == 100 {
+                identified = proj_list_get(ctx, matches, 0);
+                if !identified.is_null() {
+                    target = identified;
+                }
+            }
+            let text = proj_as_wkt(ctx, target, PJ_WKT_TYPE_PJ_WKT1_GDAL, std::ptr::null());
+            if !text.is_null() {
+                out = Some(CStr::from_ptr(text).to_string_lossy().into_owned());
+            }
+            if !identified.is_null() {
+                proj_destroy(identified);
+            }
+            if !matches.is_null() {
+                proj_list_destroy(matches);
+            }
+            if !confidence.is_null() {
+                proj_int_list_destroy(confidence);
+            }
+            proj_destroy(crs);
+        }
+        proj_context_destroy(ctx);
+        out
+    }
+}
+
 /// Attempt to read GeoTIFF georeferencing directly from a TIFF file.
 /// Returns Ok(Some(Georef)) if tags are found and parsed, Ok(None) otherwise.
 pub fn read_geotiff_georeferencing(tiff_path: &str) -> Result<Option<Georef>> {
+    Ok(read_geotiff_detailed(tiff_path)?.map(|(g, _)| g))
+}
+
+fn read_geotiff_detailed(tiff_path: &str) -> Result<Option<(Georef, GeorefSource)>> {
     use tiff::decoder::Decoder;
     use tiff::tags::Tag;
     let mut dec = match Decoder::new(std::fs::File::open(tiff_path)?) {
@@ -118,13 +625,13 @@ pub fn read_geotiff_georeferencing(tiff_path: &str) -> Result<Option<Georef>> {
         Err(_) => return Ok(None),
     };
     // Read tags we care about: ModelTransformation (34264), ModelPixelScale (33550), ModelTiepoint (33922)
-    // tiff crate exposes GeoTIFF tags as Tag variants in newer versions; fall back to numeric if missing.
+    // tiff 0.9 decodes these numbers as named Tag variants, so `Tag::Unknown(n)` never matches them.
     fn read_f64_vec(dec: &mut Decoder<std::fs::File>, tag: Tag) -> Option<Vec<f64>> {
         // Try best-effort: not all tiff versions expose this; ignore errors.
         dec.get_tag_f64_vec(tag).ok()
     }
     // Try transformation first
-    let transform = read_f64_vec(&mut dec, Tag::Unknown(34264));
+    let transform = read_f64_vec(&mut dec, Tag::ModelTransformationTag);
     if let Some(m) = transform {
         if m.len() == 16 {
             // Row-major 4x4
@@ -135,14 +642,15 @@ pub fn read_geotiff_georeferencing(tiff_path: &str) -> Result<Option<Georef>> {
             let c = m[3];
             let f = m[7];
             let wkt = geotiff_epsg(&mut dec);
-            return Ok(Some(Georef {
+            let geo = Georef {
                 affine: [a, b, d, e, c, f],
                 wkt,
-            }));
+            };
+            return Ok(Some((geo, GeorefSource::GeoTiffTransform)));
         }
     }
-    let scale = read_f64_vec(&mut dec, Tag::Unknown(33550));
-    let tie = read_f64_vec(&mut dec, Tag::Unknown(33922));
+    let scale = read_f64_vec(&mut dec, Tag::ModelPixelScaleTag);
+    let tie = read_f64_vec(&mut dec, Tag::ModelTiepointTag);
     if let (Some(scale), Some(tie)) = (scale, tie) {
         if scale.len() >= 2 && tie.len() >= 6 {
             let sx = scale[0];
@@ -172,21 +680,247 @@ pub fn read_geotiff_georeferencing(tiff_path: &str) -> Result<Option<Georef>> {
                 }
             }
             let wkt = geotiff_epsg(&mut dec);
-            return Ok(Some(Georef {
+            let geo = Georef {
                 affine: [a, b, d, e, c, f],
                 wkt,
-            }));
+            };
+            return Ok(Some((geo, GeorefSource::GeoTiffTiepoint)));
         }
     }
     Ok(None)
 }
 
+/// Write `image` as an RGBA GeoTIFF whose georeferencing is embedded as a
+/// full ModelTransformation (34264) matrix, so rotation terms (B, D) survive
+/// in tools that ignore world files and can't express rotation with
+/// ModelPixelScale + ModelTiepoint. `affine` is world-file order
+/// [A,B,D,E,C,F] (pixel centers), so the raster is tagged PixelIsPoint. When
+/// `crs` resolves to an EPSG code (see `crs_epsg_code`) it is written to the
+/// GeoKeys; a CRS PROJ cannot identify is left out.
+pub fn write_geotiff(
+    out_path: &str,
+    image: &image::RgbaImage,
+    affine: [f64; 6],
+    crs: Option<&str>,
+) -> Result<()> {
+    use tiff::encoder::{colortype, TiffEncoder};
+    use tiff::tags::Tag;
+    let [a, b, d, e, c, f] = affine;
+    // Row-major 4x4: X = A*i + B*j + C, Y = D*i + E*j + F, Z passes through
+    let transform = [
+        a, b, 0.0, c, d, e, 0.0, f, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+    ];
+    // GeoKeyDirectory: header (version 1.1.0, key count), then sorted
+    // (key, location, count, value) entries
+    let mut keys: Vec<u16> = vec![1, 1, 0, 0];
+    let epsg = crs
+        .and_then(crs_epsg_code)
+        .and_then(|code| u16::try_from(code).ok())
+        .map(|code| (code, crs_is_geographic(&format!("EPSG:{}", code))));
+    if let Some((_, geographic)) = epsg {
+        // GTModelTypeGeoKey (1024): 1 projected, 2 geographic
+        keys.extend([1024, 0, 1, if geographic { 2 } else { 1 }]);
+    }
+    // GTRasterTypeGeoKey (1025): 2 = PixelIsPoint
+    keys.extend([1025, 0, 1, 2]);
+    if let Some((code, geographic)) = epsg {
+        // GeographicTypeGeoKey (2048) or ProjectedCSTypeGeoKey (3072)
+        keys.extend([if geographic { 2048 } else { 3072 }, 0, 1, code]);
+    }
+    keys[3] = ((keys.len() - 4) / 4) as u16;
+
+    let mut enc = TiffEncoder::new(std::fs::File::create(out_path)?)?;
+    let mut img = enc.new_image::<colortype::RGBA8>(image.width(), image.height())?;
+    img.encoder()
+        .write_tag(Tag::ModelTransformationTag, &transform[..])?;
+    img.encoder()
+        .write_tag(Tag::GeoKeyDirectoryTag, &keys[..])?;
+    img.write_data(image.as_raw())?;
+    Ok(())
+}
+
+/// Copy the image at `src_path` into a GeoTIFF at `out_path` with `affine`
+/// and `crs` embedded (see `write_geotiff`).
+pub fn embed_geotiff(
+    src_path: &str,
+    out_path: &str,
+    affine: [f64; 6],
+    crs: Option<&str>,
+) -> Result<()> {
+    let image = image::open(src_path)?.to_rgba8();
+    write_geotiff(out_path, &image, affine, crs)
+}
+
+/// Order of the first two axes a CRS declares: `EastNorth` (x/lon first) or
+/// `NorthEast` (y/lat first, e.g. EPSG:4326 and many other authority
+/// definitions). Every coordinate this crate and the app produce or accept is
+/// normalized to east, north (lon, lat) regardless; this only reports what
+/// the definition itself says.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AxisOrder {
+    EastNorth,
+    NorthEast,
+}
+
+/// Axis order declared by `crs` (EPSG code, PROJ string or WKT), read from
+/// the direction of its first coordinate-system axis. Bound CRSs are resolved
+/// to their source and compound CRSs to their horizontal part. None when PROJ
+/// cannot parse the CRS or the first axis is neither east/west nor
+/// north/south.
+pub fn crs_axis_order(crs: &str) -> Option<AxisOrder> {
+    use proj_sys::*;
+    let input = std::ffi::CString::new(crs).ok()?;
+    // SAFETY: every PJ object and the context are destroyed before returning;
+    // the direction string is owned by `cs` and copied out while it lives.
+    unsafe {
+        let ctx = proj_context_create();
+        proj_log_level(ctx, PJ_LOG_LEVEL_PJ_LOG_NONE);
+        let mut objs = vec![proj_create(ctx, input.as_ptr())];
+        let mut horizontal = objs[0];
+        if !horizontal.is_null() {
+            let inner = match proj_get_type(horizontal) {
+                PJ_TYPE_PJ_TYPE_BOUND_CRS => proj_get_source_crs(ctx, horizontal),
+                PJ_TYPE_PJ_TYPE_COMPOUND_CRS => proj_crs_get_sub_crs(ctx, horizontal, 0),
+                _ => std::ptr::null_mut(),
+            };
+            if !inner.is_null() {
+                objs.push(inner);
+                horizontal = inner;
+            }
+        }
+        let cs = if horizontal.is_null() {
+            std::ptr::null_mut()
+        } else {
+            proj_crs_get_coordinate_system(ctx, horizontal)
+        };
+        let mut order = None;
+        if !cs.is_null() {
+            objs.push(cs);
+            let mut direction: *const std::os::raw::c_char = std::ptr::null();
+            let ok = proj_cs_get_axis_info(
+                ctx,
+                cs,
+                0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &mut direction,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            );
+            if ok != 0 && !direction.is_null() {
+                let dir = std::ffi::CStr::from_ptr(direction)
+                    .to_string_lossy()
+                    .to_ascii_lowercase();
+                order = match dir.as_str() {
+                    "east" | "west" => Some(AxisOrder::EastNorth),
+                    "north" | "south" => Some(AxisOrder::NorthEast),
+                    _ => None,
+                };
+            }
+        }
+        for obj in objs.into_iter().filter(|o| !o.is_null()) {
+            proj_destroy(obj);
+        }
+        proj_context_destroy(ctx);
+        order
+    }
+}
+
+/// Stated accuracy in meters of the best transformation PROJ can run from
+/// `from_wkt` to `to_wkt` (EPSG codes, PROJ strings or WKT): the first of
+/// its ranked candidate operations over the CRSs' whole area of use whose
+/// grids are available, so a regional shift may do better locally. A datum
+/// shift without a grid is typically good to 1-10 m, which bounds the
+/// ground accuracy any fit can reach once reprojected; pure conversions
+/// (same datum) report 0. None when either CRS does not parse, no
+/// operation exists, or the best one has no known accuracy.
+pub fn transformation_accuracy(from_wkt: &str, to_wkt: &str) -> Option<f64> {
+    use proj_sys::*;
+    let from = std::ffi::CString::new(from_wkt).ok()?;
+    let to = std::ffi::CString::new(to_wkt).ok()?;
+    // SAFETY: the list, factory context, CRS objects and context are all
+    // destroyed before returning; the operation is destroyed after use.
+    unsafe {
+        let ctx = proj_context_create();
+        proj_log_level(ctx, PJ_LOG_LEVEL_PJ_LOG_NONE);
+        let src = proj_create(ctx, from.as_ptr());
+        let dst = proj_create(ctx, to.as_ptr());
+        let factory = proj_create_operation_factory_context(ctx, std::ptr::null());
+        let mut accuracy = None;
+        if !src.is_null() && !dst.is_null() && !factory.is_null() {
+            // Same candidate search as proj_create_crs_to_crs
+            proj_operation_factory_context_set_spatial_criterion(
+                ctx,
+                factory,
+                PROJ_SPATIAL_CRITERION_PROJ_SPATIAL_CRITERION_PARTIAL_INTERSECTION,
+            );
+            proj_operation_factory_context_set_grid_availability_use(
+                ctx,
+                factory,
+                PROJ_GRID_AVAILABILITY_USE_PROJ_GRID_AVAILABILITY_DISCARD_OPERATION_IF_MISSING_GRID,
+            );
+            let ops = proj_create_operations(ctx, src, dst, factory);
+            if !ops.is_null() {
+                if proj_list_get_count(ops) > 0 {
+                    let op = proj_list_get(ctx, ops, 0);
+                    if !op.is_null() {
+                        let acc = proj_coordoperation_get_accuracy(ctx, op);
+                        if acc >= 0.0 {
+                            accuracy = Some(acc);
+                        } else if proj_get_type(op) == PJ_TYPE_PJ_TYPE_CONVERSION {
+                            // Map projection on the same datum: exact
+                            accuracy = Some(0.0);
+                        }
+                        proj_destroy(op);
+                    }
+                }
+                proj_list_destroy(ops);
+            }
+        }
+        if !factory.is_null() {
+            proj_operation_factory_context_destroy(factory);
+        }
+        for obj in [src, dst].into_iter().filter(|o| !o.is_null()) {
+            proj_destroy(obj);
+        }
+        proj_context_destroy(ctx);
+        accuracy
+    }
+}
+
+/// Whether PROJ parses `crs` as a geographic (lon/lat) CRS.
+pub fn crs_is_geographic(crs: &str) -> bool {
+    use proj_sys::*;
+    let Ok(input) = std::ffi::CString::new(crs) else {
+        return false;
+    };
+    // SAFETY: the context and CRS object are destroyed before returning.
+    unsafe {
+        let ctx = proj_context_create();
+        proj_log_level(ctx, PJ_LOG_LEVEL_PJ_LOG_NONE);
+        let obj = proj_create(ctx, input.as_ptr());
+        let geographic = !obj.is_null()
+            && matches!(
+                proj_get_type(obj),
+                PJ_TYPE_PJ_TYPE_GEOGRAPHIC_2D_CRS | PJ_TYPE_PJ_TYPE_GEOGRAPHIC_3D_CRS
+            );
+        if !obj.is_null() {
+            proj_destroy(obj);
+        }
+        proj_context_destroy(ctx);
+        geographic
+    }
+}
+
 /// Extract an EPSG code from GeoTIFF GeoKeyDirectory (34735) and return a CRS identifier
 /// string suitable for PROJ (e.g., "EPSG:32633"). Best-effort; returns None if unavailable.
 fn geotiff_epsg(dec: &mut tiff::decoder::Decoder<std::fs::File>) -> Option<String> {
     use tiff::tags::Tag;
     // GeoKeyDirectoryTag (34735) contains u16 values with a 4-value header and 4-value entries.
-    let dir = dec.get_tag_u16_vec(Tag::Unknown(34735)).ok()?;
+    let dir = dec.get_tag_u16_vec(Tag::GeoKeyDirectoryTag).ok()?;
     if dir.len() < 4 {
         return None;
     }
@@ -214,7 +948,7 @@ fn geotiff_epsg(dec: &mut tiff::decoder::Decoder<std::fs::File>) -> Option<Strin
 /// Extract GTRasterTypeGeoKey (1025). 1 = PixelIsArea (default), 2 = PixelIsPoint
 fn geotiff_raster_type(dec: &mut tiff::decoder::Decoder<std::fs::File>) -> Option<u16> {
     use tiff::tags::Tag;
-    let dir = dec.get_tag_u16_vec(Tag::Unknown(34735)).ok()?;
+    let dir = dec.get_tag_u16_vec(Tag::GeoKeyDirectoryTag).ok()?;
     if dir.len() < 4 {
         return None;
     }
@@ -243,6 +977,52 @@ pub fn pixel_to_world(geo: &Georef, px: [f64; 2]) -> [f64; 2] {
     [x, y]
 }
 
+/// Geodesic area (square meters, on the WGS84 ellipsoid) of a polygon given
+/// in reference pixels. Vertices are mapped through `geo` to WGS84; the ring
+/// is closed implicitly, and a repeated closing vertex is ignored. Winding
+/// does not matter. Ok(None) when `geo` has no CRS.
+pub fn polygon_area_m2(geo: &Georef, polygon_px: &[[f64; 2]]) -> Result<Option<f64>> {
+    let Some(wkt) = &geo.wkt else {
+        return Ok(None);
+    };
+    let mut ring = polygon_px;
+    if ring.len() > 1 && ring.first() == ring.last() {
+        ring = &ring[..ring.len() - 1];
+    }
+    if ring.len() < 3 {
+        return Ok(Some(0.0));
+    }
+    let to_wgs84 = Proj::new_known_crs(wkt, "EPSG:4326", None)?;
+    let geod = geographiclib_rs::Geodesic::wgs84();
+    let mut area =
+        geographiclib_rs::PolygonArea::new(&geod, geographiclib_rs::Winding::CounterClockwise);
+    for px in ring {
+        let world = pixel_to_world(geo, *px);
+        let (lon, lat) = to_wgs84.convert((world[0], world[1]))?;
+        area.add_point(lat, lon);
+    }
+    let (_perimeter, signed, _) = area.compute(true);
+    Ok(Some(signed.abs()))
+}
+
+/// Forward geodesic azimuth (degrees clockwise from true north, in [0, 360))
+/// on the WGS84 ellipsoid from reference pixel `from_px` to `to_px`. Ok(None)
+/// when `geo` has no CRS.
+pub fn pixel_bearing_deg(geo: &Georef, from_px: [f64; 2], to_px: [f64; 2]) -> Result<Option<f64>> {
+    use geographiclib_rs::InverseGeodesic;
+    let Some(wkt) = &geo.wkt else {
+        return Ok(None);
+    };
+    let to_wgs84 = Proj::new_known_crs(wkt, "EPSG:4326", None)?;
+    let from = pixel_to_world(geo, from_px);
+    let to = pixel_to_world(geo, to_px);
+    let (lon1, lat1) = to_wgs84.convert((from[0], from[1]))?;
+    let (lon2, lat2) = to_wgs84.convert((to[0], to[1]))?;
+    let (azi1, _azi2, _a12): (f64, f64, f64) =
+        geographiclib_rs::Geodesic::wgs84().inverse(lat1, lon1, lat2, lon2);
+    Ok(Some(azi1.rem_euclid(360.0)))
+}
+
 /// Convert a reference pixel coordinate to a local meter-plane coordinate
 /// relative to `origin_px`.
 pub fn pixel_to_local_meters(
@@ -256,6 +1036,16 @@ pub fn pixel_to_local_meters(
         Some(w) => w,
         None => return Ok(None),
     };
+    world_to_local_meters(wkt, world, origin_world).map(Some)
+}
+
+/// Convert a world coordinate in `wkt` to a local meter plane (azimuthal
+/// equidistant) centered on `origin_world` (same CRS).
+pub fn world_to_local_meters(
+    wkt: &str,
+    world: [f64; 2],
+    origin_world: [f64; 2],
+) -> Result<[f64; 2]> {
     // Convert world coordinates to WGS84
     let to_wgs84 = Proj::new_known_crs(wkt, "EPSG:4326", None)?;
     let (lon, lat) = to_wgs84.convert((world[0], world[1]))?;
@@ -264,12 +1054,325 @@ pub fn pixel_to_local_meters(
     let aeqd_def = format!("+proj=aeqd +lat_0={} +lon_0={}", origin_lat, origin_lon);
     let to_local = Proj::new_known_crs("EPSG:4326", &aeqd_def, None)?;
     let (x, y) = to_local.convert((lon, lat))?;
-    Ok(Some([x, y]))
+    Ok([x, y])
+}
+
+/// UTM zone (1-60) containing longitude `lon` (degrees): zone 1 spans
+/// 180°W to 174°W.
+pub fn utm_zone(lon: f64) -> i32 {
+    ((((lon + 180.0) / 6.0).floor() as i32) + 1).clamp(1, 60)
+}
+
+/// EPSG code of WGS84 UTM `zone`: EPSG:326zz north, EPSG:327zz south, with
+/// the zone zero-padded (zone 5 north is EPSG:32605, not EPSG:3265).
+pub fn utm_epsg(zone: i32, north: bool) -> String {
+    format!("EPSG:32{}{:02}", if north { 6 } else { 7 }, zone)
+}
+
+/// Conversions of reference pixels to world, WGS84, local meter-plane and
+/// UTM coordinates, built once per georef so repeated conversions reuse the
+/// PROJ transformers instead of recreating them per point. Results match
+/// `pixel_to_world`, `pixel_to_local_meters` and friends. Conversions other
+/// than `to_world` return Ok(None) when the georef has no CRS.
+pub struct ReferenceProjector {
+    geo: Georef,
+    local_origin_px: [f64; 2],
+    to_wgs84: Option<Proj>,
+    to_local: std::cell::OnceCell<Proj>,
+    to_utm: std::cell::RefCell<std::collections::HashMap<(i32, bool, bool), Proj>>,
 }
 
-pub fn load_raster(path: &str) -> Result<String> {
+impl ReferenceProjector {
+    /// `ref_wh` is the reference image size; the local meter plane is
+    /// centered on the image center when it is known, else on pixel (0, 0).
+    /// Errors when PROJ rejects the georef's CRS.
+    pub fn new(geo: &Georef, ref_wh: Option<(u32, u32)>) -> Result<Self> {
+        let to_wgs84 = match &geo.wkt {
+            Some(wkt) => Some(Proj::new_known_crs(wkt, "EPSG:4326", None)?),
+            None => None,
+        };
+        let local_origin_px = ref_wh
+            .map(|(w, h)| [w as f64 / 2.0, h as f64 / 2.0])
+            .unwrap_or([0.0, 0.0]);
+        Ok(Self {
+            geo: geo.clone(),
+            local_origin_px,
+            to_wgs84,
+            to_local: std::cell::OnceCell::new(),
+            to_utm: Default::default(),
+        })
+    }
+
+    /// World coordinates (CRS units) of reference pixel `px`.
+    pub fn to_world(&self, px: [f64; 2]) -> [f64; 2] {
+        pixel_to_world(&self.geo, px)
+    }
+
+    /// (lon, lat) in WGS84 of reference pixel `px`.
+    pub fn to_wgs84(&self, px: [f64; 2]) -> Result<Option<[f64; 2]>> {
+        let Some(to_wgs84) = &self.to_wgs84 else {
+            return Ok(None);
+        };
+        let world = self.to_world(px);
+        let (lon, lat) = to_wgs84.convert((world[0], world[1]))?;
+        Ok(Some([lon, lat]))
+    }
+
+    /// Meters east and north of the local origin (see `new`) on an azimuthal
+    /// equidistant plane, as `pixel_to_local_meters`.
+    pub fn to_local_meters(&self, px: [f64; 2]) -> Result<Option<[f64; 2]>> {
+        let Some([lon, lat]) = self.to_wgs84(px)? else {
+            return Ok(None);
+        };
+        let to_local = match self.to_local.get() {
+            Some(p) => p,
+            None => {
+                let [origin_lon, origin_lat] = self
+                    .to_wgs84(self.local_origin_px)?
+                    .expect("CRS checked above");
+                let aeqd_def = format!("+proj=aeqd +lat_0={} +lon_0={}", origin_lat, origin_lon);
+                let p = Proj::new_known_crs("EPSG:4326", &aeqd_def, None)?;
+                self.to_local.get_or_init(|| p)
+            }
+        };
+        let (x, y) = to_local.convert((lon, lat))?;
+        Ok(Some([x, y]))
+    }
+
+    /// UTM easting/northing of reference pixel `px` in the zone containing
+    /// it. `policy` "NAD83_2011" uses a GRS80 north-zone UTM; anything else
+    /// is WGS84 UTM (EPSG:326xx/327xx by hemisphere).
+    pub fn to_utm(&self, px: [f64; 2], policy: &str) -> Result<Option<[f64; 2]>> {
+        let Some([lon, lat]) = self.to_wgs84(px)? else {
+            return Ok(None);
+        };
+        let zone = utm_zone(lon);
+        let nad83 = policy == "NAD83_2011";
+        let north = nad83 || lat >= 0.0;
+        let mut cache = self.to_utm.borrow_mut();
+        let proj = match cache.entry((zone, north, nad83)) {
+            std::collections::hash_map::Entry::Occupied(e) => e.into_mut(),
+            std::collections::hash_map::Entry::Vacant(e) => {
+                let target = if nad83 {
+                    format!(
+                        "+proj=utm +zone={} +ellps=GRS80 +units=m +no_defs +type=crs",
+                        zone
+                    )
+                } else {
+                    utm_epsg(zone, north)
+                };
+                let p = Proj::new_known_crs("EPSG:4326", &target, None)?;
+                e.insert(p)
+            }
+        };
+        let (x, y) = proj.convert((lon, lat))?;
+        Ok(Some([x, y]))
+    }
+}
+
+/// Reproject the `dst_real` coordinates of point pairs from `from_wkt` to
+/// `to_wkt`, leaving pixel `src`/`dst` and other constraints untouched.
+/// `dst_local` is recomputed around `local_origin` (world coordinates in
+/// `to_wkt`, usually the new reference's pixel (0, 0)); when that is None it
+/// is cleared, since coordinates measured around the old reference's origin
+/// would no longer line up with the new one.
+pub fn reproject_constraints(
+    constraints: &[types::ConstraintKind],
+    from_wkt: &str,
+    to_wkt: &str,
+    local_origin: Option<[f64; 2]>,
+) -> Result<Vec<types::ConstraintKind>> {
+    let transform = Proj::new_known_crs(from_wkt, to_wkt, None)?;
+    let mut out = constraints.to_vec();
+    for c in &mut out {
+        if let types::ConstraintKind::PointPair {
+            dst_real: Some(real),
+            dst_local,
+            ..
+        } = c
+        {
+            let (x, y) = transform.convert((real[0], real[1]))?;
+            *real = [x, y];
+            *dst_local = match local_origin {
+                Some(origin) => Some(world_to_local_meters(to_wkt, *real, origin)?),
+                None => None,
+            };
+        }
+    }
+    Ok(out)
+}
+
+/// A raster decoded for display, plus any georeferencing found alongside it.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LoadedRaster {
+    pub data_uri: String,
+    pub width: u32,
+    pub height: u32,
+    /// Pixel-to-world affine [A,B,D,E,C,F] from a world file or GeoTIFF tags;
+    /// non-zero B/D means the image is rotated or sheared
+    pub affine: Option<[f64; 6]>,
+    /// EXIF Orientation (2-8) when the file asks viewers to flip/rotate it.
+    /// It is deliberately not applied: `data_uri`, `width`/`height` and all
+    /// pixel coordinates are in stored-pixel space, so the UI can warn that
+    /// other viewers show the image turned.
+    #[serde(default)]
+    pub exif_orientation: Option<u16>,
+    /// Why `affine` is None although a georeferencing source was found (e.g.
+    /// a malformed world file), or which broken sources were skipped for
+    /// the one used; the image itself still loads
+    #[serde(default)]
+    pub georef_warning: Option<String>,
+}
+
+/// Sample depth of the PNGs built for display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreviewDepth {
+    /// 8 bits per channel; 16-bit sources keep their top 8 bits
+    #[default]
+    Eight,
+    /// Keep 16 bits per channel when the source has them, so the frontend
+    /// can window DEM-style data itself; 8-bit sources stay 8-bit
+    Sixteen,
+}
+
+fn is_16bit(img: &image::DynamicImage) -> bool {
+    use image::ColorType::*;
+    matches!(img.color(), L16 | La16 | Rgb16 | Rgba16)
+}
+
+/// `img` as it should be encoded for `depth`.
+fn at_depth(img: image::DynamicImage, depth: PreviewDepth) -> image::DynamicImage {
+    if depth == PreviewDepth::Eight && is_16bit(&img) {
+        image::DynamicImage::ImageRgba8(img.to_rgba8())
+    } else {
+        img
+    }
+}
+
+pub fn load_raster(path: &str) -> Result<LoadedRaster> {
+    load_raster_with_depth(path, PreviewDepth::Eight)
+}
+
+/// `load_raster` with the `data_uri` encoded at `depth`.
+pub fn load_raster_with_depth(path: &str, depth: PreviewDepth) -> Result<LoadedRaster> {
     // Load raster and return as PNG data URI for UI display
     let img = image::open(path)?;
+    // Georeferencing is best-effort here: a broken sidecar must not keep
+    // the image from being displayed
+    let (affine, georef_warning) = match read_georeferencing_detailed(path) {
+        Ok(Some(d)) if d.warnings.is_empty() => (Some(d.georef.affine), None),
+        Ok(Some(d)) => (
+            Some(d.georef.affine),
+            Some(format!("skipped: {}", d.warnings.join("; "))),
+        ),
+        Ok(None) => (None, None),
+        Err(e) => (None, Some(format!("georeferencing ignored: {}", e))),
+    };
+    let (width, height) = (img.width(), img.height());
+    Ok(LoadedRaster {
+        data_uri: png_data_uri(&at_depth(img, depth))?,
+        width,
+        height,
+        affine,
+        exif_orientation: exif_orientation(path)?,
+        georef_warning,
+    })
+}
+
+/// Orientation tag (0x0112) of a JPEG's Exif block or a TIFF's IFD0, when it
+/// is present and not 1 (the stored layout). Other formats and files without
+/// the tag give None. Only the header, marker segments and IFD0 are read, so
+/// multi-GB rasters cost a few small reads. `image` does not apply this tag,
+/// and neither does anything in this crate: coordinates always refer to
+/// stored pixels.
+pub fn exif_orientation(path: &str) -> Result<Option<u16>> {
+    use std::io::{Read, Seek, SeekFrom};
+    let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut magic = [0u8; 4];
+    if file.read_exact(&mut magic).is_err() {
+        return Ok(None);
+    }
+    let orientation = if magic.starts_with(&[0xFF, 0xD8]) {
+        file.seek(SeekFrom::Start(2))?;
+        jpeg_exif_orientation(&mut file)
+    } else if &magic == b"II*\0" || &magic == b"MM\0*" {
+        tiff_ifd0_orientation(&mut file, 0)
+    } else {
+        None
+    };
+    Ok(orientation.filter(|&o| (2..=8).contains(&o)))
+}
+
+/// Walk the JPEG marker segments (reader just past SOI) up to the image
+/// data looking for an APP1 Exif block, then read Orientation from its IFD0.
+/// Other segments are skipped with seeks.
+fn jpeg_exif_orientation<R: std::io::Read + std::io::Seek>(r: &mut R) -> Option<u16> {
+    use std::io::SeekFrom;
+    loop {
+        let mut head = [0u8; 4];
+        r.read_exact(&mut head).ok()?;
+        // Start of scan: no metadata after this point
+        if head[0] != 0xFF || head[1] == 0xDA {
+            return None;
+        }
+        let len = u16::from_be_bytes([head[2], head[3]]) as i64;
+        if len < 2 {
+            return None;
+        }
+        if head[1] == 0xE1 && len >= 8 {
+            let mut id = [0u8; 6];
+            r.read_exact(&mut id).ok()?;
+            if &id == b"Exif\0\0" {
+                let base = r.stream_position().ok()?;
+                return tiff_ifd0_orientation(r, base);
+            }
+            r.seek(SeekFrom::Current(len - 8)).ok()?;
+        } else {
+            r.seek(SeekFrom::Current(len - 2)).ok()?;
+        }
+    }
+}
+
+/// Orientation from the IFD0 of the TIFF structure starting at `base`.
+fn tiff_ifd0_orientation<R: std::io::Read + std::io::Seek>(r: &mut R, base: u64) -> Option<u16> {
+    use std::io::SeekFrom;
+    let mut header = [0u8; 8];
+    r.seek(SeekFrom::Start(base)).ok()?;
+    r.read_exact(&mut header).ok()?;
+    let little = match &header[..2] {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let u16_of = |b: [u8; 2]| match little {
+        true => u16::from_le_bytes(b),
+        false => u16::from_be_bytes(b),
+    };
+    let b = [header[4], header[5], header[6], header[7]];
+    let ifd = match little {
+        true => u32::from_le_bytes(b),
+        false => u32::from_be_bytes(b),
+    };
+    let mut count = [0u8; 2];
+    r.seek(SeekFrom::Start(base + ifd as u64)).ok()?;
+    r.read_exact(&mut count).ok()?;
+    for _ in 0..u16_of(count) {
+        let mut entry = [0u8; 12];
+        r.read_exact(&mut entry).ok()?;
+        if u16_of([entry[0], entry[1]]) == 0x0112 {
+            // SHORT value, left-justified in the 4-byte value field
+            return Some(u16_of([entry[8], entry[9]]));
+        }
+    }
+    None
+}
+
+/// Only the PNG data URI of `load_raster`.
+pub fn load_raster_data_uri(path: &str) -> Result<String> {
+    Ok(load_raster(path)?.data_uri)
+}
+
+fn png_data_uri(img: &image::DynamicImage) -> Result<String> {
     let mut buffer = Cursor::new(Vec::new());
     img.write_to(&mut buffer, ImageFormat::Png)?;
     let data_uri = format!(
@@ -279,11 +1382,247 @@ pub fn load_raster(path: &str) -> Result<String> {
     Ok(data_uri)
 }
 
+/// Load a downsampled PNG data URI whose longest edge is at most `max_dim`.
+/// TIFFs are decoded one strip/tile at a time, skipping chunks that hold no
+/// sampled rows, so multi-gigabyte rasters never sit in memory at full size.
+/// Preview pixels are decimated by an integer step: preview pixel (i, j) is
+/// source pixel (i*step, j*step), with step = ceil(longest edge / max_dim).
+/// When a GDAL `<path>.ovr` sidecar has a level at least `max_dim` across,
+/// the smallest such level is decimated instead, so the step is taken over
+/// the overview's pixels.
+pub fn load_raster_preview(path: &str, max_dim: u32) -> Result<String> {
+    load_raster_preview_with_depth(path, max_dim, PreviewDepth::Eight)
+}
+
+/// `load_raster_preview` encoded at `depth`; decimation is unchanged.
+pub fn load_raster_preview_with_depth(
+    path: &str,
+    max_dim: u32,
+    depth: PreviewDepth,
+) -> Result<String> {
+    // A corrupt or unreadable overview must not cost the preview: the base
+    // image is decimated instead
+    if let Ok(Some(img)) = overview_preview(path, max_dim, depth) {
+        return png_data_uri(&img);
+    }
+    if is_tiff(path) {
+        if let Some(img) = decimated_tiff(path, max_dim, depth)? {
+            return png_data_uri(&img);
+        }
+    }
+    let img = image::open(path)?;
+    let step = decimation_step(img.width(), img.height(), max_dim);
+    if step == 1 {
+        return png_data_uri(&at_depth(img, depth));
+    }
+    let (w, h) = (img.width().div_ceil(step), img.height().div_ceil(step));
+    let out = if depth == PreviewDepth::Sixteen && is_16bit(&img) {
+        let src = img.to_rgba16();
+        image::DynamicImage::ImageRgba16(image::ImageBuffer::from_fn(w, h, |x, y| {
+            *src.get_pixel(x * step, y * step)
+        }))
+    } else {
+        let src = img.to_rgba8();
+        image::DynamicImage::ImageRgba8(image::RgbaImage::from_fn(w, h, |x, y| {
+            *src.get_pixel(x * step, y * step)
+        }))
+    };
+    png_data_uri(&out)
+}
+
+fn is_tiff(path: &str) -> bool {
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|s| s.to_ascii_lowercase())
+        .unwrap_or_default();
+    ext == "tif" || ext == "tiff"
+}
+
+fn decimation_step(width: u32, height: u32, max_dim: u32) -> u32 {
+    width.max(height).div_ceil(max_dim.max(1)).max(1)
+}
+
+/// Preview from a GDAL external overview (`<image>.ovr`, as written by
+/// `gdaladdo -ro`), so the full-resolution image is never decoded. Uses the
+/// smallest overview level whose longest edge still reaches `max_dim`,
+/// decimated to fit like the base image would be; its pixels span the whole
+/// image, so the preview covers the same extent at a coarser sampling. None
+/// when there is no sidecar, no level is that large (the base image is then
+/// needed for the detail) or the level's layout is unsupported; errors when
+/// the sidecar cannot be decoded. Callers fall back to the base image in
+/// every case but Some.
+fn overview_preview(
+    path: &str,
+    max_dim: u32,
+    depth: PreviewDepth,
+) -> Result<Option<image::DynamicImage>> {
+    use tiff::tags::Tag;
+    let ovr = std::path::PathBuf::from(format!("{}.ovr", path));
+    if !ovr.is_file() {
+        return Ok(None);
+    }
+    let mut dec = tiff::decoder::Decoder::new(std::fs::File::open(&ovr)?)?;
+    let mut best: Option<(usize, u32)> = None;
+    let mut index = 0;
+    loop {
+        let (w, h) = dec.dimensions()?;
+        // Skip transparency masks (NewSubfileType bit 2)
+        let is_mask = dec
+            .get_tag_u32(Tag::NewSubfileType)
+            .is_ok_and(|t| t & 4 != 0);
+        let longest = w.max(h);
+        if !is_mask && longest >= max_dim && best.is_none_or(|(_, l)| longest < l) {
+            best = Some((index, longest));
+        }
+        if !dec.more_images() {
+            break;
+        }
+        dec.next_image()?;
+        index += 1;
+    }
+    let Some((level, _)) = best else {
+        return Ok(None);
+    };
+    dec.seek_to_image(level)?;
+    decimate_tiff_image(&mut dec, max_dim, depth)
+}
+
+/// Chunk-wise decimating TIFF decode. Returns Ok(None) for layouts this path
+/// does not handle (palette, CMYK, planar, float samples) so callers can fall
+/// back to a full decode. 16-bit samples are kept only for
+/// `PreviewDepth::Sixteen`; otherwise their top 8 bits are used.
+fn decimated_tiff(
+    path: &str,
+    max_dim: u32,
+    depth: PreviewDepth,
+) -> Result<Option<image::DynamicImage>> {
+    let mut dec = tiff::decoder::Decoder::new(std::fs::File::open(path)?)?;
+    decimate_tiff_image(&mut dec, max_dim, depth)
+}
+
+/// `decimated_tiff` for the image `dec` is positioned at.
+fn decimate_tiff_image(
+    dec: &mut tiff::decoder::Decoder<std::fs::File>,
+    max_dim: u32,
+    depth: PreviewDepth,
+) -> Result<Option<image::DynamicImage>> {
+    use tiff::decoder::DecodingResult;
+    use tiff::ColorType;
+    let (width, height) = dec.dimensions()?;
+    let (channels, bits) = match dec.colortype()? {
+        ColorType::Gray(b @ (8 | 16)) => (1, b),
+        ColorType::GrayA(b @ (8 | 16)) => (2, b),
+        ColorType::RGB(b @ (8 | 16)) => (3, b),
+        ColorType::RGBA(b @ (8 | 16)) => (4, b),
+        _ => return Ok(None),
+    };
+    let step = decimation_step(width, height, max_dim);
+    // Sampled at 16 bits; 8-bit samples are widened by ×257 so the final
+    // `>> 8` gives them back exactly
+    let mut out: image::ImageBuffer<image::Rgba<u16>, Vec<u16>> =
+        image::ImageBuffer::new(width.div_ceil(step), height.div_ceil(step));
+    let (chunk_w, chunk_h) = dec.chunk_dimensions();
+    let across = width.div_ceil(chunk_w);
+    let down = height.div_ceil(chunk_h);
+    for chunk in 0..across * down {
+        let x0 = (chunk % across) * chunk_w;
+        let y0 = (chunk / across) * chunk_h;
+        let (data_w, data_h) = dec.chunk_data_dimensions(chunk);
+        // First sampled row/column inside this chunk, if any
+        let first_y = y0.div_ceil(step) * step;
+        let first_x = x0.div_ceil(step) * step;
+        if first_y >= y0 + data_h || first_x >= x0 + data_w {
+            continue;
+        }
+        let samples: Vec<u16> = match dec.read_chunk(chunk)? {
+            DecodingResult::U8(v) => v.into_iter().map(|s| s as u16 * 257).collect(),
+            DecodingResult::U16(v) => v,
+            _ => return Ok(None),
+        };
+        if samples.len() < (data_w * data_h) as usize * channels {
+            return Ok(None); // planar or otherwise unexpected layout
+        }
+        for y in (first_y..y0 + data_h).step_by(step as usize) {
+            for x in (first_x..x0 + data_w).step_by(step as usize) {
+                let i = (((y - y0) * data_w + (x - x0)) as usize) * channels;
+                let px = &samples[i..i + channels];
+                let rgba = match channels {
+                    1 => [px[0], px[0], px[0], u16::MAX],
+                    2 => [px[0], px[0], px[0], px[1]],
+                    3 => [px[0], px[1], px[2], u16::MAX],
+                    _ => [px[0], px[1], px[2], px[3]],
+                };
+                out.put_pixel(x / step, y / step, image::Rgba(rgba));
+            }
+        }
+    }
+    if depth == PreviewDepth::Sixteen && bits == 16 {
+        return Ok(Some(image::DynamicImage::ImageRgba16(out)));
+    }
+    let (w, h) = out.dimensions();
+    let narrow = out.into_raw().into_iter().map(|s| (s >> 8) as u8).collect();
+    Ok(image::RgbaImage::from_raw(w, h, narrow).map(image::DynamicImage::ImageRgba8))
+}
+
+/// Image (width, height) without decoding pixels. TIFFs are read via their
+/// header so this stays cheap for multi-gigabyte rasters.
 pub fn image_dimensions(path: &str) -> Result<(u32, u32)> {
+    if is_tiff(path) {
+        let mut dec = tiff::decoder::Decoder::new(std::fs::File::open(path)?)?;
+        return Ok(dec.dimensions()?);
+    }
     let img = image::image_dimensions(path)?;
     Ok(img)
 }
 
+/// What the app can do with one input file extension.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FormatInfo {
+    /// Lowercase extension without the dot
+    pub extension: String,
+    pub description: String,
+    /// `load_raster`/`load_raster_preview` can display it
+    pub preview: bool,
+    /// Georeferencing can be read (world-file sidecar, PRJ, GeoTIFF tags)
+    pub georeferencing: bool,
+    /// World file/warp exports can be written for it
+    pub export: bool,
+}
+
+/// Input formats and their support level. Keep in sync with `load_raster`
+/// (image crate decoders), `read_georeferencing_for_image` and the
+/// `load_mbtiles`/`load_cog`/`load_pdf` stubs, which do not load anything yet.
+pub fn supported_formats() -> Vec<FormatInfo> {
+    let f = |ext: &str, desc: &str, supported: bool| FormatInfo {
+        extension: ext.to_string(),
+        description: desc.to_string(),
+        preview: supported,
+        georeferencing: supported,
+        export: supported,
+    };
+    vec![
+        f("png", "PNG (+ .pgw/.wld world file)", true),
+        f("jpg", "JPEG (+ .jgw/.j2w/.wld world file)", true),
+        f("jpeg", "JPEG (+ .jgw/.j2w/.wld world file)", true),
+        f(
+            "tif",
+            "TIFF/GeoTIFF (+ .tfw/.wld or embedded tags); COGs are read as plain TIFF",
+            true,
+        ),
+        f(
+            "tiff",
+            "TIFF/GeoTIFF (+ .tfw/.wld or embedded tags); COGs are read as plain TIFF",
+            true,
+        ),
+        f("gif", "GIF (+ .gfw/.wld world file)", true),
+        f("bmp", "BMP (+ .bpw/.wld world file)", true),
+        f("webp", "WebP (+ .wld world file)", true),
+        f("mbtiles", "MBTiles (not yet supported)", false),
+        f("pdf", "PDF/GeoPDF (not yet supported)", false),
+    ]
+}
+
 pub fn load_mbtiles(_path: &str) -> Result<()> {
     // ... implementation ...
     Ok(())
@@ -300,33 +1639,126 @@ pub fn load_pdf(_path: &str) -> Result<()> {
 }
 
 pub fn write_world_file(path_without_ext: &str, affine: [f64; 6]) -> Result<()> {
-    use std::fs::write;
-    use std::path::PathBuf;
-    let mut out = String::new();
-    // ESRI world file convention values per line: A B D E C F
-    out.push_str(&format!(
-        "{}\n{}\n{}\n{}\n{}\n{}\n",
-        affine[0], affine[1], affine[2], affine[3], affine[4], affine[5]
-    ));
-    let mut tfw = PathBuf::from(path_without_ext);
+    write_world_file_with_precision(path_without_ext, affine, None)
+}
+
+/// `write_world_file` with values rounded to `digits` significant digits
+/// (see `world_file_contents_with_precision`).
+pub fn write_world_file_with_precision(
+    path_without_ext: &str,
+    affine: [f64; 6],
+    digits: Option<usize>,
+) -> Result<()> {
+    let mut tfw = std::path::PathBuf::from(path_without_ext);
     tfw.set_extension("tfw");
-    write(tfw, out)?;
+    std::fs::write(tfw, world_file_contents_with_precision(affine, digits))?;
     Ok(())
 }
 
+/// World file text for `affine`: A, B, D, E, C, F, one per line.
+pub fn world_file_contents(affine: [f64; 6]) -> String {
+    world_file_contents_with_precision(affine, None)
+}
+
+/// `world_file_contents` with each value written in fixed-point notation
+/// rounded to `digits` significant digits (at least 1), e.g. 10 for
+/// ArcGIS-like output; digits left of the decimal point are never dropped,
+/// so large offsets keep their integer part. None keeps the shortest text
+/// that round-trips the f64 exactly, which can run to 17 digits.
+pub fn world_file_contents_with_precision(affine: [f64; 6], digits: Option<usize>) -> String {
+    // ESRI world file convention values per line: A B D E C F
+    affine
+        .iter()
+        .map(|&v| match digits {
+            Some(d) if v != 0.0 && v.is_finite() => {
+                let magnitude = v.abs().log10().floor() as i64;
+                let decimals = (d.max(1) as i64 - 1 - magnitude).max(0) as usize;
+                let text = format!("{:.*}", decimals, v);
+                // Rounding up to the next power of ten (9.99996 -> 10.000)
+                // adds a digit on the left, so give one back on the right
+                let carried = text
+                    .parse::<f64>()
+                    .is_ok_and(|r| r.abs() >= 10f64.powi(magnitude as i32 + 1));
+                match carried && decimals > 0 {
+                    true => format!("{:.*}\n", decimals - 1, v),
+                    false => text + "\n",
+                }
+            }
+            _ => format!("{}\n", v),
+        })
+        .collect()
+}
+
 pub fn read_world_file(path_without_ext: &str) -> Result<[f64; 6]> {
     use std::fs::read_to_string;
     use std::path::PathBuf;
     let mut tfw = PathBuf::from(path_without_ext);
     tfw.set_extension("tfw");
-    let s = read_to_string(tfw)?;
+    parse_world_file(&read_to_string(tfw)?)
+}
+
+/// Parse world file text: the first six lines as A, B, D, E, C, F. Text with
+/// fewer than six lines, as a few tools write, is instead read as exactly
+/// six numbers separated by whitespace and/or commas.
+pub fn parse_world_file(text: &str) -> Result<[f64; 6]> {
+    let vals = parse_world_values(text)?;
+    validate_world_affine(vals)?;
+    Ok(vals)
+}
+
+/// The six values of world file text, unvalidated (see `parse_world_file`).
+fn parse_world_values(text: &str) -> Result<[f64; 6]> {
     let mut vals = [0.0f64; 6];
-    for (i, line) in s.lines().enumerate().take(6) {
-        vals[i] = line.trim().parse::<f64>()?;
+    if text.lines().count() >= 6 {
+        for (i, line) in text.lines().enumerate().take(6) {
+            vals[i] = line.trim().parse::<f64>()?;
+        }
+    } else {
+        let tokens: Vec<&str> = text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|t| !t.is_empty())
+            .collect();
+        if tokens.len() != 6 {
+            anyhow::bail!(
+                "world file needs six values (one per line, or separated by spaces or commas); found {}",
+                tokens.len()
+            );
+        }
+        for (v, t) in vals.iter_mut().zip(tokens) {
+            *v = t.parse::<f64>()?;
+        }
     }
     Ok(vals)
 }
 
+/// Check that a world-file affine [A,B,D,E,C,F] is usable: every value
+/// finite, both pixel axes (A, D) and (B, E) of nonzero length, and the
+/// linear part non-singular (axes not parallel), so `pixel_to_world` is
+/// invertible and per-pixel sizes are never zero. Readers apply this so a
+/// malformed sidecar fails on read rather than as NaNs downstream.
+pub fn validate_world_affine(affine: [f64; 6]) -> Result<()> {
+    let [a, b, d, e, _, _] = affine;
+    if affine.iter().any(|v| !v.is_finite()) {
+        anyhow::bail!("world file affine has non-finite values: {:?}", affine);
+    }
+    let (col_x, col_y) = (a.hypot(d), b.hypot(e));
+    if col_x == 0.0 || col_y == 0.0 {
+        anyhow::bail!(
+            "world file has zero pixel size (A={}, B={}, D={}, E={})",
+            a,
+            b,
+            d,
+            e
+        );
+    }
+    // |det| is the pixel area; relative to the axis lengths it is the sine
+    // of the angle between the pixel axes
+    if (a * e - b * d).abs() <= 1e-12 * col_x * col_y {
+        anyhow::bail!("world file affine is singular (pixel axes are parallel)");
+    }
+    Ok(())
+}
+
 pub fn write_prj(path_without_ext: &str, wkt: &str) -> Result<()> {
     use std::fs::write;
     use std::path::PathBuf;
@@ -335,3 +1767,991 @@ pub fn write_prj(path_without_ext: &str, wkt: &str) -> Result<()> {
     write(prj, wkt.as_bytes())?;
     Ok(())
 }
+
+/// Last-resort PRJ for exports when neither the reference nor the user supplies a CRS.
+pub const DEFAULT_PRJ_WKT: &str = "GEOGCS[\"NAD83(2011)\",DATUM[\"NAD83_National_Spatial_Reference_System_2011\",SPHEROID[\"GRS 1980\",6378137,298.257222101]],PRIMEM[\"Greenwich\",0],UNIT[\"degree\",0.0174532925199433]]";
+
+/// Pick the CRS written to an export's `.prj`: the reference CRS when known,
+/// else the user-configured fallback, else `DEFAULT_PRJ_WKT`. The last case
+/// also returns a warning, since the label is a guess.
+pub fn resolve_export_crs(
+    reference: Option<&str>,
+    fallback: Option<&str>,
+) -> (String, Option<String>) {
+    if let Some(crs) = reference.or(fallback) {
+        return (crs.to_string(), None);
+    }
+    (
+        DEFAULT_PRJ_WKT.to_string(),
+        Some("No reference or fallback CRS set; PRJ labeled NAD83(2011) by default".into()),
+    )
+}
+
+/// Everything a georeferenced export writes, computed up front so callers can
+/// inspect it before anything touches disk.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExportPreview {
+    /// Composed map pixel -> world affine in world-file order [A,B,D,E,C,F]
+    pub affine: [f64; 6],
+    /// Contents of the `.prj` sidecar
+    pub prj_wkt: String,
+    /// World-space bounds of the map image [min_x, min_y, max_x, max_y], when its size is known
+    pub extent: Option<[f64; 4]>,
+    /// Caveats about the export, e.g. a guessed CRS
+    pub warnings: Vec<String>,
+}
+
+/// World-space bounding box [min_x, min_y, max_x, max_y] of a `width`x`height`
+/// image. Pixel centers sit at integer coordinates, so the outer pixel edges
+/// are at -0.5 and `width - 0.5` / `height - 0.5`.
+pub fn image_extent(geo: &Georef, width: u32, height: u32) -> [f64; 4] {
+    let mut ext = [
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    ];
+    for [x, y] in image_corners(geo, width, height) {
+        ext[0] = ext[0].min(x);
+        ext[1] = ext[1].min(y);
+        ext[2] = ext[2].max(x);
+        ext[3] = ext[3].max(y);
+    }
+    ext
+}
+
+/// World coordinates of the outer corners of a `width`x`height` image in
+/// counter-clockwise order starting at the bottom-left pixel corner:
+/// bottom-left, bottom-right, top-right, top-left (the KML `gx:LatLonQuad`
+/// order). Outer pixel edges are at -0.5 and `width - 0.5` / `height - 0.5`.
+pub fn image_corners(geo: &Georef, width: u32, height: u32) -> [[f64; 2]; 4] {
+    let (w, h) = (width as f64 - 0.5, height as f64 - 0.5);
+    [[-0.5, h], [w, h], [w, -0.5], [-0.5, -0.5]].map(|px| pixel_to_world(geo, px))
+}
+
+/// Re-express a world-file affine for rows counted from the bottom of a
+/// `height`-row image instead of the top. World files, GeoTIFF and GDAL put
+/// row 0 at the top (E is negative for north-up rasters); consumers with a
+/// bottom-left raster origin, such as CAD image inserts and OpenGL-style
+/// texture pipelines, expect row 0 at the bottom (E positive). Applying it
+/// twice returns the original affine.
+pub fn flip_affine_rows(affine: [f64; 6], height: u32) -> [f64; 6] {
+    let [a, b, d, e, c, f] = affine;
+    let last = height.saturating_sub(1) as f64;
+    [a, -b, d, -e, c + b * last, f + e * last]
+}
+
+/// Ground discrepancy between two georeferencings of the same image.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GeorefDiff {
+    /// Largest geodesic distance (meters) over the sampled pixels
+    pub max_m: f64,
+    /// Mean geodesic distance (meters) over the sampled pixels
+    pub mean_m: f64,
+}
+
+/// How far apart `a` and `b` place a `width`x`height` image on the ground:
+/// the WGS84 geodesic distance between where each maps the four outer
+/// corners and the center pixel. Each georef is converted from its own CRS,
+/// so candidates in different CRSes compare directly. Errors when either
+/// has no CRS.
+pub fn compare_georefs(a: &Georef, b: &Georef, img_wh: (u32, u32)) -> Result<GeorefDiff> {
+    use geographiclib_rs::InverseGeodesic;
+    let (w, h) = img_wh;
+    let (fw, fh) = (w as f64 - 0.5, h as f64 - 0.5);
+    let pixels = [
+        [-0.5, fh],
+        [fw, fh],
+        [fw, -0.5],
+        [-0.5, -0.5],
+        [0.5 * (w as f64 - 1.0), 0.5 * (h as f64 - 1.0)],
+    ];
+    let lon_lat = |geo: &Georef| -> Result<Vec<(f64, f64)>> {
+        let wkt = geo
+            .wkt
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("georef has no CRS; ground distance is undefined"))?;
+        let to_wgs84 = Proj::new_known_crs(wkt, "EPSG:4326", None)?;
+        pixels
+            .iter()
+            .map(|px| {
+                let world = pixel_to_world(geo, *px);
+                Ok(to_wgs84.convert((world[0], world[1]))?)
+            })
+            .collect()
+    };
+    let (pa, pb) = (lon_lat(a)?, lon_lat(b)?);
+    let geod = geographiclib_rs::Geodesic::wgs84();
+    let dists: Vec<f64> = pa
+        .iter()
+        .zip(&pb)
+        .map(|(&(lon1, lat1), &(lon2, lat2))| geod.inverse(lat1, lon1, lat2, lon2))
+        .collect();
+    Ok(GeorefDiff {
+        max_m: dists.iter().cloned().fold(0.0, f64::max),
+        mean_m: dists.iter().sum::<f64>() / dists.len() as f64,
+    })
+}
+
+/// KML document with one `GroundOverlay` draping `image_href` over `quad`,
+/// given as (lon, lat) in `image_corners` order. A `gx:LatLonQuad` is used
+/// rather than a `LatLonBox` so rotated and sheared overlays stay exact.
+pub fn kml_ground_overlay(name: &str, image_href: &str, quad: &[[f64; 2]; 4]) -> String {
+    let esc = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+    let coords: Vec<String> = quad
+        .iter()
+        .map(|[lon, lat]| format!("{:.10},{:.10}", lon, lat))
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2" xmlns:gx="http://www.google.com/kml/ext/2.2">
+  <GroundOverlay>
+    <name>{}</name>
+    <Icon>
+      <href>{}</href>
+    </Icon>
+    <gx:LatLonQuad>
+      <coordinates>{}</coordinates>
+    </gx:LatLonQuad>
+  </GroundOverlay>
+</kml>
+"#,
+        esc(name),
+        esc(image_href),
+        coords.join(" ")
+    )
+}
+
+/// Write `kml_ground_overlay` for `image_path` to `image_path` with a `.kml`
+/// extension. The image is referenced by file name, so keep the two
+/// together. Returns the path written.
+pub fn write_kml_ground_overlay(
+    image_path: &str,
+    quad: &[[f64; 2]; 4],
+) -> Result<std::path::PathBuf> {
+    let path = Path::new(image_path);
+    let href = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow::anyhow!("invalid image path {}", image_path))?;
+    let name = path.file_stem().and_then(|n| n.to_str()).unwrap_or(href);
+    let out = path.with_extension("kml");
+    std::fs::write(&out, kml_ground_overlay(name, href, quad))?;
+    Ok(out)
+}
+
+/// Write the world file and PRJ described by `preview` next to
+/// `path_without_ext`, all or nothing (see `write_files_atomic`).
+pub fn write_export(path_without_ext: &str, preview: &ExportPreview) -> Result<()> {
+    write_export_with_precision(path_without_ext, preview, None)
+}
+
+/// `write_export` with world file values rounded to `digits` significant
+/// digits (see `world_file_contents_with_precision`).
+pub fn write_export_with_precision(
+    path_without_ext: &str,
+    preview: &ExportPreview,
+    digits: Option<usize>,
+) -> Result<()> {
+    let base = Path::new(path_without_ext);
+    write_files_atomic(&[
+        (
+            base.with_extension("tfw"),
+            world_file_contents_with_precision(preview.affine, digits).into_bytes(),
+        ),
+        (
+            base.with_extension("prj"),
+            preview.prj_wkt.clone().into_bytes(),
+        ),
+    ])
+}
+
+/// Write several files as one unit: each is first written to a hidden temp
+/// file beside its target, and only when every write succeeded are they
+/// renamed into place, so a batch job never sees e.g. a world file without
+/// its PRJ. Existing targets are moved to a hidden `.bak` beside them first;
+/// on any error the targets already renamed by this call are removed and the
+/// backups restored, leaving the previous export as it was. The backups are
+/// deleted once every rename succeeded.
+pub fn write_files_atomic(files: &[(std::path::PathBuf, Vec<u8>)]) -> Result<()> {
+    let hidden = |target: &Path, ext: &str| {
+        let name = target.file_name().map(|n| n.to_string_lossy().into_owned());
+        target.with_file_name(format!(".{}.{}", name.unwrap_or_default(), ext))
+    };
+    let temps: Vec<_> = files
+        .iter()
+        .map(|(target, _)| hidden(target, "tmp"))
+        .collect();
+    let rollback = |renamed: &[std::path::PathBuf],
+                    backups: &[(std::path::PathBuf, std::path::PathBuf)]| {
+        for p in temps.iter().chain(renamed) {
+            let _ = std::fs::remove_file(p);
+        }
+        for (target, backup) in backups {
+            let _ = std::fs::rename(backup, target);
+        }
+    };
+    for ((_, bytes), temp) in files.iter().zip(&temps) {
+        if let Err(e) = std::fs::write(temp, bytes) {
+            rollback(&[], &[]);
+            return Err(anyhow::anyhow!("writing {}: {}", temp.display(), e));
+        }
+    }
+    let mut renamed = Vec::new();
+    let mut backups = Vec::new();
+    for ((target, _), temp) in files.iter().zip(&temps) {
+        if target.is_file() {
+            let backup = hidden(target, "bak");
+            if let Err(e) = std::fs::rename(target, &backup) {
+                rollback(&renamed, &backups);
+                return Err(anyhow::anyhow!("backing up {}: {}", target.display(), e));
+            }
+            backups.push((target.clone(), backup));
+        }
+        if let Err(e) = std::fs::rename(temp, target) {
+            rollback(&renamed, &backups);
+            return Err(anyhow::anyhow!("replacing {}: {}", target.display(), e));
+        }
+        renamed.push(target.clone());
+    }
+    for (_, backup) in &backups {
+        let _ = std::fs::remove_file(backup);
+    }
+    Ok(())
+}
+
+/// A control point row of `write_geopackage`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GpkgControlPoint {
+    pub id: u64,
+    /// Map pixel (pixel centers at integer coordinates)
+    pub map_px: [f64; 2],
+    /// Reference pixel the map pixel was matched to
+    pub ref_px: [f64; 2],
+    /// Ground position in the package CRS; the point geometry
+    pub world: [f64; 2],
+    /// Residual of the fit at this point, if one was made
+    pub residual: Option<f64>,
+}
+
+/// Write a GeoPackage (SQLite, GeoPackage 1.4) at `path` holding the control
+/// points as the point layer `control_points` (attributes `point_id`,
+/// `map_x`, `map_y`, `ref_x`, `ref_y`, `residual`) and `parameters` as
+/// name/value rows of the attribute table `transform_parameters`. `crs`
+/// (EPSG code, PROJ string or WKT) is registered in `gpkg_spatial_ref_sys`
+/// under its EPSG code when PROJ can identify one, else under a custom id;
+/// None records the geometry as in an undefined Cartesian CRS. The package
+/// is built beside `path` and renamed into place, replacing any existing
+/// file only when complete.
+pub fn write_geopackage(
+    path: &str,
+    points: &[GpkgControlPoint],
+    crs: Option<&str>,
+    parameters: &[(String, String)],
+) -> Result<()> {
+    let target = Path::new(path);
+    let name = target.file_name().map(|n| n.to_string_lossy().into_owned());
+    let temp = target.with_file_name(format!(".{}.tmp", name.unwrap_or_default()));
+    let _ = std::fs::remove_file(&temp);
+    let result = fill_geopackage(&temp, points, crs, parameters)
+        .and_then(|()| std::fs::rename(&temp, target).map_err(Into::into));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Custom `srs_id` for a CRS PROJ cannot tie to an EPSG code.
+const GPKG_CUSTOM_SRS_ID: i64 = 100000;
+
+fn fill_geopackage(
+    path: &Path,
+    points: &[GpkgControlPoint],
+    crs: Option<&str>,
+    parameters: &[(String, String)],
+) -> Result<()> {
+    let mut conn = rusqlite::Connection::open(path)?;
+    // 'GPKG' and version 1.4.0
+    conn.execute_batch(
+        "PRAGMA application_id = 1196444487;
+         PRAGMA user_version = 10400;
+         CREATE TABLE gpkg_spatial_ref_sys (
+             srs_name TEXT NOT NULL,
+             srs_id INTEGER PRIMARY KEY,
+             organization TEXT NOT NULL,
+             organization_coordsys_id INTEGER NOT NULL,
+             definition TEXT NOT NULL,
+             description TEXT);
+         CREATE TABLE gpkg_contents (
+             table_name TEXT NOT NULL PRIMARY KEY,
+             data_type TEXT NOT NULL,
+             identifier TEXT UNIQUE,
+             description TEXT DEFAULT '',
+             last_change DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+             min_x DOUBLE, min_y DOUBLE, max_x DOUBLE, max_y DOUBLE,
+             srs_id INTEGER,
+             CONSTRAINT fk_gc_r_srs_id FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys(srs_id));
+         CREATE TABLE gpkg_geometry_columns (
+             table_name TEXT NOT NULL,
+             column_name TEXT NOT NULL,
+             geometry_type_name TEXT NOT NULL,
+             srs_id INTEGER NOT NULL,
+             z TINYINT NOT NULL,
+             m TINYINT NOT NULL,
+             CONSTRAINT pk_geom_cols PRIMARY KEY (table_name, column_name),
+             CONSTRAINT fk_gc_tn FOREIGN KEY (table_name) REFERENCES gpkg_contents(table_name),
+             CONSTRAINT fk_gc_srs FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys(srs_id));
+         INSERT INTO gpkg_spatial_ref_sys VALUES
+             ('WGS 84 geodetic', 4326, 'EPSG', 4326,
+              'GEOGCS[\"WGS 84\",DATUM[\"WGS_1984\",SPHEROID[\"WGS 84\",6378137,298.257223563,AUTHORITY[\"EPSG\",\"7030\"]],AUTHORITY[\"EPSG\",\"6326\"]],PRIMEM[\"Greenwich\",0,AUTHORITY[\"EPSG\",\"8901\"]],UNIT[\"degree\",0.0174532925199433,AUTHORITY[\"EPSG\",\"9122\"]],AUTHORITY[\"EPSG\",\"4326\"]]',
+              'longitude/latitude coordinates in decimal degrees on the WGS 84 spheroid'),
+             ('Undefined cartesian SRS', -1, 'NONE', -1, 'undefined', 'undefined cartesian coordinate reference system'),
+             ('Undefined geographic SRS', 0, 'NONE', 0, 'undefined', 'undefined geographic coordinate reference system');
+         CREATE TABLE control_points (
+             fid INTEGER PRIMARY KEY AUTOINCREMENT,
+             geom POINT,
+             point_id INTEGER NOT NULL,
+             map_x REAL NOT NULL,
+             map_y REAL NOT NULL,
+             ref_x REAL NOT NULL,
+             ref_y REAL NOT NULL,
+             residual REAL);
+         CREATE TABLE transform_parameters (
+             fid INTEGER PRIMARY KEY AUTOINCREMENT,
+             name TEXT NOT NULL,
+             value TEXT NOT NULL);",
+    )?;
+    let tx = conn.transaction()?;
+    let srs_id = match crs {
+        Some(crs) => {
+            let definition = normalize_esri_wkt(crs).unwrap_or_else(|| crs.to_string());
+            let code = crs
+                .trim()
+                .strip_prefix("EPSG:")
+                .map(str::to_string)
+                .or_else(|| wkt_epsg_code(&definition))
+                .and_then(|c| c.parse::<i64>().ok());
+            let (srs_id, org, org_id) = match code {
+                Some(c) => (c, "EPSG", c),
+                None => (GPKG_CUSTOM_SRS_ID, "NONE", GPKG_CUSTOM_SRS_ID),
+            };
+            let srs_name = wkt_name(&definition).unwrap_or_else(|| crs.to_string());
+            tx.execute(
+                "INSERT OR IGNORE INTO gpkg_spatial_ref_sys VALUES (?1, ?2, ?3, ?4, ?5, NULL)",
+                rusqlite::params![srs_name, srs_id, org, org_id, definition],
+            )?;
+            srs_id
+        }
+        None => -1,
+    };
+    let mut extent = [
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    ];
+    for p in points {
+        extent[0] = extent[0].min(p.world[0]);
+        extent[1] = extent[1].min(p.world[1]);
+        extent[2] = extent[2].max(p.world[0]);
+        extent[3] = extent[3].max(p.world[1]);
+        tx.execute(
+            "INSERT INTO control_points (geom, point_id, map_x, map_y, ref_x, ref_y, residual)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                gpkg_point_blob(srs_id, p.world),
+                p.id as i64,
+                p.map_px[0],
+                p.map_px[1],
+                p.ref_px[0],
+                p.ref_px[1],
+                p.residual,
+            ],
+        )?;
+    }
+    let extent = extent.map(|v| v.is_finite().then_some(v));
+    tx.execute(
+        "INSERT INTO gpkg_contents (table_name, data_type, identifier, description, min_x, min_y, max_x, max_y, srs_id)
+         VALUES ('control_points', 'features', 'control_points', 'Control points with fit residuals', ?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![extent[0], extent[1], extent[2], extent[3], srs_id],
+    )?;
+    tx.execute(
+        "INSERT INTO gpkg_geometry_columns VALUES ('control_points', 'geom', 'POINT', ?1, 0, 0)",
+        [srs_id],
+    )?;
+    for (name, value) in parameters {
+        tx.execute(
+            "INSERT INTO transform_parameters (name, value) VALUES (?1, ?2)",
+            [name, value],
+        )?;
+    }
+    tx.execute(
+        "INSERT INTO gpkg_contents (table_name, data_type, identifier, description)
+         VALUES ('transform_parameters', 'attributes', 'transform_parameters', 'Fitted transform and solve summary')",
+        [],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// GeoPackage geometry blob for a point: the "GP" header (version 0, no
+/// envelope, little endian) with `srs_id`, then little-endian WKB.
+fn gpkg_point_blob(srs_id: i64, xy: [f64; 2]) -> Vec<u8> {
+    let mut blob = Vec::with_capacity(29);
+    blob.extend_from_slice(b"GP");
+    blob.push(0);
+    blob.push(0b0000_0001);
+    blob.extend_from_slice(&(srs_id as i32).to_le_bytes());
+    blob.push(1);
+    blob.extend_from_slice(&1u32.to_le_bytes());
+    blob.extend_from_slice(&xy[0].to_le_bytes());
+    blob.extend_from_slice(&xy[1].to_le_bytes());
+    blob
+}
+
+/// Code of the outermost `AUTHORITY["EPSG","<code>"]` in WKT1: the last one,
+/// when it closes the top-level node (not, say, a UNIT's authority in WKT
+/// for an unregistered CRS).
+fn wkt_epsg_code(wkt: &str) -> Option<String> {
+    let start = wkt.rfind("AUTHORITY[\"EPSG\",\"")? + "AUTHORITY[\"EPSG\",\"".len();
+    let len = wkt[start..].find('"')?;
+    let rest: String = wkt[start + len + 1..]
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    (rest == "]]").then(|| wkt[start..start + len].to_string())
+}
+
+/// EPSG code of a CRS given as `EPSG:<code>`, WKT (OGC or ESRI) or a PROJ
+/// string. Anything but an `EPSG:` identifier goes through PROJ
+/// identification (see `normalize_esri_wkt`), which must be fully
+/// confident; None when it is not.
+pub fn crs_epsg_code(crs: &str) -> Option<u32> {
+    let code = match crs.trim().strip_prefix("EPSG:") {
+        Some(code) => code.to_string(),
+        None => wkt_epsg_code(&normalize_esri_wkt(crs)?)?,
+    };
+    code.parse().ok()
+}
+
+/// First quoted name in WKT, e.g. `WGS 84 / UTM zone 10N` of `PROJCS["WGS 84 / UTM zone 10N",...`.
+fn wkt_name(wkt: &str) -> Option<String> {
+    let start = wkt.find('"')? + 1;
+    let len = wkt[start..].find('"')?;
+    Some(wkt[start..start + len].to_string())
+}
+
+/// Which CSV columns (0-based) hold each control-point field.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ColumnMapping {
+    pub src_x: usize,
+    pub src_y: usize,
+    pub dst_x: usize,
+    pub dst_y: usize,
+    /// Optional weight column; rows default to 1.0 when absent
+    pub weight: Option<usize>,
+    /// Skip the first row as a header
+    pub has_header: bool,
+}
+
+/// A control point read from an external file: map pixel `src`, reference
+/// pixel `dst`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ControlPoint {
+    pub src: [f64; 2],
+    pub dst: [f64; 2],
+    pub weight: f64,
+}
+
+/// Read control points from a comma-separated file laid out per `mapping`.
+/// Blank lines are skipped; any other unparsable row fails the whole import
+/// with its 1-based line number so the user can fix the file.
+pub fn read_control_points_csv(path: &str, mapping: &ColumnMapping) -> Result<Vec<ControlPoint>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(mapping.has_header)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(path)?;
+    let mut out = Vec::new();
+    for record in rdr.records() {
+        let record = record?;
+        let line = record.position().map(|p| p.line()).unwrap_or(0);
+        if record.iter().all(|f| f.is_empty()) {
+            continue;
+        }
+        let field = |col: usize, name: &str| -> Result<f64> {
+            let raw = record.get(col).ok_or_else(|| {
+                anyhow::anyhow!("row {}: missing {} (column {})", line, name, col)
+            })?;
+            raw.parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| anyhow::anyhow!("row {}: invalid {} '{}'", line, name, raw))
+        };
+        let weight = match mapping.weight {
+            Some(col) => field(col, "weight")?,
+            None => 1.0,
+        };
+        out.push(ControlPoint {
+            src: [
+                field(mapping.src_x, "src_x")?,
+                field(mapping.src_y, "src_y")?,
+            ],
+            dst: [
+                field(mapping.dst_x, "dst_x")?,
+                field(mapping.dst_y, "dst_y")?,
+            ],
+            weight,
+        });
+    }
+    Ok(out)
+}
+
+/// A ground control point in QGIS georeferencer terms: `map` is the world
+/// coordinate, `pixel` the position in the raster being georeferenced
+/// (pixel-center convention, y down).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GcpPoint {
+    pub map: [f64; 2],
+    pub pixel: [f64; 2],
+    pub enabled: bool,
+}
+
+/// Write a QGIS georeferencer `.points` file. QGIS puts the source origin at
+/// the top-left pixel corner and stores rows as negative y, so pixel centers
+/// are written as `pixelX = col + 0.5`, `pixelY = -(row + 0.5)`. Values
+/// use Rust's shortest round-trip formatting, so nothing is lost. `crs`
+/// (WKT or an authority code) is written as the `#CRS:` line newer QGIS
+/// versions read.
+pub fn write_gcp_points(path: &str, points: &[GcpPoint], crs: Option<&str>) -> Result<()> {
+    let mut out = String::new();
+    if let Some(crs) = crs {
+        let one_line: Vec<&str> = crs.lines().map(|l| l.trim()).collect();
+        out.push_str(&format!("#CRS: {}\n", one_line.concat()));
+    }
+    out.push_str("mapX,mapY,pixelX,pixelY,enable\n");
+    for p in points {
+        out.push_str(&format!(
+            "{},{},{},{},{}\n",
+            p.map[0],
+            p.map[1],
+            p.pixel[0] + 0.5,
+            // Not -(row + 0.5): the top edge must not print as "-0"
+            -0.5 - p.pixel[1],
+            p.enabled as u8
+        ));
+    }
+    std::fs::write(path, out)?;
+    Ok(())
+}
+
+/// Read a QGIS georeferencer `.points` file (QGIS 2 `pixelX/pixelY` or QGIS 3
+/// `sourceX/sourceY` headers). Columns are located by header name, `#` lines
+/// are skipped, and the corner-origin source coordinates are converted back
+/// to pixel-center columns and rows (see `write_gcp_points`). A missing
+/// `enable` column means enabled.
+pub fn read_gcp_points(path: &str) -> Result<Vec<GcpPoint>> {
+    let text = std::fs::read_to_string(path)?;
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'));
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("{}: no header row", path))?;
+    let cols: Vec<&str> = header.split(',').map(|c| c.trim()).collect();
+    let find = |names: &[&str]| cols.iter().position(|c| names.contains(c));
+    let missing = |name: &str| anyhow::anyhow!("{}: missing {} column", path, name);
+    let map_x = find(&["mapX"]).ok_or_else(|| missing("mapX"))?;
+    let map_y = find(&["mapY"]).ok_or_else(|| missing("mapY"))?;
+    let px = find(&["pixelX", "sourceX"]).ok_or_else(|| missing("pixelX"))?;
+    let py = find(&["pixelY", "sourceY"]).ok_or_else(|| missing("pixelY"))?;
+    let enable = find(&["enable"]);
+    let mut out = Vec::new();
+    for (i, line) in lines {
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        let num = |col: usize| -> Result<f64> {
+            fields
+                .get(col)
+                .and_then(|f| f.parse::<f64>().ok())
+                .ok_or_else(|| anyhow::anyhow!("row {}: invalid {}", i + 1, cols[col]))
+        };
+        out.push(GcpPoint {
+            map: [num(map_x)?, num(map_y)?],
+            pixel: [num(px)? - 0.5, -num(py)? - 0.5],
+            enabled: match enable {
+                Some(col) => num(col)? != 0.0,
+                None => true,
+            },
+        });
+    }
+    Ok(out)
+}
+
+/// Read correspondences from an OpenCV calibration JSON with `image_points`
+/// and `object_points`: image points become `src`, object points `dst` (z is
+/// dropped). Each may be a nested list (`[[x, y], ...]`, optionally grouped
+/// per view), a flat list (`[x, y, x, y, ...]`), or a `cv::FileStorage`
+/// matrix (`{"type_id": "opencv-matrix", "rows", "cols", "dt", "data"}`).
+/// Pairs get ids 0..n in file order.
+pub fn read_opencv_correspondences(path: &str) -> Result<Vec<types::ConstraintKind>> {
+    let doc: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let field = |name: &str| {
+        doc.get(name)
+            .ok_or_else(|| anyhow::anyhow!("missing '{}'", name))
+    };
+    let image = opencv_points(field("image_points")?, 2, "image_points")?;
+    // A flat object list is split by the image point count (x,y or x,y,z)
+    let object = opencv_points(
+        field("object_points")?,
+        opencv_flat_dims(field("object_points")?, image.len()),
+        "object_points",
+    )?;
+    if image.len() != object.len() {
+        return Err(anyhow::anyhow!(
+            "{} image points but {} object points",
+            image.len(),
+            object.len()
+        ));
+    }
+    Ok(image
+        .into_iter()
+        .zip(object)
+        .enumerate()
+        .map(|(i, (src, dst))| types::ConstraintKind::PointPair {
+            id: i as u64,
+            src,
+            dst,
+            dst_real: None,
+            dst_local: None,
+            src_z: None,
+            dst_z: None,
+            weight: 1.0,
+        })
+        .collect())
+}
+
+/// Components per point of a flat number list holding `n_points` points.
+fn opencv_flat_dims(v: &serde_json::Value, n_points: usize) -> usize {
+    match v.as_array() {
+        Some(a) if n_points > 0 && a.len() == 3 * n_points && a.iter().all(|x| x.is_number()) => 3,
+        _ => 2,
+    }
+}
+
+/// Collect (x, y) points from any of the layouts `read_opencv_correspondences`
+/// accepts. `flat_dims` is the point size used for a flat number list.
+fn opencv_points(v: &serde_json::Value, flat_dims: usize, name: &str) -> Result<Vec<[f64; 2]>> {
+    let num = |x: &serde_json::Value| {
+        x.as_f64()
+            .ok_or_else(|| anyhow::anyhow!("{}: expected a number, got {}", name, x))
+    };
+    let chunked = |data: &[serde_json::Value], dims: usize| -> Result<Vec<[f64; 2]>> {
+        if !(2..=3).contains(&dims) || data.len() % dims != 0 {
+            return Err(anyhow::anyhow!(
+                "{}: {} values do not split into points of {} components",
+                name,
+                data.len(),
+                dims
+            ));
+        }
+        data.chunks(dims)
+            .map(|c| Ok([num(&c[0])?, num(&c[1])?]))
+            .collect()
+    };
+    if let Some(data) = v.get("data").and_then(|d| d.as_array()) {
+        // cv::Mat: channels from `dt` ("2f", "3d", ...), else one per column
+        let channels = v
+            .get("dt")
+            .and_then(|d| d.as_str())
+            .and_then(|d| {
+                d.trim_end_matches(char::is_alphabetic)
+                    .parse::<usize>()
+                    .ok()
+            })
+            .unwrap_or(1);
+        let cols = v.get("cols").and_then(|c| c.as_u64()).unwrap_or(1) as usize;
+        let dims = if channels > 1 { channels } else { cols };
+        return chunked(data, dims);
+    }
+    let arr = v
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("{}: expected a list or matrix", name))?;
+    if arr.iter().all(|x| x.is_number()) {
+        return chunked(arr, flat_dims);
+    }
+    let mut out = Vec::new();
+    for item in arr {
+        let inner = item
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("{}: expected a point list, got {}", name, item))?;
+        if inner.iter().all(|x| x.is_number()) && (2..=3).contains(&inner.len()) {
+            out.push([num(&inner[0])?, num(&inner[1])?]);
+        } else {
+            // One list per view (or OpenCV's [[x, y]] single-row points)
+            out.extend(opencv_points(item, flat_dims, name)?);
+        }
+    }
+    Ok(out)
+}
+
+/// Output pixel grid options for `warp_raster`.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct WarpOptions {
+    /// Ground units per output pixel (square pixels); wins over `size`
+    pub resolution: Option<f64>,
+    /// Explicit output (width, height); pixel size follows from the extent
+    pub size: Option<(u32, u32)>,
+    /// Snap the grid's outer top-left corner to a multiple of the pixel size
+    /// so outputs line up with other datasets/tiles. May add a row/column.
+    #[serde(default)]
+    pub snap_origin: bool,
+    /// RGBA written where the output falls outside the source image;
+    /// fully transparent when unset
+    #[serde(default)]
+    pub nodata: Option<[u8; 4]>,
+}
+
+/// A north-up output grid: world-file affine [A,B,D,E,C,F] and size.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WarpGrid {
+    pub affine: [f64; 6],
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Largest output `warp_grid` will produce (pixels), to catch unit mix-ups
+/// such as a resolution in degrees for a projected CRS.
+pub const MAX_WARP_PIXELS: u64 = 1 << 30;
+
+/// Compute the north-up grid covering a `src_w`x`src_h` image placed by
+/// `src_affine`. Without a resolution or size the source's native ground
+/// resolution (square root of the pixel area) is kept.
+pub fn warp_grid(
+    src_affine: [f64; 6],
+    src_w: u32,
+    src_h: u32,
+    opts: &WarpOptions,
+) -> Result<WarpGrid> {
+    let ext = image_extent(
+        &Georef {
+            affine: src_affine,
+            wkt: None,
+        },
+        src_w,
+        src_h,
+    );
+    let (ew, eh) = (ext[2] - ext[0], ext[3] - ext[1]);
+    let [a, b, d, e, _, _] = src_affine;
+    let (rx, ry) = match (opts.resolution, opts.size) {
+        (Some(r), _) => (r, r),
+        (None, Some((w, h))) if w > 0 && h > 0 => (ew / w as f64, eh / h as f64),
+        (None, Some(_)) => return Err(anyhow::anyhow!("output size must be non-zero")),
+        (None, None) => {
+            let r = (a * e - b * d).abs().sqrt();
+            (r, r)
+        }
+    };
+    if !(rx.is_finite() && ry.is_finite() && rx > 0.0 && ry > 0.0) {
+        return Err(anyhow::anyhow!("invalid output resolution {} x {}", rx, ry));
+    }
+    let (mut left, mut top) = (ext[0], ext[3]);
+    if opts.snap_origin {
+        left = (left / rx).floor() * rx;
+        top = (top / ry).ceil() * ry;
+    }
+    // Tolerance keeps exact multiples from gaining a spurious extra pixel
+    let cells = |span: f64, res: f64| (span / res - 1e-9).ceil().max(1.0);
+    let (width, height) = match (opts.resolution, opts.size, opts.snap_origin) {
+        (None, Some((w, h)), false) => (w as f64, h as f64),
+        _ => (cells(ext[2] - left, rx), cells(top - ext[1], ry)),
+    };
+    if width * height > MAX_WARP_PIXELS as f64 {
+        return Err(anyhow::anyhow!(
+            "output grid {}x{} exceeds {} pixels; check the resolution units",
+            width,
+            height,
+            MAX_WARP_PIXELS
+        ));
+    }
+    Ok(WarpGrid {
+        affine: [rx, 0.0, 0.0, -ry, left + rx / 2.0, top - ry / 2.0],
+        width: width as u32,
+        height: height as u32,
+    })
+}
+
+/// Inverse of a world-file affine [A,B,D,E,C,F], or None if singular.
+pub fn invert_world_affine(affine: [f64; 6]) -> Option<[f64; 6]> {
+    let [a, b, d, e, c, f] = affine;
+    let det = a * e - b * d;
+    if !det.is_finite() || det.abs() < 1e-300 {
+        return None;
+    }
+    let (ia, ib, id, ie) = (e / det, -b / det, -d / det, a / det);
+    Some([ia, ib, id, ie, -(ia * c + ib * f), -(id * c + ie * f)])
+}
+
+/// Resample `src` (placed by `src_affine`) onto `grid` with nearest-neighbour
+/// sampling. Output pixels whose source location falls outside the image
+/// (beyond the outer pixel edges) are set to `nodata`.
+pub fn warp_image(
+    src: &image::RgbaImage,
+    src_affine: [f64; 6],
+    grid: &WarpGrid,
+    nodata: [u8; 4],
+) -> Result<image::RgbaImage> {
+    warp_window(
+        src,
+        src_affine,
+        grid,
+        nodata,
+        (0, 0),
+        (grid.width, grid.height),
+    )
+}
+
+/// The `size` block of `warp_image`'s output whose top-left output pixel is
+/// `origin`; pixels are sampled exactly as the full warp would.
+fn warp_window(
+    src: &image::RgbaImage,
+    src_affine: [f64; 6],
+    grid: &WarpGrid,
+    nodata: [u8; 4],
+    origin: (u32, u32),
+    size: (u32, u32),
+) -> Result<image::RgbaImage> {
+    let inv = invert_world_affine(src_affine)
+        .ok_or_else(|| anyhow::anyhow!("source transform is not invertible"))?;
+    let inv_geo = Georef {
+        affine: inv,
+        wkt: None,
+    };
+    let out_geo = Georef {
+        affine: grid.affine,
+        wkt: None,
+    };
+    let (w, h) = src.dimensions();
+    if w == 0 || h == 0 {
+        return Err(anyhow::anyhow!("source image is empty"));
+    }
+    Ok(image::RgbaImage::from_fn(size.0, size.1, |i, j| {
+        let (i, j) = (origin.0 + i, origin.1 + j);
+        let world = pixel_to_world(&out_geo, [i as f64, j as f64]);
+        let [u, v] = pixel_to_world(&inv_geo, world);
+        let (u, v) = (u.round(), v.round());
+        if u < 0.0 || v < 0.0 || u >= w as f64 || v >= h as f64 {
+            return image::Rgba(nodata);
+        }
+        *src.get_pixel(u as u32, v as u32)
+    }))
+}
+
+/// NoData fill for `opts`: the requested value, else fully transparent.
+fn warp_nodata(opts: &WarpOptions) -> [u8; 4] {
+    opts.nodata.unwrap_or([0, 0, 0, 0])
+}
+
+/// Warp the image at `src_path` as `warp_raster` would, but at a resolution
+/// whose longest output edge is at most `max_dim`, returned as a PNG data
+/// URI for on-screen preview. `opts.resolution`/`size` are ignored.
+pub fn warp_preview(
+    src_path: &str,
+    src_affine: [f64; 6],
+    max_dim: u32,
+    opts: &WarpOptions,
+) -> Result<String> {
+    let src = image::open(src_path)?.to_rgba8();
+    let native = warp_grid(
+        src_affine,
+        src.width(),
+        src.height(),
+        &WarpOptions::default(),
+    )?;
+    let factor = (native.width.max(native.height) as f64 / max_dim.max(1) as f64).max(1.0);
+    let preview_opts = WarpOptions {
+        resolution: Some(native.affine[0] * factor),
+        size: None,
+        ..opts.clone()
+    };
+    let grid = warp_grid(src_affine, src.width(), src.height(), &preview_opts)?;
+    let out = warp_image(&src, src_affine, &grid, warp_nodata(opts))?;
+    png_data_uri(&image::DynamicImage::ImageRgba8(out))
+}
+
+/// Warp the image at `src_path` onto a north-up grid (see `warp_grid`) and
+/// write it to `out_path` (format from the extension, e.g. `.png`/`.tif`)
+/// with a matching world file. Returns the grid used.
+pub fn warp_raster(
+    src_path: &str,
+    src_affine: [f64; 6],
+    out_path: &str,
+    opts: &WarpOptions,
+) -> Result<WarpGrid> {
+    let src = image::open(src_path)?.to_rgba8();
+    let grid = warp_grid(src_affine, src.width(), src.height(), opts)?;
+    warp_image(&src, src_affine, &grid, warp_nodata(opts))?.save(out_path)?;
+    write_world_file_for_image(out_path, grid.affine)?;
+    Ok(grid)
+}
+
+/// Default block edge for `warp_raster_tiled` (pixels).
+pub const WARP_TILE_SIZE: u32 = 512;
+
+/// `warp_raster` for outputs too large to hold in memory: the grid is warped
+/// in `tile_size`x`tile_size` blocks and written to a striped RGBA TIFF at
+/// `out_path` one band of `tile_size` rows at a time, so only a single band
+/// of output is resident. Pixels match `warp_image` exactly. Writes a world
+/// file alongside and returns the grid used.
+pub fn warp_raster_tiled(
+    src_path: &str,
+    src_affine: [f64; 6],
+    out_path: &str,
+    opts: &WarpOptions,
+    tile_size: u32,
+) -> Result<WarpGrid> {
+    use tiff::encoder::{colortype, TiffEncoder};
+    if tile_size == 0 {
+        return Err(anyhow::anyhow!("tile size must be non-zero"));
+    }
+    let ext = std::path::Path::new(out_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    if !matches!(ext.as_deref(), Some("tif" | "tiff")) {
+        return Err(anyhow::anyhow!(
+            "tiled warp writes TIFF; use a .tif output path, got {}",
+            out_path
+        ));
+    }
+    let src = image::open(src_path)?.to_rgba8();
+    let grid = warp_grid(src_affine, src.width(), src.height(), opts)?;
+    let nodata = warp_nodata(opts);
+    let file = std::io::BufWriter::new(std::fs::File::create(out_path)?);
+    let mut enc = TiffEncoder::new(file)?;
+    let mut img = enc.new_image::<colortype::RGBA8>(grid.width, grid.height)?;
+    img.rows_per_strip(tile_size)?;
+    let row_bytes = grid.width as usize * 4;
+    for y0 in (0..grid.height).step_by(tile_size as usize) {
+        let rows = tile_size.min(grid.height - y0);
+        let mut strip = vec![0u8; row_bytes * rows as usize];
+        for x0 in (0..grid.width).step_by(tile_size as usize) {
+            let cols = tile_size.min(grid.width - x0);
+            let tile = warp_window(&src, src_affine, &grid, nodata, (x0, y0), (cols, rows))?;
+            let tile_row = cols as usize * 4;
+            for (r, line) in tile.as_raw().chunks_exact(tile_row).enumerate() {
+                let at = r * row_bytes + x0 as usize * 4;
+                strip[at..at + tile_row].copy_from_slice(line);
+            }
+        }
+        img.write_strip(&strip)?;
+    }
+    img.finish()?;
+    write_world_file_for_image(out_path, grid.affine)?;
+    Ok(grid)
+}
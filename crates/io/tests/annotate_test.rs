@@ -0,0 +1,76 @@
+mod common;
+
+use common::temp_dir;
+use io::{write_annotated_preview, PreviewMarker};
+use types::ErrorUnit;
+
+fn gray_map(dir: &std::path::Path) -> String {
+    let path = dir.join("map.png");
+    image::RgbaImage::from_pixel(200, 120, image::Rgba([90, 90, 90, 255]))
+        .save(&path)
+        .unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_markers_are_burned_in() {
+    let dir = temp_dir("annotate");
+    let map = gray_map(&dir);
+    let out = dir.join("qc.png").to_string_lossy().into_owned();
+    let markers = [
+        PreviewMarker {
+            id: 1,
+            px: [30.0, 40.0],
+            residual: Some(0.0),
+        },
+        PreviewMarker {
+            id: 27,
+            px: [150.0, 60.0],
+            residual: Some(2.5),
+        },
+    ];
+    write_annotated_preview(&map, &markers, ErrorUnit::Pixels, &out).unwrap();
+    let input = image::open(&map).unwrap().to_rgba8();
+    let output = image::open(&out).unwrap().to_rgba8();
+    assert_eq!(output.dimensions(), input.dimensions());
+    // Marker centers take the residual colors: green for 0, red at the max
+    let low = output.get_pixel(30, 40);
+    let high = output.get_pixel(150, 60);
+    assert_ne!(low, input.get_pixel(30, 40));
+    assert!(low[1] > 200 && low[0] < 50, "{:?}", low);
+    assert!(high[0] > 200 && high[1] < 50, "{:?}", high);
+    // The white ring of the pin
+    assert_eq!(
+        output.get_pixel(30 + 3, 40),
+        &image::Rgba([250, 250, 250, 255])
+    );
+    // Far from every marker and the legend the image is untouched
+    assert_eq!(output.get_pixel(100, 5), input.get_pixel(100, 5));
+    // The legend names the unit
+    let out_m = dir.join("qc-m.png").to_string_lossy().into_owned();
+    write_annotated_preview(&map, &markers, ErrorUnit::Meters, &out_m).unwrap();
+    let meters = image::open(&out_m).unwrap().to_rgba8();
+    assert_ne!(meters, output);
+}
+
+#[test]
+fn test_markers_without_residuals_use_one_color() {
+    let dir = temp_dir("annotate-plain");
+    let map = gray_map(&dir);
+    let out = dir.join("qc.png").to_string_lossy().into_owned();
+    let markers: Vec<_> = [[20.0, 20.0], [180.0, 100.0]]
+        .iter()
+        .enumerate()
+        .map(|(i, px)| PreviewMarker {
+            id: i as u64,
+            px: *px,
+            residual: None,
+        })
+        .collect();
+    write_annotated_preview(&map, &markers, ErrorUnit::Pixels, &out).unwrap();
+    let output = image::open(&out).unwrap().to_rgba8();
+    assert_eq!(output.get_pixel(20, 20), output.get_pixel(180, 100));
+    assert_ne!(output.get_pixel(20, 20), &image::Rgba([90, 90, 90, 255]));
+    // No legend without residuals
+    assert_eq!(output.get_pixel(6, 105), &image::Rgba([90, 90, 90, 255]));
+}
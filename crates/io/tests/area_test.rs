@@ -0,0 +1,40 @@
+use io::{polygon_area_m2, Georef};
+
+fn utm10n_one_meter_pixels() -> Georef {
+    Georef {
+        affine: [1.0, 0.0, 0.0, -1.0, 500000.0, 4100000.0],
+        wkt: Some("EPSG:32610".to_string()),
+    }
+}
+
+#[test]
+fn test_rectangle_area_matches_ground_size() {
+    let geo = utm10n_one_meter_pixels();
+    // 1000 x 500 m on the central meridian, where UTM grid distances are
+    // 0.9996 of ground distances
+    let rect = [[0.0, 0.0], [1000.0, 0.0], [1000.0, 500.0], [0.0, 500.0]];
+    let area = polygon_area_m2(&geo, &rect).unwrap().unwrap();
+    let expected = 500000.0 / (0.9996f64 * 0.9996);
+    assert!(
+        (area - expected).abs() / expected < 1e-4,
+        "{} vs {}",
+        area,
+        expected
+    );
+
+    // Explicitly closed input and the opposite winding give the same area
+    let mut closed: Vec<_> = rect.iter().rev().cloned().collect();
+    closed.push(closed[0]);
+    let again = polygon_area_m2(&geo, &closed).unwrap().unwrap();
+    assert!((again - area).abs() < 1e-6 * area);
+}
+
+#[test]
+fn test_area_needs_crs() {
+    let geo = Georef {
+        wkt: None,
+        ..utm10n_one_meter_pixels()
+    };
+    let tri = [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]];
+    assert_eq!(polygon_area_m2(&geo, &tri).unwrap(), None);
+}
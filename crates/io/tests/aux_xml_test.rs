@@ -0,0 +1,73 @@
+use io::{
+    pixel_to_world, read_aux_xml_for_image, read_georeferencing_detailed,
+    read_georeferencing_for_image, CrsSource,
+};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/aux")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// GDAL's own mapping for the fixture's GeoTransform, at pixel-corner
+/// coordinates (col, row)
+fn gdal_apply(col: f64, row: f64) -> [f64; 2] {
+    let gt = [
+        550000.0,
+        1.7320508075688772,
+        1.0,
+        4180000.0,
+        1.0,
+        -1.7320508075688772,
+    ];
+    [
+        gt[0] + col * gt[1] + row * gt[2],
+        gt[3] + col * gt[4] + row * gt[5],
+    ]
+}
+
+#[test]
+fn test_rotated_geotransform_maps_pixels_like_gdal() {
+    let geo = read_aux_xml_for_image(&fixture("rotated.png"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        geo.affine[..4],
+        [1.7320508075688772, 1.0, 1.0, -1.7320508075688772]
+    );
+    // Pixel centers here are GDAL's (col + 0.5, row + 0.5)
+    for (u, v) in [(0.0, 0.0), (10.0, 0.0), (0.0, 7.0), (123.0, 45.0)] {
+        let ours = pixel_to_world(&geo, [u, v]);
+        let gdal = gdal_apply(u + 0.5, v + 0.5);
+        assert!((ours[0] - gdal[0]).abs() < 1e-6, "{:?} vs {:?}", ours, gdal);
+        assert!((ours[1] - gdal[1]).abs() < 1e-6, "{:?} vs {:?}", ours, gdal);
+    }
+    // Outer top-left corner is the GeoTransform origin
+    assert_eq!(pixel_to_world(&geo, [-0.5, -0.5]), [550000.0, 4180000.0]);
+    let wkt = geo.wkt.unwrap();
+    assert!(wkt.contains("\"WGS 84 / UTM zone 10N\""), "{}", wkt);
+}
+
+#[test]
+fn test_aux_xml_used_by_read_georeferencing() {
+    let geo = read_georeferencing_for_image(&fixture("rotated.png"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(geo.affine[1], 1.0);
+    assert!(read_aux_xml_for_image(&fixture("missing.png"))
+        .unwrap()
+        .is_none());
+    // The CRS comes from the same sidecar's <SRS>
+    let detail = read_georeferencing_detailed(&fixture("rotated.png"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        detail.crs_source,
+        Some(CrsSource::AuxXml {
+            path: PathBuf::from(fixture("rotated.png.aux.xml"))
+        })
+    );
+}
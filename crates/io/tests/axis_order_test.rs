@@ -0,0 +1,40 @@
+use io::{crs_axis_order, reproject_constraints, AxisOrder};
+use types::ConstraintKind;
+
+#[test]
+fn test_declared_axis_order() {
+    // EPSG:4326 is defined latitude first
+    assert_eq!(crs_axis_order("EPSG:4326"), Some(AxisOrder::NorthEast));
+    assert_eq!(crs_axis_order("EPSG:32633"), Some(AxisOrder::EastNorth));
+    assert_eq!(
+        crs_axis_order("+proj=longlat +datum=WGS84 +type=crs"),
+        Some(AxisOrder::EastNorth)
+    );
+    assert_eq!(crs_axis_order("not a crs"), None);
+}
+
+#[test]
+fn test_lat_lon_crs_is_used_lon_first() {
+    // Despite the declared lat,lon order, coordinates are taken as lon,lat
+    let list = vec![ConstraintKind::PointPair {
+        id: 1,
+        src: [0.0, 0.0],
+        dst: [0.0, 0.0],
+        dst_real: Some([10.0, 50.0]),
+        dst_local: None,
+        src_z: None,
+        dst_z: None,
+        weight: 1.0,
+    }];
+    let out = reproject_constraints(&list, "EPSG:4326", "EPSG:3857", None).unwrap();
+    let ConstraintKind::PointPair {
+        dst_real: Some([x, y]),
+        ..
+    } = out[0]
+    else {
+        panic!("expected a reprojected point pair");
+    };
+    // 10°E is ~1113 km east of Greenwich in Web Mercator; 50°N ~6446 km north
+    assert!((x - 1_113_194.9).abs() < 1.0, "{}", x);
+    assert!((y - 6_446_275.8).abs() < 1.0, "{}", y);
+}
@@ -0,0 +1,43 @@
+use io::{pixel_bearing_deg, Georef};
+
+fn geographic_tenth_degree_pixels() -> Georef {
+    Georef {
+        affine: [0.1, 0.0, 0.0, -0.1, -93.0, 45.0],
+        wkt: Some("EPSG:4326".to_string()),
+    }
+}
+
+#[test]
+fn test_bearing_north_and_east() {
+    let geo = geographic_tenth_degree_pixels();
+    // Up the image is north along a meridian
+    let north = pixel_bearing_deg(&geo, [5.0, 20.0], [5.0, 0.0])
+        .unwrap()
+        .unwrap();
+    assert!(
+        north.abs() < 1e-9 || (north - 360.0).abs() < 1e-9,
+        "{}",
+        north
+    );
+    // Along a parallel the geodesic starts slightly poleward of east
+    let east = pixel_bearing_deg(&geo, [0.0, 0.0], [10.0, 0.0])
+        .unwrap()
+        .unwrap();
+    assert!((east - 90.0).abs() < 0.5, "{}", east);
+    let west = pixel_bearing_deg(&geo, [10.0, 0.0], [0.0, 0.0])
+        .unwrap()
+        .unwrap();
+    assert!((west - 270.0).abs() < 0.5, "{}", west);
+}
+
+#[test]
+fn test_bearing_needs_crs() {
+    let geo = Georef {
+        wkt: None,
+        ..geographic_tenth_degree_pixels()
+    };
+    assert_eq!(
+        pixel_bearing_deg(&geo, [0.0, 0.0], [1.0, 1.0]).unwrap(),
+        None
+    );
+}
@@ -0,0 +1,34 @@
+use std::path::{Path, PathBuf};
+
+/// Scratch directory `io-test-<name>-<pid>` under the system temp dir,
+/// created if missing and removed with its contents when the returned guard
+/// is dropped, so keep it bound while the files are in use. Names must be
+/// unique within a test binary: tests run in parallel.
+pub fn temp_dir(name: &str) -> TempDir {
+    let dir = std::env::temp_dir().join(format!("io-test-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    TempDir(dir)
+}
+
+/// Guard returned by `temp_dir`; derefs to the directory's path.
+pub struct TempDir(PathBuf);
+
+impl std::ops::Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,62 @@
+use io::{compare_georefs, Georef};
+
+fn utm10n(c: f64, f: f64) -> Georef {
+    Georef {
+        affine: [2.0, 0.0, 0.0, -2.0, c, f],
+        wkt: Some("EPSG:32610".to_string()),
+    }
+}
+
+#[test]
+fn test_translation_is_reported_in_meters() {
+    let a = utm10n(499000.0, 4100000.0);
+    let b = utm10n(499025.0, 4100000.0);
+    let diff = compare_georefs(&a, &b, (1000, 800)).unwrap();
+    // 25 m of grid near the central meridian, where grid distances are
+    // 0.9996 of ground distances
+    let expected = 25.0 / 0.9996;
+    assert!((diff.mean_m - expected).abs() < 0.01, "{:?}", diff);
+    assert!((diff.max_m - expected).abs() < 0.01, "{:?}", diff);
+
+    let same = compare_georefs(&a, &a, (1000, 800)).unwrap();
+    assert!(same.max_m < 1e-6);
+}
+
+#[test]
+fn test_mismatched_crs_compares_on_the_ground() {
+    // The same 1-degree image, once in degrees and once in Web Mercator
+    // (meters); shifting the latter by 100 m is a 100 m ground offset
+    // scaled by the Mercator factor 1/cos(lat)
+    let geo = Georef {
+        affine: [0.001, 0.0, 0.0, -0.001, -93.0, 45.0],
+        wkt: Some("EPSG:4326".to_string()),
+    };
+    let to_merc = proj::Proj::new_known_crs("EPSG:4326", "EPSG:3857", None).unwrap();
+    let (x0, y0) = to_merc.convert((-93.0, 45.0)).unwrap();
+    let (x1, y1) = to_merc.convert((-92.999, 44.999)).unwrap();
+    let merc = Georef {
+        affine: [x1 - x0, 0.0, 0.0, y1 - y0, x0, y0],
+        wkt: Some("EPSG:3857".to_string()),
+    };
+    // Pixel (0, 0) and the row/column steps coincide; other samples drift
+    // apart only through the Mercator's latitude stretch over 10 pixels
+    let diff = compare_georefs(&geo, &merc, (10, 10)).unwrap();
+    assert!(diff.max_m < 0.5, "{:?}", diff);
+    let shifted = Georef {
+        affine: [x1 - x0, 0.0, 0.0, y1 - y0, x0 + 100.0, y0],
+        ..merc.clone()
+    };
+    let moved = compare_georefs(&merc, &shifted, (10, 10)).unwrap();
+    let expected = 100.0 * 45f64.to_radians().cos();
+    assert!((moved.mean_m - expected).abs() < 0.5, "{:?}", moved);
+}
+
+#[test]
+fn test_compare_needs_crs() {
+    let a = utm10n(0.0, 0.0);
+    let b = Georef {
+        wkt: None,
+        ..a.clone()
+    };
+    assert!(compare_georefs(&a, &b, (10, 10)).is_err());
+}
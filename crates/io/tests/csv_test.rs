@@ -0,0 +1,60 @@
+mod common;
+
+use common::temp_dir;
+use io::{read_control_points_csv, ColumnMapping, ControlPoint};
+
+#[test]
+fn test_csv_custom_column_order_with_header() {
+    let dir = temp_dir("csv");
+    let path = dir.join("points.csv");
+    std::fs::write(
+        &path,
+        "label,ref_x,ref_y,w,map_y,map_x\n\
+         a,100.5,200,2,20,10\n\
+         \n\
+         b, 300 ,400.25,0.5,40,30\n",
+    )
+    .unwrap();
+    let mapping = ColumnMapping {
+        src_x: 5,
+        src_y: 4,
+        dst_x: 1,
+        dst_y: 2,
+        weight: Some(3),
+        has_header: true,
+    };
+    let pts = read_control_points_csv(path.to_str().unwrap(), &mapping).unwrap();
+    assert_eq!(
+        pts,
+        vec![
+            ControlPoint {
+                src: [10.0, 20.0],
+                dst: [100.5, 200.0],
+                weight: 2.0,
+            },
+            ControlPoint {
+                src: [30.0, 40.0],
+                dst: [300.0, 400.25],
+                weight: 0.5,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_csv_reports_failing_row_number() {
+    let dir = temp_dir("csv-bad");
+    let path = dir.join("points.csv");
+    std::fs::write(&path, "1,2,3,4\n5,x,7,8\n").unwrap();
+    let mapping = ColumnMapping {
+        src_x: 0,
+        src_y: 1,
+        dst_x: 2,
+        dst_y: 3,
+        weight: None,
+        has_header: false,
+    };
+    let err = read_control_points_csv(path.to_str().unwrap(), &mapping).unwrap_err();
+    assert!(err.to_string().contains("row 2"), "{}", err);
+    assert!(err.to_string().contains("src_y"), "{}", err);
+}
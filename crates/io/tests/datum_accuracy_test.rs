@@ -0,0 +1,17 @@
+use io::transformation_accuracy;
+
+#[test]
+fn test_datum_shift_reports_its_accuracy() {
+    // OSGB36 -> WGS 84 without the OSTN15 grid is a Helmert good to ~2 m
+    let osgb = transformation_accuracy("EPSG:4277", "EPSG:4326").unwrap();
+    assert!(osgb > 0.5 && osgb <= 5.0, "{}", osgb);
+    // ETRS89 and WGS 84 are treated as the same to about 1 m
+    let etrs = transformation_accuracy("EPSG:4258", "EPSG:4326").unwrap();
+    assert!(etrs > 0.0 && etrs <= 2.0, "{}", etrs);
+    // Projecting on the same datum involves no shift
+    assert_eq!(
+        transformation_accuracy("EPSG:4326", "EPSG:32610"),
+        Some(0.0)
+    );
+    assert_eq!(transformation_accuracy("not a crs", "EPSG:4326"), None);
+}
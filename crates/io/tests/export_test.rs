@@ -0,0 +1,139 @@
+mod common;
+
+use common::{temp_dir, TempDir};
+use io::{
+    image_extent, read_world_file, resolve_export_crs, write_export, write_files_atomic,
+    ExportPreview, Georef, DEFAULT_PRJ_WKT,
+};
+
+/// Scratch directory and the export base path `out` inside it.
+fn temp_base(name: &str) -> (TempDir, String) {
+    let dir = temp_dir(name);
+    let base = dir.join("out").to_string_lossy().into_owned();
+    (dir, base)
+}
+
+#[test]
+fn test_preview_matches_written_files() {
+    let affine = [0.5, 0.1, -0.1, -0.5, 1000.0, 2000.0];
+    let geo = Georef { affine, wkt: None };
+    let preview = ExportPreview {
+        affine,
+        prj_wkt: "EPSG:32633".into(),
+        extent: Some(image_extent(&geo, 200, 100)),
+        warnings: Vec::new(),
+    };
+    let (_dir, base) = temp_base("preview");
+    write_export(&base, &preview).unwrap();
+
+    let written = read_world_file(&base).unwrap();
+    for (w, p) in written.iter().zip(preview.affine.iter()) {
+        assert_eq!(w, p);
+    }
+    let prj = std::fs::read_to_string(format!("{}.prj", base)).unwrap();
+    assert_eq!(prj, preview.prj_wkt);
+}
+
+#[test]
+fn test_image_extent_covers_pixel_edges() {
+    let geo = Georef {
+        affine: [2.0, 0.0, 0.0, -2.0, 100.0, 50.0],
+        wkt: None,
+    };
+    let [min_x, min_y, max_x, max_y] = image_extent(&geo, 10, 5);
+    assert_eq!(min_x, 99.0);
+    assert_eq!(max_x, 119.0);
+    assert_eq!(min_y, 41.0);
+    assert_eq!(max_y, 51.0);
+}
+
+#[test]
+fn test_fallback_crs_written_to_prj() {
+    let (prj_wkt, warning) = resolve_export_crs(None, Some("EPSG:4326"));
+    assert!(warning.is_none());
+    let preview = ExportPreview {
+        affine: [1.0, 0.0, 0.0, -1.0, 0.0, 0.0],
+        prj_wkt,
+        extent: None,
+        warnings: Vec::new(),
+    };
+    let (_dir, base) = temp_base("fallback");
+    write_export(&base, &preview).unwrap();
+    let prj = std::fs::read_to_string(format!("{}.prj", base)).unwrap();
+    assert_eq!(prj, "EPSG:4326");
+}
+
+#[test]
+fn test_export_crs_prefers_reference_and_warns_on_default() {
+    let (crs, warning) = resolve_export_crs(Some("EPSG:32633"), Some("EPSG:4326"));
+    assert_eq!(crs, "EPSG:32633");
+    assert!(warning.is_none());
+    let (crs, warning) = resolve_export_crs(None, None);
+    assert_eq!(crs, DEFAULT_PRJ_WKT);
+    assert!(warning.is_some());
+}
+
+#[test]
+fn test_failed_export_leaves_no_partial_files() {
+    let (dir, base) = temp_base("rollback");
+    // A directory where the PRJ should go: the world file is renamed into
+    // place first, then the PRJ rename fails
+    std::fs::create_dir_all(format!("{}.prj", base)).unwrap();
+    let preview = ExportPreview {
+        affine: [1.0, 0.0, 0.0, -1.0, 0.0, 0.0],
+        prj_wkt: "EPSG:4326".into(),
+        extent: None,
+        warnings: Vec::new(),
+    };
+    assert!(write_export(&base, &preview).is_err());
+    let mut left: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    left.sort();
+    assert_eq!(
+        left,
+        vec!["out.prj".to_string()],
+        "only the blocking directory remains"
+    );
+
+    // A write-stage failure (missing directory) also leaves nothing behind
+    let ok = dir.join("first.txt");
+    let bad = dir.join("missing").join("second.txt");
+    assert!(write_files_atomic(&[(ok.clone(), b"a".to_vec()), (bad, b"b".to_vec())]).is_err());
+    assert!(!ok.exists());
+    assert!(!dir.join(".first.txt.tmp").exists());
+}
+
+#[test]
+fn test_failed_export_restores_existing_sidecars() {
+    let dir = temp_dir("restore");
+    let files = |names: [&str; 2]| {
+        names
+            .iter()
+            .map(|n| (dir.join(n), format!("new {}", n).into_bytes()))
+            .collect::<Vec<_>>()
+    };
+    std::fs::write(dir.join("a.tfw"), "old tfw").unwrap();
+    std::fs::write(dir.join("a.prj"), "old prj").unwrap();
+    // Overwriting works and leaves no backups behind
+    write_files_atomic(&files(["a.tfw", "a.prj"])).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(dir.join("a.tfw")).unwrap(),
+        "new a.tfw"
+    );
+    assert!(!dir.join(".a.tfw.bak").exists());
+
+    // The second rename fails: the first target gets its old contents back
+    std::fs::write(dir.join("b.tfw"), "old tfw").unwrap();
+    std::fs::create_dir_all(dir.join("b.prj")).unwrap();
+    assert!(write_files_atomic(&files(["b.tfw", "b.prj"])).is_err());
+    assert_eq!(
+        std::fs::read_to_string(dir.join("b.tfw")).unwrap(),
+        "old tfw"
+    );
+    assert!(dir.join("b.prj").is_dir());
+    for leftover in [".b.tfw.bak", ".b.tfw.tmp", ".b.prj.tmp"] {
+        assert!(!dir.join(leftover).exists(), "{}", leftover);
+    }
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::temp_dir;
+use io::{load_raster, supported_formats};
+
+#[test]
+fn test_known_formats_listed() {
+    let formats = supported_formats();
+    let get = |ext: &str| formats.iter().find(|f| f.extension == ext).unwrap();
+    for ext in ["png", "jpg", "jpeg", "tif", "tiff", "gif", "bmp"] {
+        let f = get(ext);
+        assert!(f.preview && f.georeferencing && f.export, "{}", ext);
+    }
+    // Stubbed loaders must not be advertised
+    for ext in ["mbtiles", "pdf"] {
+        let f = get(ext);
+        assert!(!f.preview && !f.georeferencing && !f.export, "{}", ext);
+    }
+}
+
+#[test]
+fn test_previewable_formats_actually_load() {
+    let dir = temp_dir("formats");
+    let img = image::RgbImage::from_pixel(3, 2, image::Rgb([9, 8, 7]));
+    // Formats the image crate can also encode, so a sample can be generated
+    let encodable = ["png", "jpg", "jpeg", "tif", "tiff", "gif", "bmp"];
+    for f in supported_formats().iter().filter(|f| f.preview) {
+        if !encodable.contains(&f.extension.as_str()) {
+            continue;
+        }
+        let path = dir.join(format!("sample.{}", f.extension));
+        img.save(&path).unwrap();
+        let uri = load_raster(path.to_str().unwrap()).unwrap().data_uri;
+        assert!(uri.starts_with("data:image/png;base64,"), "{}", f.extension);
+    }
+}
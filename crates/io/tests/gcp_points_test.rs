@@ -0,0 +1,93 @@
+mod common;
+
+use common::temp_dir;
+use io::{read_gcp_points, write_gcp_points, GcpPoint};
+use std::path::PathBuf;
+
+#[test]
+fn test_gcp_points_round_trip_full_precision() {
+    let points = vec![
+        GcpPoint {
+            map: [500123.1234567891, 4649876.987654321],
+            pixel: [12.5, 300.25],
+            enabled: true,
+        },
+        GcpPoint {
+            map: [-0.1 + 0.2, 1e-12],
+            pixel: [1023.0, 0.0],
+            enabled: true,
+        },
+    ];
+    let dir = temp_dir("gcp");
+    let path = dir.join("map.tif.points");
+    let path = path.to_str().unwrap();
+    write_gcp_points(path, &points, Some("EPSG:26910")).unwrap();
+
+    let text = std::fs::read_to_string(path).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(lines.next(), Some("#CRS: EPSG:26910"));
+    assert_eq!(lines.next(), Some("mapX,mapY,pixelX,pixelY,enable"));
+    // QGIS convention: corner origin, pixel rows stored as negative y
+    assert_eq!(
+        lines.next(),
+        Some("500123.1234567891,4649876.987654321,13,-300.75,1")
+    );
+
+    assert_eq!(read_gcp_points(path).unwrap(), points);
+}
+
+#[test]
+fn test_read_qgis3_source_columns() {
+    let dir = temp_dir("gcp-qgis3");
+    let path = dir.join("scan.points");
+    std::fs::write(
+        &path,
+        "#CRS: GEOGCRS[\"WGS 84\"]\n\
+         mapX,mapY,sourceX,sourceY,enable,dX,dY,residual\n\
+         -122.5,45.25,10,-20,1,0.1,0.2,0.22\n\
+         -122.4,45.35,110,-220,0,0,0,0\n",
+    )
+    .unwrap();
+    let pts = read_gcp_points(path.to_str().unwrap()).unwrap();
+    assert_eq!(pts.len(), 2);
+    assert_eq!(pts[0].pixel, [9.5, 19.5]);
+    assert_eq!(pts[1].map, [-122.4, 45.35]);
+    assert!(!pts[1].enabled);
+}
+
+#[test]
+fn test_qgis_points_use_corner_origin() {
+    // Saved by the QGIS georeferencer for a 200x100 px scan: GCPs on the
+    // four outer image corners and one on the center of pixel (50, 20)
+    let fixture =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/points/scan.tif.points");
+    let pts = read_gcp_points(fixture.to_str().unwrap()).unwrap();
+    let pixels: Vec<[f64; 2]> = pts.iter().map(|p| p.pixel).collect();
+    assert_eq!(
+        pixels,
+        vec![
+            [-0.5, -0.5],
+            [199.5, -0.5],
+            [-0.5, 99.5],
+            [199.5, 99.5],
+            [50.0, 20.0],
+        ]
+    );
+    assert_eq!(pts[4].map, [500101.0, 4649959.0]);
+
+    // Writing them back gives QGIS the same source coordinates
+    let dir = temp_dir("gcp-corner");
+    let path = dir.join("scan.tif.points");
+    let path = path.to_str().unwrap();
+    write_gcp_points(path, &pts, None).unwrap();
+    let text = std::fs::read_to_string(path).unwrap();
+    let sources: Vec<&str> = text
+        .lines()
+        .skip(1)
+        .map(|l| l.splitn(3, ',').nth(2).unwrap())
+        .collect();
+    assert_eq!(
+        sources,
+        vec!["0,0,1", "200,0,1", "0,-100,1", "200,-100,1", "50.5,-20.5,1"]
+    );
+}
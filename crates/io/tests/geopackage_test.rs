@@ -0,0 +1,94 @@
+mod common;
+
+use common::temp_dir;
+use io::{write_geopackage, GpkgControlPoint};
+
+#[test]
+fn test_geopackage_has_points_and_parameters() {
+    let dir = temp_dir("gpkg");
+    let path = dir.join("deliverable.gpkg");
+    let points: Vec<_> = (0..3)
+        .map(|i| GpkgControlPoint {
+            id: 10 + i,
+            map_px: [i as f64 * 100.0, 50.0],
+            ref_px: [i as f64 * 200.0, 100.0],
+            world: [500000.0 + i as f64 * 400.0, 4200000.0 - 200.0],
+            residual: (i > 0).then_some(0.25 * i as f64),
+        })
+        .collect();
+    let parameters = vec![
+        ("method".to_string(), "similarity".to_string()),
+        ("scale".to_string(), "2".to_string()),
+    ];
+    write_geopackage(
+        &path.to_string_lossy(),
+        &points,
+        Some("EPSG:32610"),
+        &parameters,
+    )
+    .unwrap();
+
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let app_id: i64 = conn
+        .query_row("PRAGMA application_id", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(app_id, 0x4750_4B47);
+    let mut tables: Vec<(String, String)> = conn
+        .prepare("SELECT table_name, data_type FROM gpkg_contents ORDER BY table_name")
+        .unwrap()
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    tables.sort();
+    assert_eq!(
+        tables,
+        vec![
+            ("control_points".to_string(), "features".to_string()),
+            ("transform_parameters".to_string(), "attributes".to_string()),
+        ]
+    );
+    let n: i64 = conn
+        .query_row("SELECT COUNT(*) FROM control_points", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(n, 3);
+    let (srs, org): (i64, String) = conn
+        .query_row(
+            "SELECT g.srs_id, s.organization FROM gpkg_geometry_columns g
+             JOIN gpkg_spatial_ref_sys s USING (srs_id) WHERE g.table_name = 'control_points'",
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!((srs, org.as_str()), (32610, "EPSG"));
+
+    let (blob, residual): (Vec<u8>, Option<f64>) = conn
+        .query_row(
+            "SELECT geom, residual FROM control_points WHERE point_id = 12",
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(residual, Some(0.5));
+    assert_eq!(&blob[..2], b"GP");
+    assert_eq!(i32::from_le_bytes(blob[4..8].try_into().unwrap()), 32610);
+    let x = f64::from_le_bytes(blob[13..21].try_into().unwrap());
+    let y = f64::from_le_bytes(blob[21..29].try_into().unwrap());
+    assert_eq!([x, y], points[2].world);
+    let scale: String = conn
+        .query_row(
+            "SELECT value FROM transform_parameters WHERE name = 'scale'",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(scale, "2");
+
+    // Rewriting replaces the package instead of appending to it
+    write_geopackage(&path.to_string_lossy(), &points[..1], None, &[]).unwrap();
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let n: i64 = conn
+        .query_row("SELECT COUNT(*) FROM control_points", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(n, 1);
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::temp_dir;
+use io::{
+    embed_geotiff, image_dimensions, read_georeferencing_detailed, read_georeferencing_for_image,
+    read_geotiff_georeferencing, write_geotiff, CrsSource,
+};
+use std::path::PathBuf;
+
+fn gradient(w: u32, h: u32) -> image::RgbaImage {
+    image::RgbaImage::from_fn(w, h, |x, y| image::Rgba([x as u8, y as u8, 7, 255]))
+}
+
+#[test]
+fn test_rotated_affine_round_trips_through_model_transformation() {
+    // 2 m pixels rotated by 15 degrees: B and D are non-zero
+    let (s, c) = 15f64.to_radians().sin_cos();
+    let affine = [2.0 * c, 2.0 * s, 2.0 * s, -2.0 * c, 500123.5, 4100456.25];
+    let dir = temp_dir("geotiff-write");
+    let out = dir.join("rotated.tif");
+    let out = out.to_str().unwrap();
+    write_geotiff(out, &gradient(40, 30), affine, Some("EPSG:32610")).unwrap();
+
+    let geo = read_geotiff_georeferencing(out).unwrap().unwrap();
+    assert_eq!(geo.affine, affine);
+    assert_eq!(geo.wkt.as_deref(), Some("EPSG:32610"));
+    assert_eq!(image_dimensions(out).unwrap(), (40, 30));
+    // No sidecars: the generic reader finds the embedded tags
+    assert_eq!(
+        read_georeferencing_for_image(out).unwrap().unwrap().affine,
+        affine
+    );
+    assert_eq!(
+        read_georeferencing_detailed(out)
+            .unwrap()
+            .unwrap()
+            .crs_source,
+        Some(CrsSource::GeoTiffKeys)
+    );
+    let back = image::open(out).unwrap().to_rgba8();
+    assert_eq!(back, gradient(40, 30));
+}
+
+#[test]
+fn test_embed_geotiff_geographic_and_non_epsg_crs() {
+    let dir = temp_dir("geotiff-embed");
+    let src = dir.join("map.png");
+    gradient(8, 6).save(&src).unwrap();
+    let affine = [0.001, 0.0002, 0.0002, -0.001, -122.5, 37.8];
+    let out = dir.join("map.tif");
+    embed_geotiff(
+        src.to_str().unwrap(),
+        out.to_str().unwrap(),
+        affine,
+        Some("EPSG:4326"),
+    )
+    .unwrap();
+    let geo = read_geotiff_georeferencing(out.to_str().unwrap())
+        .unwrap()
+        .unwrap();
+    assert_eq!(geo.affine, affine);
+    assert_eq!(geo.wkt.as_deref(), Some("EPSG:4326"));
+
+    // A CRS PROJ cannot identify is not embedded; the affine still is
+    write_geotiff(
+        out.to_str().unwrap(),
+        &gradient(8, 6),
+        affine,
+        Some("+proj=longlat"),
+    )
+    .unwrap();
+    let geo = read_geotiff_georeferencing(out.to_str().unwrap())
+        .unwrap()
+        .unwrap();
+    assert_eq!(geo.affine, affine);
+    assert_eq!(geo.wkt, None);
+}
+
+#[test]
+fn test_wkt_crs_is_embedded_as_its_epsg_code() {
+    let dir = temp_dir("geotiff-wkt");
+    let out = dir.join("utm.tif");
+    let out = out.to_str().unwrap();
+    let affine = [2.0, 0.0, 0.0, -2.0, 500001.0, 4100001.0];
+    // ESRI-flavored .prj text for NAD83 / UTM zone 10N
+    let prj = std::fs::read_to_string(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/esri_utm10n.prj"),
+    )
+    .unwrap();
+    assert_eq!(io::crs_epsg_code(&prj), Some(26910));
+    write_geotiff(out, &gradient(4, 3), affine, Some(&prj)).unwrap();
+    let geo = read_geotiff_georeferencing(out).unwrap().unwrap();
+    assert_eq!(geo.wkt.as_deref(), Some("EPSG:26910"));
+
+    // OGC WKT of a geographic CRS
+    let wgs84 = "GEOGCS[\"WGS 84\",DATUM[\"WGS_1984\",SPHEROID[\"WGS 84\",6378137,298.257223563,\
+                 AUTHORITY[\"EPSG\",\"7030\"]],AUTHORITY[\"EPSG\",\"6326\"]],PRIMEM[\"Greenwich\",0],\
+                 UNIT[\"degree\",0.0174532925199433],AUTHORITY[\"EPSG\",\"4326\"]]";
+    write_geotiff(out, &gradient(4, 3), affine, Some(wgs84)).unwrap();
+    let geo = read_geotiff_georeferencing(out).unwrap().unwrap();
+    assert_eq!(geo.wkt.as_deref(), Some("EPSG:4326"));
+
+    // A custom transverse Mercator has no EPSG code
+    let custom = "+proj=tmerc +lon_0=-121.3 +k=0.9999 +x_0=123456 +datum=WGS84 +units=m +type=crs";
+    assert_eq!(io::crs_epsg_code(custom), None);
+}
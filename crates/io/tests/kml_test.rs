@@ -0,0 +1,46 @@
+mod common;
+
+use common::temp_dir;
+use io::{image_corners, write_kml_ground_overlay, Georef};
+
+#[test]
+fn test_image_corners_counter_clockwise_from_bottom_left() {
+    let geo = Georef {
+        affine: [1.0, 0.0, 0.0, -1.0, 100.0, 200.0],
+        wkt: None,
+    };
+    let c = image_corners(&geo, 10, 4);
+    assert_eq!(
+        c,
+        [[99.5, 196.5], [109.5, 196.5], [109.5, 200.5], [99.5, 200.5]]
+    );
+}
+
+#[test]
+fn test_kml_quad_has_four_lon_lat_corners() {
+    let dir = temp_dir("kml");
+    let image = dir.join("sheet & map.png");
+    // Rotated overlay near Zurich, given as (lon, lat)
+    let quad = [[8.50, 47.35], [8.56, 47.36], [8.55, 47.40], [8.49, 47.39]];
+    let path = write_kml_ground_overlay(image.to_str().unwrap(), &quad).unwrap();
+    assert_eq!(path.extension().unwrap(), "kml");
+    let kml = std::fs::read_to_string(path).unwrap();
+    assert!(kml.contains("<href>sheet &amp; map.png</href>"));
+    assert!(kml.contains("<gx:LatLonQuad>"));
+    assert!(!kml.contains("LatLonBox"));
+
+    let start = kml.find("<coordinates>").unwrap() + "<coordinates>".len();
+    let end = kml.find("</coordinates>").unwrap();
+    let corners: Vec<[f64; 2]> = kml[start..end]
+        .split_whitespace()
+        .map(|t| {
+            let v: Vec<f64> = t.split(',').map(|x| x.parse().unwrap()).collect();
+            [v[0], v[1]]
+        })
+        .collect();
+    assert_eq!(corners.len(), 4);
+    for (got, want) in corners.iter().zip(quad.iter()) {
+        // Longitude first: 8.x, then latitude 47.x
+        assert!((got[0] - want[0]).abs() < 1e-9 && (got[1] - want[1]).abs() < 1e-9);
+    }
+}
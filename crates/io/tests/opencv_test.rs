@@ -0,0 +1,71 @@
+mod common;
+
+use common::temp_dir;
+use io::read_opencv_correspondences;
+use std::path::PathBuf;
+use types::ConstraintKind;
+
+fn src_dst(c: &ConstraintKind) -> ([f64; 2], [f64; 2]) {
+    match c {
+        ConstraintKind::PointPair { src, dst, .. } => (*src, *dst),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_nested_per_view_fixture() {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/opencv_calibration.json");
+    let pairs = read_opencv_correspondences(path.to_str().unwrap()).unwrap();
+    assert_eq!(pairs.len(), 5);
+    assert_eq!(src_dst(&pairs[0]), ([101.5, 202.25], [500010.0, 4100200.0]));
+    assert_eq!(src_dst(&pairs[4]), ([650.5, 795.25], [500122.0, 4100082.0]));
+    assert_eq!(pairs[4].id(), 4);
+}
+
+#[test]
+fn test_flat_and_matrix_layouts() {
+    let dir = temp_dir("opencv");
+    let flat = dir.join("flat.json");
+    std::fs::write(
+        &flat,
+        r#"{"image_points": [1, 2, 3, 4], "object_points": [10, 20, 0, 30, 40, 0]}"#,
+    )
+    .unwrap();
+    let pairs = read_opencv_correspondences(flat.to_str().unwrap()).unwrap();
+    assert_eq!(pairs.len(), 2);
+    assert_eq!(src_dst(&pairs[1]), ([3.0, 4.0], [30.0, 40.0]));
+
+    let matrix = dir.join("matrix.json");
+    std::fs::write(
+        &matrix,
+        r#"{
+            "image_points": {"type_id": "opencv-matrix", "rows": 3, "cols": 1, "dt": "2f",
+                             "data": [1, 2, 3, 4, 5, 6]},
+            "object_points": {"type_id": "opencv-matrix", "rows": 3, "cols": 3, "dt": "d",
+                              "data": [7, 8, 0, 9, 10, 0, 11, 12, 0]}
+        }"#,
+    )
+    .unwrap();
+    let pairs = read_opencv_correspondences(matrix.to_str().unwrap()).unwrap();
+    assert_eq!(pairs.len(), 3);
+    assert_eq!(src_dst(&pairs[2]), ([5.0, 6.0], [11.0, 12.0]));
+}
+
+#[test]
+fn test_mismatched_counts_fail() {
+    let dir = temp_dir("opencv-mismatch");
+    let path = dir.join("mismatch.json");
+    std::fs::write(
+        &path,
+        r#"{"image_points": [[1, 2], [3, 4]], "object_points": [[1, 2, 0]]}"#,
+    )
+    .unwrap();
+    let err = read_opencv_correspondences(path.to_str().unwrap()).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("2 image points but 1 object points"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,30 @@
+use io::{is_esri_wkt, read_prj_for_image, DEFAULT_PRJ_WKT};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn test_esri_prj_normalized_to_epsg() {
+    // Only the .prj sidecar exists; the image itself is not needed
+    let raw = std::fs::read_to_string(fixture("esri_utm10n.prj")).unwrap();
+    assert!(is_esri_wkt(&raw));
+    let wkt = read_prj_for_image(&fixture("esri_utm10n.tif")).unwrap();
+    assert!(!is_esri_wkt(&wkt));
+    assert!(wkt.contains("AUTHORITY[\"EPSG\",\"26910\"]"), "{}", wkt);
+
+    let to_wgs84 = proj::Proj::new_known_crs(&wkt, "EPSG:4326", None).unwrap();
+    let (lon, lat): (f64, f64) = to_wgs84.convert((500000.0, 4000000.0)).unwrap();
+    assert!((lon + 123.0).abs() < 1e-9);
+    assert!((lat - 36.1447).abs() < 1e-3);
+}
+
+#[test]
+fn test_ogc_wkt_not_treated_as_esri() {
+    assert!(!is_esri_wkt(DEFAULT_PRJ_WKT));
+}
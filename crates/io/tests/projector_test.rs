@@ -0,0 +1,94 @@
+use io::{pixel_to_local_meters, pixel_to_world, utm_epsg, utm_zone, Georef, ReferenceProjector};
+
+fn utm10n_half_meter_pixels() -> Georef {
+    Georef {
+        affine: [0.5, 0.0, 0.0, -0.5, 431000.0, 4270000.0],
+        wkt: Some("EPSG:32610".to_string()),
+    }
+}
+
+const PIXELS: [[f64; 2]; 3] = [[0.0, 0.0], [812.5, 40.0], [-30.0, 1999.0]];
+
+#[test]
+fn test_projector_matches_standalone_functions() {
+    let geo = utm10n_half_meter_pixels();
+    let projector = ReferenceProjector::new(&geo, Some((2000, 1600))).unwrap();
+    let to_wgs84 = proj::Proj::new_known_crs("EPSG:32610", "EPSG:4326", None).unwrap();
+    for px in PIXELS {
+        let world = pixel_to_world(&geo, px);
+        assert_eq!(projector.to_world(px), world);
+
+        let (lon, lat) = to_wgs84.convert((world[0], world[1])).unwrap();
+        let ll = projector.to_wgs84(px).unwrap().unwrap();
+        assert!((ll[0] - lon).abs() < 1e-12 && (ll[1] - lat).abs() < 1e-12);
+
+        let local = projector.to_local_meters(px).unwrap().unwrap();
+        let expected = pixel_to_local_meters(&geo, px, [1000.0, 800.0])
+            .unwrap()
+            .unwrap();
+        assert!((local[0] - expected[0]).abs() < 1e-6 && (local[1] - expected[1]).abs() < 1e-6);
+
+        // Back to the reference's own zone
+        let utm = projector.to_utm(px, "WGS84").unwrap().unwrap();
+        assert!((utm[0] - world[0]).abs() < 1e-4 && (utm[1] - world[1]).abs() < 1e-4);
+        // GRS80 and WGS84 UTM differ by well under a millimeter
+        let nad = projector.to_utm(px, "NAD83_2011").unwrap().unwrap();
+        assert!((nad[0] - world[0]).abs() < 1e-3 && (nad[1] - world[1]).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn test_projector_without_crs() {
+    let geo = Georef {
+        wkt: None,
+        ..utm10n_half_meter_pixels()
+    };
+    let projector = ReferenceProjector::new(&geo, None).unwrap();
+    assert_eq!(projector.to_world([2.0, 2.0]), [431001.0, 4269999.0]);
+    assert_eq!(projector.to_wgs84([0.0, 0.0]).unwrap(), None);
+    assert_eq!(projector.to_local_meters([0.0, 0.0]).unwrap(), None);
+    assert_eq!(projector.to_utm([0.0, 0.0], "WGS84").unwrap(), None);
+    let bad = Georef {
+        wkt: Some("not a crs".to_string()),
+        ..geo
+    };
+    assert!(ReferenceProjector::new(&bad, None).is_err());
+}
+
+#[test]
+fn test_utm_zone_boundaries() {
+    assert_eq!(utm_zone(-180.0), 1);
+    assert_eq!(utm_zone(-123.0), 10);
+    assert_eq!(utm_zone(-0.5), 30);
+    assert_eq!(utm_zone(0.0), 31);
+    assert_eq!(utm_zone(180.0), 60);
+}
+
+#[test]
+fn test_utm_zone_60_and_padded_epsg() {
+    // Regressions: zones were numbered from 0, and single-digit zones gave
+    // 3-digit codes such as EPSG:3265
+    assert_eq!(utm_zone(179.9), 60);
+    assert_eq!(utm_zone(174.0), 60);
+    assert_eq!(utm_zone(173.9), 59);
+    assert_eq!(utm_epsg(5, true), "EPSG:32605");
+    assert_eq!(utm_epsg(1, false), "EPSG:32701");
+    assert_eq!(utm_epsg(60, true), "EPSG:32660");
+
+    // A reference near the antimeridian converts in zone 60
+    let geo = Georef {
+        affine: [0.001, 0.0, 0.0, -0.001, 177.0, -40.0],
+        wkt: Some("EPSG:4326".to_string()),
+    };
+    let projector = ReferenceProjector::new(&geo, None).unwrap();
+    let utm = projector.to_utm([0.0, 0.0], "WGS84").unwrap().unwrap();
+    let expected = proj::Proj::new_known_crs("EPSG:4326", "EPSG:32760", None)
+        .unwrap()
+        .convert((177.0, -40.0))
+        .unwrap();
+    assert!((utm[0] - expected.0).abs() < 1e-6 && (utm[1] - expected.1).abs() < 1e-6);
+    assert!(
+        (utm[0] - 500000.0).abs() < 1.0,
+        "177E is zone 60's meridian"
+    );
+}
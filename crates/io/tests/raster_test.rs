@@ -0,0 +1,251 @@
+mod common;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use common::temp_dir;
+use io::{
+    exif_orientation, image_dimensions, load_raster, load_raster_preview,
+    load_raster_preview_with_depth, load_raster_with_depth, write_world_file_for_image,
+    PreviewDepth,
+};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn decode_data_uri(uri: &str) -> image::RgbaImage {
+    let b64 = uri.strip_prefix("data:image/png;base64,").unwrap();
+    let bytes = BASE64.decode(b64).unwrap();
+    image::load_from_memory(&bytes).unwrap().to_rgba8()
+}
+
+#[test]
+fn test_tiff_dimensions_from_header_only() {
+    // The fixture declares a 2 GB strip that is not in the file; any pixel
+    // decode would fail, so success proves only the header was read.
+    let dims = image_dimensions(&fixture("header_only_50000x40000.tif")).unwrap();
+    assert_eq!(dims, (50000, 40000));
+}
+
+#[test]
+fn test_tiff_preview_decimates_by_chunks() {
+    use tiff::encoder::{colortype, TiffEncoder};
+    let (w, h) = (64u32, 48u32);
+    let mut data = Vec::with_capacity((w * h * 3) as usize);
+    for y in 0..h {
+        for x in 0..w {
+            data.extend_from_slice(&[(x * 4) as u8, (y * 5) as u8, 7]);
+        }
+    }
+    let dir = temp_dir("preview");
+    let path = dir.join("gradient.tif");
+    {
+        let file = std::fs::File::create(&path).unwrap();
+        let mut enc = TiffEncoder::new(file).unwrap();
+        let mut img = enc.new_image::<colortype::RGB8>(w, h).unwrap();
+        img.rows_per_strip(5).unwrap();
+        img.write_data(&data).unwrap();
+    }
+    let uri = load_raster_preview(path.to_str().unwrap(), 16).unwrap();
+    let preview = decode_data_uri(&uri);
+    // step = ceil(64 / 16) = 4
+    assert_eq!(preview.dimensions(), (16, 12));
+    for (px, py) in [(0, 0), (3, 7), (15, 11)] {
+        let p = preview.get_pixel(px, py);
+        assert_eq!(p.0, [(px * 4 * 4) as u8, (py * 4 * 5) as u8, 7, 255]);
+    }
+}
+
+#[test]
+fn test_load_raster_surfaces_rotated_georef() {
+    let dir = temp_dir("rotated");
+    let path = dir.join("rotated.png");
+    image::RgbImage::from_pixel(5, 3, image::Rgb([1, 2, 3]))
+        .save(&path)
+        .unwrap();
+    let path = path.to_str().unwrap();
+    // 30 degree rotation at 2 m/px
+    let (s, c) = (
+        30f64.to_radians().sin() * 2.0,
+        30f64.to_radians().cos() * 2.0,
+    );
+    let affine = [c, s, s, -c, 500000.0, 4100000.0];
+    write_world_file_for_image(path, affine).unwrap();
+
+    let loaded = load_raster(path).unwrap();
+    assert_eq!((loaded.width, loaded.height), (5, 3));
+    assert_eq!(decode_data_uri(&loaded.data_uri).dimensions(), (5, 3));
+    let got = loaded.affine.expect("rotated georef");
+    for (g, e) in got.iter().zip(affine.iter()) {
+        assert!((g - e).abs() < 1e-9, "{:?} vs {:?}", got, affine);
+    }
+}
+
+#[test]
+fn test_load_raster_without_georef() {
+    let dir = temp_dir("plain");
+    let path = dir.join("plain.png");
+    image::RgbImage::from_pixel(2, 2, image::Rgb([0, 0, 0]))
+        .save(&path)
+        .unwrap();
+    assert_eq!(load_raster(path.to_str().unwrap()).unwrap().affine, None);
+}
+
+#[test]
+fn test_load_raster_with_garbage_world_file() {
+    let dir = temp_dir("garbage-tfw");
+    let path = dir.join("scan.tif");
+    image::RgbImage::from_pixel(4, 3, image::Rgb([9, 9, 9]))
+        .save(&path)
+        .unwrap();
+    // Parses, but all-zero pixel sizes make the affine singular
+    std::fs::write(dir.join("scan.tfw"), "0\n0\n0\n0\n0\n0\n").unwrap();
+    let loaded = load_raster(path.to_str().unwrap()).unwrap();
+    assert_eq!((loaded.width, loaded.height), (4, 3));
+    assert_eq!(loaded.affine, None);
+    assert!(loaded.georef_warning.is_some());
+
+    std::fs::remove_file(dir.join("scan.tfw")).unwrap();
+    assert_eq!(
+        load_raster(path.to_str().unwrap()).unwrap().georef_warning,
+        None
+    );
+}
+
+#[test]
+fn test_exif_rotation_is_reported_not_applied() {
+    // 6x4 stored pixels tagged Orientation 6 (viewers show it 4x6, turned
+    // 90° clockwise)
+    let path = fixture("exif_orientation6_6x4.jpg");
+    assert_eq!(exif_orientation(&path).unwrap(), Some(6));
+    let loaded = load_raster(&path).unwrap();
+    assert_eq!(loaded.exif_orientation, Some(6));
+    // Preview, size and coordinates all stay in stored-pixel space
+    assert_eq!((loaded.width, loaded.height), (6, 4));
+    assert_eq!(decode_data_uri(&loaded.data_uri).dimensions(), (6, 4));
+    assert_eq!(image_dimensions(&path).unwrap(), (6, 4));
+    let preview = decode_data_uri(&load_raster_preview(&path, 100).unwrap());
+    assert_eq!(preview.dimensions(), (6, 4));
+
+    // Untagged images report nothing
+    let dir = temp_dir("exif-none");
+    let png = dir.join("plain.png");
+    image::RgbaImage::new(3, 2).save(&png).unwrap();
+    assert_eq!(exif_orientation(png.to_str().unwrap()).unwrap(), None);
+    assert_eq!(
+        load_raster(png.to_str().unwrap()).unwrap().exif_orientation,
+        None
+    );
+}
+
+#[test]
+fn test_tiff_orientation_read_from_ifd0() {
+    // Big-endian TIFF header, IFD0 at 8 with ImageWidth then Orientation 8
+    let mut tiff = b"MM\0*\0\0\0\x08\0\x02".to_vec();
+    tiff.extend([0x01, 0x00, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0]);
+    tiff.extend([0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 8, 0, 0]);
+    tiff.extend([0, 0, 0, 0]);
+    let dir = temp_dir("exif-tiff");
+    let path = dir.join("turned.tif");
+    std::fs::write(&path, tiff).unwrap();
+    assert_eq!(exif_orientation(path.to_str().unwrap()).unwrap(), Some(8));
+    // No Orientation tag in a TIFF whose strip is never read
+    assert_eq!(
+        exif_orientation(&fixture("header_only_50000x40000.tif")).unwrap(),
+        None
+    );
+}
+
+fn decode_png_uri(uri: &str) -> image::DynamicImage {
+    let b64 = uri.strip_prefix("data:image/png;base64,").unwrap();
+    image::load_from_memory(&BASE64.decode(b64).unwrap()).unwrap()
+}
+
+#[test]
+fn test_16bit_preview_keeps_full_range() {
+    let dir = temp_dir("preview16");
+    // Values span the full 16-bit range, so any 8-bit step would show
+    let src: image::ImageBuffer<image::Luma<u16>, Vec<u16>> =
+        image::ImageBuffer::from_fn(40, 30, |x, y| image::Luma([(x * 1601 + y * 37) as u16]));
+    for name in ["dem.png", "dem.tif"] {
+        let path = dir.join(name);
+        src.save(&path).unwrap();
+        let path = path.to_str().unwrap();
+
+        let full = decode_png_uri(
+            &load_raster_with_depth(path, PreviewDepth::Sixteen)
+                .unwrap()
+                .data_uri,
+        )
+        .to_luma16();
+        assert_eq!(full, src, "{}", name);
+
+        // Decimated (step 2): preview pixel (i, j) is source (2i, 2j)
+        let preview = decode_png_uri(
+            &load_raster_preview_with_depth(path, 20, PreviewDepth::Sixteen).unwrap(),
+        );
+        assert!(
+            matches!(preview.color(), image::ColorType::Rgba16),
+            "{}",
+            name
+        );
+        let preview = preview.to_rgba16();
+        assert_eq!(preview.dimensions(), (20, 15));
+        for (i, j) in [(0, 0), (7, 3), (19, 14)] {
+            let v = src.get_pixel(2 * i, 2 * j).0[0];
+            assert_eq!(preview.get_pixel(i, j).0, [v, v, v, u16::MAX], "{}", name);
+        }
+
+        // Default stays 8-bit
+        let eight = decode_png_uri(&load_raster_preview(path, 20).unwrap());
+        assert!(matches!(eight.color(), image::ColorType::Rgba8), "{}", name);
+        let eight = decode_png_uri(&load_raster(path).unwrap().data_uri);
+        assert!(!matches!(
+            eight.color(),
+            image::ColorType::L16 | image::ColorType::Rgba16
+        ));
+    }
+}
+
+#[test]
+fn test_preview_uses_ovr_overview() {
+    // Base is 64x48 of 10; the .ovr holds 32x24 of 200 and 16x12 of 100, so
+    // the pixel value shows which level the preview came from.
+    let path = fixture("ovr/base.tif");
+    let level = |max_dim| {
+        let img = decode_data_uri(&load_raster_preview(&path, max_dim).unwrap());
+        (img.dimensions(), img.get_pixel(0, 0).0[0])
+    };
+    assert_eq!(level(16), ((16, 12), 100));
+    // Smallest level reaching max_dim, then decimated
+    assert_eq!(level(20), ((16, 12), 200));
+    // No overview is large enough
+    assert_eq!(level(64), ((64, 48), 10));
+}
+
+#[test]
+fn test_truncated_ovr_falls_back_to_base() {
+    let dir = temp_dir("bad-ovr");
+    let path = dir.join("base.tif");
+    std::fs::copy(fixture("ovr/base.tif"), &path).unwrap();
+    // Header intact, image data cut off
+    let ovr = std::fs::read(fixture("ovr/base.tif.ovr")).unwrap();
+    std::fs::write(dir.join("base.tif.ovr"), &ovr[..ovr.len() / 3]).unwrap();
+    let img = decode_data_uri(&load_raster_preview(path.to_str().unwrap(), 16).unwrap());
+    assert_eq!(img.dimensions(), (16, 12));
+    assert_eq!(img.get_pixel(0, 0).0[0], 10);
+}
+
+#[test]
+fn test_preview_without_ovr_decimates_base() {
+    let dir = temp_dir("no-ovr");
+    let path = dir.join("base.tif");
+    std::fs::copy(fixture("ovr/base.tif"), &path).unwrap();
+    let img = decode_data_uri(&load_raster_preview(path.to_str().unwrap(), 16).unwrap());
+    assert_eq!(img.dimensions(), (16, 12));
+    assert_eq!(img.get_pixel(0, 0).0[0], 10);
+}
@@ -0,0 +1,72 @@
+use io::reproject_constraints;
+use types::ConstraintKind;
+
+fn pp(id: u64, real: [f64; 2]) -> ConstraintKind {
+    ConstraintKind::PointPair {
+        id,
+        src: [id as f64, 2.0 * id as f64],
+        dst: [10.0 * id as f64, 5.0],
+        dst_real: Some(real),
+        dst_local: Some([1.0, 2.0]),
+        src_z: None,
+        dst_z: None,
+        weight: 1.0,
+    }
+}
+
+fn real(c: &ConstraintKind) -> [f64; 2] {
+    match c {
+        ConstraintKind::PointPair { dst_real, .. } => dst_real.unwrap(),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_reproject_between_utm_zones_round_trip() {
+    // Points near the 10N/11N boundary (-120°), in UTM 10N (NAD83)
+    let list = vec![pp(1, [760000.0, 4300000.0]), pp(2, [740000.0, 4350000.0])];
+    let in_11n = reproject_constraints(&list, "EPSG:26910", "EPSG:26911", None).unwrap();
+    // Far west in zone 11's frame, so well below its 500 km false easting
+    assert!(real(&in_11n[0])[0] < 300000.0);
+    // Pixel coordinates are untouched; without a new origin the old local
+    // plane coordinates are dropped rather than left stale
+    match (&in_11n[0], &list[0]) {
+        (
+            ConstraintKind::PointPair {
+                src: s1,
+                dst: d1,
+                dst_local: l1,
+                ..
+            },
+            ConstraintKind::PointPair {
+                src: s0, dst: d0, ..
+            },
+        ) => {
+            assert_eq!((s1, d1), (s0, d0));
+            assert_eq!(*l1, None);
+        }
+        _ => unreachable!(),
+    }
+
+    let back = reproject_constraints(&in_11n, "EPSG:26911", "EPSG:26910", None).unwrap();
+    for (a, b) in back.iter().zip(&list) {
+        let (ra, rb) = (real(a), real(b));
+        assert!((ra[0] - rb[0]).abs() < 1e-6 && (ra[1] - rb[1]).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_reproject_recomputes_local_plane() {
+    let list = vec![pp(1, [760000.0, 4300000.0]), pp(2, [760000.0, 4301000.0])];
+    let origin = real(&reproject_constraints(&list, "EPSG:26910", "EPSG:26911", None).unwrap()[0]);
+    let out = reproject_constraints(&list, "EPSG:26910", "EPSG:26911", Some(origin)).unwrap();
+    let local = |c: &ConstraintKind| match c {
+        ConstraintKind::PointPair { dst_local, .. } => dst_local.unwrap(),
+        _ => unreachable!(),
+    };
+    let l0 = local(&out[0]);
+    assert!(l0[0].abs() < 1e-6 && l0[1].abs() < 1e-6);
+    // 1 km north in UTM 10N is ~1 km from the origin on the ground
+    let l1 = local(&out[1]);
+    assert!((l1[0].hypot(l1[1]) - 1000.0).abs() < 1.0, "{:?}", l1);
+}
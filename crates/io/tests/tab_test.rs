@@ -0,0 +1,126 @@
+mod common;
+
+use common::temp_dir;
+use io::{
+    mapinfo_coordsys_to_proj, read_georeferencing_detailed, read_tab_for_image, CrsSource,
+    GeorefSource,
+};
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn test_tab_control_points_and_coordsys() {
+    // 2 m pixels; raster (0, 0) is the outer top-left corner at
+    // (430000, 4270000), so the first pixel center is one meter in
+    let image = fixture("tab/scan.tif");
+    let geo = read_tab_for_image(&image).unwrap().unwrap();
+    let expected = [2.0, 0.0, 0.0, -2.0, 430001.0, 4269999.0];
+    for (a, e) in geo.affine.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-6, "{:?}", geo.affine);
+    }
+    // UTM zone 10N written out as transverse Mercator on WGS84
+    let crs = geo.wkt.unwrap();
+    let to_utm = proj::Proj::new_known_crs(&crs, "EPSG:32610", None).unwrap();
+    let (x, y): (f64, f64) = to_utm.convert((431000.0, 4269000.0)).unwrap();
+    assert!((x - 431000.0).abs() < 1e-3 && (y - 4269000.0).abs() < 1e-3);
+
+    // Reported as the source when nothing earlier in the order matches
+    let detail = read_georeferencing_detailed(&image).unwrap().unwrap();
+    assert_eq!(
+        detail.source,
+        GeorefSource::MapInfoTab {
+            path: PathBuf::from(fixture("tab/scan.tab"))
+        }
+    );
+    assert_eq!(
+        detail.crs_source,
+        Some(CrsSource::MapInfoTab {
+            path: PathBuf::from(fixture("tab/scan.tab"))
+        })
+    );
+}
+
+#[test]
+fn test_coordsys_clauses() {
+    assert_eq!(
+        mapinfo_coordsys_to_proj("CoordSys Earth Projection 1, 104").as_deref(),
+        Some("+proj=longlat +datum=WGS84 +no_defs +type=crs")
+    );
+    let lcc = mapinfo_coordsys_to_proj(
+        "CoordSys Earth Projection 3, 74, \"survey ft\", -120.5, 37.6666, 38.4333, 39.8333, 6561666.667, 1640416.667",
+    )
+    .unwrap();
+    assert!(
+        lcc.starts_with("+proj=lcc +lon_0=-120.5 +lat_0=37.6666"),
+        "{}",
+        lcc
+    );
+    assert!(lcc.contains("+units=us-ft") && lcc.contains("+datum=NAD83"));
+    assert_eq!(
+        mapinfo_coordsys_to_proj("CoordSys NonEarth Units \"m\""),
+        None
+    );
+    assert_eq!(
+        mapinfo_coordsys_to_proj("CoordSys Earth Projection 8, 999, \"m\", 0, 0, 1, 0, 0"),
+        None
+    );
+}
+
+#[test]
+fn test_state_plane_feet_false_origin_is_converted_to_meters() {
+    // NAD83 / California zone 2 (ftUS), EPSG:2226: false easting and
+    // northing of 2,000,000 and 500,000 m, given in US survey feet
+    let crs = mapinfo_coordsys_to_proj(
+        "CoordSys Earth Projection 3, 74, \"survey ft\", -122, 37.6666666667, 39.8333333333, 38.3333333333, 6561666.667, 1640416.667",
+    )
+    .unwrap();
+    assert!(
+        crs.contains("+x_0=2000000.000") && crs.contains("+y_0=500000.000"),
+        "{}",
+        crs
+    );
+    let to_epsg = proj::Proj::new_known_crs(&crs, "EPSG:2226", None).unwrap();
+    let (x, y): (f64, f64) = to_epsg.convert((6_050_000.0, 2_120_000.0)).unwrap();
+    assert!(
+        (x - 6_050_000.0).abs() < 0.01 && (y - 2_120_000.0).abs() < 0.01,
+        "{} {}",
+        x,
+        y
+    );
+}
+
+#[test]
+fn test_degenerate_tab_control_points_are_rejected() {
+    let dir = temp_dir("tab-degenerate");
+    let image = dir.join("scan.tif").to_string_lossy().into_owned();
+    let tab = |points: &str| {
+        std::fs::write(
+            dir.join("scan.tab"),
+            format!(
+                "!table\nDefinition Table\n  File \"scan.tif\"\n  Type \"RASTER\"\n{}",
+                points
+            ),
+        )
+        .unwrap()
+    };
+    // Collinear raster points
+    tab(
+        "  (0,0) (0,0) Label \"1\",\n  (10,10) (5,5) Label \"2\",\n  (20,20) (10,10) Label \"3\"\n",
+    );
+    let err = read_tab_for_image(&image).unwrap_err();
+    assert!(err.to_string().contains("collinear"), "{}", err);
+    // Spread-out raster points on a line of map points: singular affine
+    tab(
+        "  (0,0) (0,0) Label \"1\",\n  (10,10) (10,0) Label \"2\",\n  (20,20) (0,10) Label \"3\"\n",
+    );
+    let err = read_tab_for_image(&image).unwrap_err();
+    assert!(err.to_string().contains("singular"), "{}", err);
+    assert!(read_georeferencing_detailed(&image).is_err());
+}
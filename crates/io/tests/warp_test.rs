@@ -0,0 +1,174 @@
+mod common;
+
+use approx::assert_relative_eq;
+use common::temp_dir;
+use io::{
+    read_world_file_for_image, warp_grid, warp_image, warp_raster, warp_raster_tiled, WarpGrid,
+    WarpOptions,
+};
+
+/// Assert `value` is a whole multiple of `step`.
+fn assert_multiple(value: f64, step: f64) {
+    let k = value / step;
+    assert_relative_eq!(k, k.round(), epsilon = 1e-9);
+}
+
+#[test]
+fn test_warp_grid_snaps_origin_at_requested_resolution() {
+    // 100x50 image, 2 units/px, top-left pixel center at (1001.3, 5003.7)
+    let src = [2.0, 0.0, 0.0, -2.0, 1001.3, 5003.7];
+    let opts = WarpOptions {
+        resolution: Some(5.0),
+        snap_origin: true,
+        ..Default::default()
+    };
+    let grid = warp_grid(src, 100, 50, &opts).unwrap();
+    assert_eq!(grid.affine[0], 5.0);
+    assert_eq!(grid.affine[3], -5.0);
+    // Outer corner (center minus half a pixel) sits on the 5-unit lattice
+    let left = grid.affine[4] - 2.5;
+    let top = grid.affine[5] + 2.5;
+    assert_multiple(left, 5.0);
+    assert_multiple(top, 5.0);
+    // Grid still covers the source extent [1000.3, 4904.7, 1200.3, 5004.7]
+    assert!(left <= 1000.3 && top >= 5004.7);
+    assert!(left + 5.0 * grid.width as f64 >= 1200.3);
+    assert!(top - 5.0 * grid.height as f64 <= 4904.7);
+    assert_eq!((grid.width, grid.height), (41, 21));
+}
+
+#[test]
+fn test_warp_grid_explicit_size_and_native_default() {
+    let src = [2.0, 0.0, 0.0, -2.0, 1001.0, 5003.0];
+    let sized = warp_grid(
+        src,
+        100,
+        50,
+        &WarpOptions {
+            size: Some((50, 25)),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!((sized.width, sized.height), (50, 25));
+    assert_relative_eq!(sized.affine[0], 4.0, epsilon = 1e-12);
+
+    let native = warp_grid(src, 100, 50, &WarpOptions::default()).unwrap();
+    assert_eq!((native.width, native.height), (100, 50));
+    assert_eq!(native.affine, src);
+}
+
+#[test]
+fn test_warp_raster_writes_image_and_world_file() {
+    let dir = temp_dir("warp");
+    let src_path = dir.join("src.png");
+    image::RgbaImage::from_pixel(8, 4, image::Rgba([10, 20, 30, 255]))
+        .save(&src_path)
+        .unwrap();
+    let out = dir.join("out.png");
+    let out = out.to_str().unwrap();
+    // Rotated 90°: output grid is 4 wide, 8 tall
+    let src_affine = [0.0, 1.0, -1.0, 0.0, 0.0, 0.0];
+    let grid = warp_raster(
+        src_path.to_str().unwrap(),
+        src_affine,
+        out,
+        &WarpOptions::default(),
+    )
+    .unwrap();
+    assert_eq!((grid.width, grid.height), (4, 8));
+    let img = image::open(out).unwrap().to_rgba8();
+    assert_eq!(img.dimensions(), (4, 8));
+    assert_eq!(img.get_pixel(1, 1).0, [10, 20, 30, 255]);
+    assert_eq!(read_world_file_for_image(out).unwrap(), Some(grid.affine));
+}
+
+#[test]
+fn test_warp_outside_source_is_nodata() {
+    let src = image::RgbaImage::from_pixel(4, 4, image::Rgba([200, 100, 50, 255]));
+    // Identity placement, but the output grid extends 4 px past the right edge
+    let src_affine = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+    let grid = WarpGrid {
+        affine: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+        width: 8,
+        height: 4,
+    };
+    let out = warp_image(&src, src_affine, &grid, [0, 0, 0, 0]).unwrap();
+    for y in 0..4 {
+        assert_eq!(out.get_pixel(3, y).0, [200, 100, 50, 255]);
+        assert_eq!(out.get_pixel(4, y).0, [0, 0, 0, 0]);
+        assert_eq!(out.get_pixel(7, y).0, [0, 0, 0, 0]);
+    }
+    let out = warp_image(&src, src_affine, &grid, [255, 0, 255, 255]).unwrap();
+    assert_eq!(out.get_pixel(6, 2).0, [255, 0, 255, 255]);
+}
+
+#[test]
+fn test_warp_raster_rotation_corners_transparent() {
+    let dir = temp_dir("warp-nodata");
+    let src_path = dir.join("src.png");
+    image::RgbaImage::from_pixel(20, 20, image::Rgba([1, 2, 3, 255]))
+        .save(&src_path)
+        .unwrap();
+    let out = dir.join("rot.png");
+    let (c, s) = (45f64.to_radians().cos(), 45f64.to_radians().sin());
+    warp_raster(
+        src_path.to_str().unwrap(),
+        [c, -s, s, c, 0.0, 0.0],
+        out.to_str().unwrap(),
+        &WarpOptions::default(),
+    )
+    .unwrap();
+    let img = image::open(&out).unwrap().to_rgba8();
+    let (w, h) = img.dimensions();
+    // The rotated square's bounding-box corners are outside the source
+    assert_eq!(img.get_pixel(0, 0).0[3], 0);
+    assert_eq!(img.get_pixel(w - 1, h - 1).0[3], 0);
+    assert_eq!(img.get_pixel(w / 2, h / 2).0, [1, 2, 3, 255]);
+}
+
+#[test]
+fn test_warp_raster_tiled_matches_single_shot() {
+    let dir = temp_dir("warp-tiled");
+    let src_path = dir.join("src.png");
+    let src = image::RgbaImage::from_fn(37, 23, |x, y| {
+        image::Rgba([(x * 7) as u8, (y * 11) as u8, (x ^ y) as u8, 255])
+    });
+    src.save(&src_path).unwrap();
+    // Rotated, so tiles straddle nodata and image content
+    let (c, s) = (30f64.to_radians().cos(), 30f64.to_radians().sin());
+    let affine = [2.0 * c, -2.0 * s, 2.0 * s, 2.0 * c, 500.0, 800.0];
+    let opts = WarpOptions {
+        nodata: Some([9, 9, 9, 9]),
+        ..Default::default()
+    };
+    let out = dir.join("tiled.tif");
+    // Tile edge chosen not to divide the output size
+    let grid = warp_raster_tiled(
+        src_path.to_str().unwrap(),
+        affine,
+        out.to_str().unwrap(),
+        &opts,
+        7,
+    )
+    .unwrap();
+    assert!(grid.width % 7 != 0 || grid.height % 7 != 0);
+    let expected = warp_image(&src, affine, &grid, [9, 9, 9, 9]).unwrap();
+    let tiled = image::open(&out).unwrap().to_rgba8();
+    assert_eq!(tiled.dimensions(), expected.dimensions());
+    assert_eq!(tiled.as_raw(), expected.as_raw());
+    assert_eq!(
+        read_world_file_for_image(out.to_str().unwrap()).unwrap(),
+        Some(grid.affine)
+    );
+
+    let png = dir.join("tiled.png");
+    assert!(warp_raster_tiled(
+        src_path.to_str().unwrap(),
+        affine,
+        png.to_str().unwrap(),
+        &opts,
+        7
+    )
+    .is_err());
+}
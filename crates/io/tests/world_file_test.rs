@@ -0,0 +1,240 @@
+mod common;
+
+use common::temp_dir;
+use io::{
+    flip_affine_rows, parse_world_file, read_georeferencing_detailed, read_world_file,
+    read_world_file_for_image, world_file_contents, world_file_contents_with_precision,
+    write_world_file_for_image, write_world_file_with_precision, CrsSource, GeorefSource,
+};
+use std::path::PathBuf;
+
+#[test]
+fn test_write_world_file_for_image_uses_matching_sidecar() {
+    let dir = temp_dir("wld-ext");
+    let affine = [0.25, 0.0, 0.0, -0.25, 500.0, 900.0];
+    for (image, sidecar) in [
+        ("map.png", "map.pgw"),
+        ("scan.JPG", "scan.jgw"),
+        ("ortho.tif", "ortho.tfw"),
+        ("sheet.gif", "sheet.gfw"),
+        ("raw.bmp", "raw.bpw"),
+        ("plate.xyz", "plate.wld"),
+    ] {
+        let image_path = dir.join(image).to_string_lossy().into_owned();
+        let written = write_world_file_for_image(&image_path, affine).unwrap();
+        assert_eq!(written, dir.join(sidecar));
+        assert!(written.exists());
+        let read = read_world_file_for_image(&image_path).unwrap();
+        assert_eq!(read, Some(affine));
+    }
+}
+
+#[test]
+fn test_alternate_tiff_world_file_extension_is_found() {
+    // Only `ortho.tifw` exists next to the (absent) image
+    let image = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tifw/ortho.tif");
+    let read = read_world_file_for_image(image.to_str().unwrap()).unwrap();
+    assert_eq!(read, Some([2.0, 0.0, 0.0, -2.0, 431000.0, 5270000.0]));
+}
+
+#[test]
+fn test_alias_sidecars_are_found() {
+    let dir = temp_dir("wld-alias");
+    let affine = [0.5, 0.0, 0.0, -0.5, 10.0, 20.0];
+    let contents: String = affine.iter().map(|v| format!("{}\n", v)).collect();
+    for (image, sidecar) in [
+        ("a.tif", "a.twf"),
+        ("b.png", "b.pngw"),
+        ("c.jpeg", "c.jpgw"),
+    ] {
+        std::fs::write(dir.join(sidecar), &contents).unwrap();
+        let image_path = dir.join(image).to_string_lossy().into_owned();
+        assert_eq!(
+            read_world_file_for_image(&image_path).unwrap(),
+            Some(affine),
+            "{}",
+            sidecar
+        );
+    }
+}
+
+#[test]
+fn test_detailed_read_reports_world_file_source() {
+    let dir = temp_dir("wld-source");
+    let image_path = dir.join("map.png").to_string_lossy().into_owned();
+    assert!(read_georeferencing_detailed(&image_path).unwrap().is_none());
+    let affine = [0.5, 0.0, 0.0, -0.5, 1000.0, 2000.0];
+    write_world_file_for_image(&image_path, affine).unwrap();
+    std::fs::write(dir.join("map.prj"), "EPSG:32610").unwrap();
+    let detail = read_georeferencing_detailed(&image_path).unwrap().unwrap();
+    assert_eq!(detail.georef.affine, affine);
+    assert_eq!(detail.georef.wkt.as_deref(), Some("EPSG:32610"));
+    assert_eq!(
+        detail.source,
+        GeorefSource::WorldFile {
+            path: dir.join("map.pgw")
+        }
+    );
+    assert_eq!(
+        detail.crs_source,
+        Some(CrsSource::Prj {
+            path: dir.join("map.prj")
+        })
+    );
+    // No .prj: no CRS and no CRS source
+    std::fs::remove_file(dir.join("map.prj")).unwrap();
+    let detail = read_georeferencing_detailed(&image_path).unwrap().unwrap();
+    assert!(detail.georef.wkt.is_none());
+    assert!(detail.crs_source.is_none());
+}
+
+#[test]
+fn test_world_file_precision_is_respected() {
+    let affine = [
+        0.123456789012345,
+        -1.0e-7 / 3.0,
+        0.0,
+        -0.123456789012345,
+        431234.56789012345,
+        5270000.0,
+    ];
+    let text = world_file_contents_with_precision(affine, Some(10));
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "0.1234567890");
+    assert_eq!(lines[1], "-0.00000003333333333");
+    assert_eq!(lines[2], "0");
+    assert_eq!(lines[4], "431234.5679");
+    assert_eq!(lines[5], "5270000.000");
+    let back = parse_world_file(&text).unwrap();
+    for (b, a) in back.iter().zip(&affine) {
+        assert!((b - a).abs() <= 1e-9 * a.abs(), "{} vs {}", b, a);
+    }
+    // The default stays lossless
+    assert_eq!(
+        world_file_contents_with_precision(affine, None),
+        world_file_contents(affine)
+    );
+    assert_eq!(
+        parse_world_file(&world_file_contents(affine)).unwrap(),
+        affine
+    );
+
+    let dir = temp_dir("wld-precision");
+    let base = dir.join("ortho").to_string_lossy().into_owned();
+    write_world_file_with_precision(&base, affine, Some(4)).unwrap();
+    let read = read_world_file(&base).unwrap();
+    // Integer digits are kept even past the requested precision
+    assert_eq!(read[4], 431235.0);
+}
+
+#[test]
+fn test_precision_rounding_that_gains_a_digit() {
+    let affine = [9.99996, 0.0, 0.0, -0.099996, 99999.6, 0.5];
+    let text = world_file_contents_with_precision(affine, Some(4));
+    let lines: Vec<&str> = text.lines().collect();
+    // Still 4 significant digits after carrying into the next power of ten
+    assert_eq!(lines[0], "10.00");
+    assert_eq!(lines[3], "-0.1000");
+    // No decimals left to give back: the integer part wins
+    assert_eq!(lines[4], "100000");
+    assert_eq!(lines[5], "0.5000");
+}
+
+#[test]
+fn test_zero_scale_world_file_errors_on_read() {
+    let dir = temp_dir("wld-zero");
+    let image_path = dir.join("flat.png").to_string_lossy().into_owned();
+    std::fs::write(dir.join("flat.pgw"), "0\n0\n0\n-1\n500\n900\n").unwrap();
+    let err = read_world_file_for_image(&image_path).unwrap_err();
+    assert!(err.to_string().contains("zero pixel size"), "{}", err);
+    assert!(read_georeferencing_detailed(&image_path).is_err());
+    // A valid source behind the broken world file is still used
+    std::fs::write(
+        dir.join("flat.png.aux.xml"),
+        "<PAMDataset><GeoTransform>100, 2, 0, 900, 0, -2</GeoTransform></PAMDataset>",
+    )
+    .unwrap();
+    let detail = read_georeferencing_detailed(&image_path).unwrap().unwrap();
+    assert_eq!(
+        detail.source,
+        GeorefSource::AuxXml {
+            path: dir.join("flat.png.aux.xml")
+        }
+    );
+    assert_eq!(detail.georef.affine, [2.0, 0.0, 0.0, -2.0, 101.0, 899.0]);
+    assert_eq!(detail.warnings.len(), 1);
+    assert!(
+        detail.warnings[0].contains("zero pixel size"),
+        "{:?}",
+        detail.warnings
+    );
+
+    // Parallel pixel axes: nonzero sizes but a singular mapping
+    assert!(parse_world_file("1\n2\n2\n4\n0\n0\n").is_err());
+    assert!(parse_world_file("1\n0\n0\n-1\nNaN\n0\n").is_err());
+    assert!(parse_world_file("0.5\n0.01\n-0.02\n-0.5\n0\n0\n").is_ok());
+}
+
+#[test]
+fn test_invalid_world_file_does_not_hide_later_candidate() {
+    let dir = temp_dir("wld-fallthrough");
+    let image_path = dir.join("scan.tif").to_string_lossy().into_owned();
+    // `.tfw` is tried first but is singular; the generic `.wld` is fine
+    std::fs::write(dir.join("scan.tfw"), "0\n0\n0\n-1\n500\n900\n").unwrap();
+    std::fs::write(dir.join("scan.wld"), "2\n0\n0\n-2\n500\n900\n").unwrap();
+    assert_eq!(
+        read_world_file_for_image(&image_path).unwrap(),
+        Some([2.0, 0.0, 0.0, -2.0, 500.0, 900.0])
+    );
+    let detail = read_georeferencing_detailed(&image_path).unwrap().unwrap();
+    assert_eq!(
+        detail.source,
+        GeorefSource::WorldFile {
+            path: dir.join("scan.wld")
+        }
+    );
+    // With no usable candidate the first one's error is reported
+    std::fs::remove_file(dir.join("scan.wld")).unwrap();
+    let err = read_world_file_for_image(&image_path).unwrap_err();
+    assert!(err.to_string().contains("scan.tfw"), "{}", err);
+}
+
+#[test]
+fn test_flipped_rows_negate_e_and_keep_pixels_in_place() {
+    let down = [0.5, 0.01, -0.02, -0.5, 431235.0, 5270000.0];
+    let up = flip_affine_rows(down, 200);
+    assert!(down[3] < 0.0 && up[3] > 0.0);
+    assert_eq!(up[1], -down[1]);
+
+    // Row r from the top is row 199 - r from the bottom: same ground point
+    let world =
+        |t: [f64; 6], c: f64, r: f64| [t[0] * c + t[1] * r + t[4], t[2] * c + t[3] * r + t[5]];
+    for (c, r) in [(0.0, 0.0), (37.0, 12.0), (99.0, 199.0)] {
+        let a = world(down, c, r);
+        let b = world(up, c, 199.0 - r);
+        assert!((a[0] - b[0]).abs() < 1e-9 && (a[1] - b[1]).abs() < 1e-9);
+    }
+    let back = flip_affine_rows(up, 200);
+    for (x, y) in back.iter().zip(down) {
+        assert!((x - y).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_single_line_world_file_is_read() {
+    let image = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/oneline/scan.jpg");
+    let affine = read_world_file_for_image(&image.to_string_lossy()).unwrap();
+    assert_eq!(affine, Some([0.5, 0.0, 0.0, -0.5, 431235.25, 5270010.75]));
+
+    assert_eq!(
+        parse_world_file("2 0 0 -2 431000 5270000\n").unwrap(),
+        [2.0, 0.0, 0.0, -2.0, 431000.0, 5270000.0]
+    );
+    let err = parse_world_file("2, 0, 0, -2, 431000").unwrap_err();
+    assert!(err.to_string().contains("six values"), "{}", err);
+    // Six-line files are still read line by line
+    assert_eq!(
+        parse_world_file("2.0\n0.0\n0.0\n-2.0\n431000.0\n5270000.0\n").unwrap()[4],
+        431000.0
+    );
+}
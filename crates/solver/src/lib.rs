@@ -1,7 +1,60 @@
 use anyhow::{anyhow, Result};
-use nalgebra::{Matrix2, Vector2, SVD};
-use rand::seq::SliceRandom;
-use types::{Affine, ConstraintKind, Similarity};
+use nalgebra::{Matrix2, Matrix3, SMatrix, SVector, Vector2, SVD};
+use types::{
+    Affine, ConstraintKind, Ffd, Homography, QualityMetrics, Similarity, Tps, TransformKind,
+    TransformStack,
+};
+
+/// Typed failures a caller may want to tell apart; returned inside
+/// `anyhow::Error`, so match with `err.downcast_ref::<SolverError>()`.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum SolverError {
+    /// The point configuration does not determine the transform (e.g.
+    /// collinear or coincident source points).
+    #[error("degenerate point configuration: effective rank {rank} of {required} (points collinear or coincident?)")]
+    Degenerate { rank: usize, required: usize },
+    /// Fewer point pairs than the method's degrees of freedom require.
+    #[error("{method} needs ≥{required} pairs; got {got}")]
+    TooFewPairs {
+        method: String,
+        required: usize,
+        got: usize,
+    },
+    /// The fit produced NaN or infinite parameters (e.g. from coordinates so
+    /// large that intermediate sums overflow).
+    #[error("{0} fit produced non-finite parameters")]
+    Numerical(String),
+    /// A method name `min_pairs` does not know.
+    #[error("unknown method {0}")]
+    UnknownMethod(String),
+}
+
+/// Minimum point pairs that determine `method`: similarity 2, affine 3,
+/// homography 4, second-order polynomial (`polynomial2`) 6 and TPS 3 (its
+/// affine part). None for an unknown method.
+pub fn min_pairs(method: &str) -> Option<usize> {
+    match method {
+        "similarity" => Some(2),
+        "affine" | "tps" => Some(3),
+        "homography" => Some(4),
+        "polynomial2" => Some(6),
+        _ => None,
+    }
+}
+
+/// Check `n` usable pairs against `min_pairs(method)`. Every command that
+/// fits a model goes through this, so the minimums live in one place.
+pub fn can_solve(method: &str, n: usize) -> std::result::Result<(), SolverError> {
+    match min_pairs(method) {
+        None => Err(SolverError::UnknownMethod(method.to_string())),
+        Some(required) if n < required => Err(SolverError::TooFewPairs {
+            method: method.to_string(),
+            required,
+            got: n,
+        }),
+        _ => Ok(()),
+    }
+}
 
 pub trait Transform {
     fn apply(&self, point: &Vector2<f64>) -> Vector2<f64>;
@@ -31,30 +84,171 @@ impl Transform for Affine {
     }
 }
 
+impl Transform for Homography {
+    fn apply(&self, point: &Vector2<f64>) -> Vector2<f64> {
+        let h = &self.params;
+        let w = h[6] * point.x + h[7] * point.y + h[8];
+        Vector2::new(
+            (h[0] * point.x + h[1] * point.y + h[2]) / w,
+            (h[3] * point.x + h[4] * point.y + h[5]) / w,
+        )
+    }
+}
+
+/// Thin-plate spline: the affine part plus sum w_i U(|p - c_i| / scale) with
+/// U(r) = r^2 ln r. An unfitted spline (no weights) applies only `affine`,
+/// which deserializes to the identity when absent.
+impl Transform for Tps {
+    fn apply(&self, point: &Vector2<f64>) -> Vector2<f64> {
+        let [a, b, c, d, tx, ty] = self.affine;
+        let mut out = Vector2::new(
+            a * point.x + b * point.y + tx,
+            c * point.x + d * point.y + ty,
+        );
+        let unit2 = self.scale * self.scale;
+        for (cp, w) in self.control_points.iter().zip(&self.weights) {
+            let r2 = (point - Vector2::from(*cp)).norm_squared() / unit2;
+            if r2 > 0.0 {
+                out += Vector2::from(*w) * (0.5 * r2 * r2.ln());
+            }
+        }
+        out
+    }
+}
+
+/// Cubic B-spline free-form deformation: the point plus displacements
+/// interpolated over the control lattice. `control_points` are the evenly
+/// spaced lattice nodes, row-major over `grid_size` [nx, ny]; node indices
+/// past the lattice edge are clamped. Identity until displacements are fitted.
+impl Transform for Ffd {
+    fn apply(&self, point: &Vector2<f64>) -> Vector2<f64> {
+        let [nx, ny] = self.grid_size;
+        let n = nx * ny;
+        if nx < 2 || ny < 2 || self.control_points.len() != n || self.displacements.len() != n {
+            return *point;
+        }
+        let origin = self.control_points[0];
+        let dx = self.control_points[1][0] - origin[0];
+        let dy = self.control_points[nx][1] - origin[1];
+        if dx == 0.0 || dy == 0.0 {
+            return *point;
+        }
+        let (gx, gy) = ((point.x - origin[0]) / dx, (point.y - origin[1]) / dy);
+        let (ix, iy) = (gx.floor(), gy.floor());
+        let (bu, bv) = (cubic_bspline_basis(gx - ix), cubic_bspline_basis(gy - iy));
+        let mut disp = Vector2::zeros();
+        for (m, bm) in bv.iter().enumerate() {
+            let j = (iy as i64 + m as i64 - 1).clamp(0, ny as i64 - 1) as usize;
+            for (l, bl) in bu.iter().enumerate() {
+                let i = (ix as i64 + l as i64 - 1).clamp(0, nx as i64 - 1) as usize;
+                disp += Vector2::from(self.displacements[j * nx + i]) * (bl * bm);
+            }
+        }
+        point + disp
+    }
+}
+
+/// Uniform cubic B-spline weights for the four nodes around local coordinate
+/// `t` in [0, 1); they sum to 1.
+fn cubic_bspline_basis(t: f64) -> [f64; 4] {
+    let (t2, t3) = (t * t, t * t * t);
+    [
+        (1.0 - t).powi(3) / 6.0,
+        (3.0 * t3 - 6.0 * t2 + 4.0) / 6.0,
+        (-3.0 * t3 + 3.0 * t2 + 3.0 * t + 1.0) / 6.0,
+        t3 / 6.0,
+    ]
+}
+
+/// A stack applies its transforms in order, first element first.
+impl Transform for TransformStack {
+    fn apply(&self, point: &Vector2<f64>) -> Vector2<f64> {
+        self.transforms.iter().fold(*point, |p, t| t.apply(&p))
+    }
+}
+
+impl Transform for TransformKind {
+    fn apply(&self, point: &Vector2<f64>) -> Vector2<f64> {
+        match self {
+            TransformKind::Similarity(t) => t.apply(point),
+            TransformKind::Affine(t) => t.apply(point),
+            TransformKind::Homography(t) => t.apply(point),
+            TransformKind::Tps(t) => t.apply(point),
+            TransformKind::Ffd(t) => t.apply(point),
+        }
+    }
+}
+
 pub fn fit_similarity_from_pairs(pairs: &[([f64; 2], [f64; 2])]) -> Result<Similarity> {
+    fit_similarity_weighted(pairs, &vec![1.0; pairs.len()])
+}
+
+/// `SolverError::Numerical` unless every parameter of a `model` fit is finite.
+fn check_finite(model: &str, params: &[f64]) -> Result<()> {
+    if params.iter().all(|p| p.is_finite()) {
+        Ok(())
+    } else {
+        Err(SolverError::Numerical(model.to_string()).into())
+    }
+}
+
+/// Check per-pair weights: one finite, non-negative weight per pair with a
+/// positive total.
+fn check_weights(n: usize, weights: &[f64]) -> Result<()> {
+    if weights.len() != n {
+        return Err(anyhow!("{} weights for {} pairs", weights.len(), n));
+    }
+    if weights.iter().any(|w| !(w.is_finite() && *w >= 0.0)) || weights.iter().sum::<f64>() <= 0.0 {
+        return Err(anyhow!(
+            "weights must be finite, non-negative and not all zero"
+        ));
+    }
+    Ok(())
+}
+
+/// Weighted least-squares similarity: minimizes Σ wᵢ |T(srcᵢ) − dstᵢ|² (see
+/// `spatial_decluster_weights`). Unit weights give `fit_similarity_from_pairs`.
+pub fn fit_similarity_weighted(
+    pairs: &[([f64; 2], [f64; 2])],
+    weights: &[f64],
+) -> Result<Similarity> {
     let n = pairs.len();
     if n < 2 {
         return Err(anyhow!("At least 2 pairs are required (got {})", n));
     }
+    check_weights(n, weights)?;
 
+    let origin = local_origin(pairs);
     let (src_points, dst_points): (Vec<_>, Vec<_>) = pairs
         .iter()
-        .map(|(s, d)| (Vector2::from(*s), Vector2::from(*d)))
+        .map(|(s, d)| (Vector2::from(*s), Vector2::from(*d) - origin))
         .unzip();
-    let src_centroid: Vector2<f64> = src_points.iter().sum::<Vector2<f64>>() / (n as f64);
-    let dst_centroid: Vector2<f64> = dst_points.iter().sum::<Vector2<f64>>() / (n as f64);
+    let total: f64 = weights.iter().sum();
+    let weighted_mean = |pts: &[Vector2<f64>]| {
+        pts.iter()
+            .zip(weights)
+            .map(|(p, w)| p * *w)
+            .sum::<Vector2<f64>>()
+            / total
+    };
+    let src_centroid = weighted_mean(&src_points);
+    let dst_centroid = weighted_mean(&dst_points);
 
     let centered_src: Vec<_> = src_points.iter().map(|p| p - src_centroid).collect();
     let centered_dst: Vec<_> = dst_points.iter().map(|p| p - dst_centroid).collect();
 
-    let sum_centered_src_sq_norm: f64 = centered_src.iter().map(|p| p.norm_squared()).sum();
+    let sum_centered_src_sq_norm: f64 = centered_src
+        .iter()
+        .zip(weights)
+        .map(|(p, w)| w * p.norm_squared())
+        .sum();
     if !sum_centered_src_sq_norm.is_finite() || sum_centered_src_sq_norm <= f64::EPSILON {
         return Err(anyhow!("Insufficient variance in source points"));
     }
 
     let mut c = Matrix2::zeros();
     for i in 0..n {
-        c += centered_dst[i] * centered_src[i].transpose();
+        c += centered_dst[i] * centered_src[i].transpose() * weights[i];
     }
 
     let svd = SVD::new(c, true, true);
@@ -69,96 +263,1057 @@ pub fn fit_similarity_from_pairs(pairs: &[([f64; 2], [f64; 2])]) -> Result<Simil
     }
 
     let s = (c.transpose() * r).trace() / sum_centered_src_sq_norm;
-    let t = dst_centroid - s * r * src_centroid;
+    let t = origin + (dst_centroid - s * r * src_centroid);
     let theta = r.m21.atan2(r.m11);
-    Ok(Similarity {
-        params: [s, theta, t[0], t[1]],
-    })
+    let params = [s, theta, t[0], t[1]];
+    check_finite("similarity", &params)?;
+    Ok(Similarity { params })
+}
+
+/// Least-squares rigid transform: rotation and translation with the scale
+/// locked to one, returned as a `Similarity` with `params[0] == 1`. For
+/// aligning scans of the same resolution. Minimizes Σ |R srcᵢ + t − dstᵢ|²
+/// (orthogonal Procrustes).
+pub fn fit_rigid_from_pairs(pairs: &[([f64; 2], [f64; 2])]) -> Result<Similarity> {
+    let n = pairs.len();
+    if n < 2 {
+        return Err(anyhow!("At least 2 pairs are required (got {})", n));
+    }
+    let origin = local_origin(pairs);
+    let nf = n as f64;
+    let cs = pairs
+        .iter()
+        .map(|p| Vector2::from(p.0))
+        .sum::<Vector2<f64>>()
+        / nf;
+    let cd = pairs
+        .iter()
+        .map(|p| Vector2::from(p.1) - origin)
+        .sum::<Vector2<f64>>()
+        / nf;
+    let mut c = Matrix2::zeros();
+    let mut spread = 0.0;
+    for (src, dst) in pairs {
+        let u = Vector2::from(*src) - cs;
+        c += (Vector2::from(*dst) - origin - cd) * u.transpose();
+        spread += u.norm_squared();
+    }
+    if !spread.is_finite() || spread <= f64::EPSILON {
+        return Err(anyhow!("Insufficient variance in source points"));
+    }
+    // The rotation maximizing tr(Rᵀ C) has angle atan2(C21 − C12, C11 + C22)
+    let theta = (c.m21 - c.m12).atan2(c.m11 + c.m22);
+    let rot = Matrix2::new(theta.cos(), -theta.sin(), theta.sin(), theta.cos());
+    let t = origin + (cd - rot * cs);
+    let params = [1.0, theta, t[0], t[1]];
+    check_finite("rigid", &params)?;
+    Ok(Similarity { params })
+}
+
+/// Similarity fit with the rotation snapped to the nearest multiple of
+/// `snap_deg` (e.g. 90 for scanned grid maps): fit normally, snap θ, then
+/// re-solve scale and translation by linear least squares with θ fixed.
+/// This removes spurious small rotations caused by click noise. `snap_deg`
+/// must be in (0, 180].
+pub fn fit_similarity_snapped(pairs: &[([f64; 2], [f64; 2])], snap_deg: f64) -> Result<Similarity> {
+    if !(snap_deg > 0.0 && snap_deg <= 180.0) {
+        return Err(anyhow!(
+            "snap angle must be in (0, 180] degrees (got {})",
+            snap_deg
+        ));
+    }
+    let free = fit_similarity_from_pairs(pairs)?;
+    let step = snap_deg.to_radians();
+    let theta = ((free.params[1] / step).round() * step).rem_euclid(std::f64::consts::TAU);
+    let theta = if theta > std::f64::consts::PI {
+        theta - std::f64::consts::TAU
+    } else {
+        theta
+    };
+    let origin = local_origin(pairs);
+    let n = pairs.len() as f64;
+    let cs = pairs
+        .iter()
+        .map(|p| Vector2::from(p.0))
+        .sum::<Vector2<f64>>()
+        / n;
+    let cd = pairs
+        .iter()
+        .map(|p| Vector2::from(p.1) - origin)
+        .sum::<Vector2<f64>>()
+        / n;
+    let rot = Matrix2::new(theta.cos(), -theta.sin(), theta.sin(), theta.cos());
+    // With R fixed: min Σ |s R (src - cs) - (dst - cd)|² gives s = Σ (R u)·w / Σ |u|²
+    let (num, den) = pairs.iter().fold((0.0, 0.0), |(num, den), (src, dst)| {
+        let u = rot * (Vector2::from(*src) - cs);
+        let w = Vector2::from(*dst) - origin - cd;
+        (num + u.dot(&w), den + u.norm_squared())
+    });
+    let scale = num / den;
+    let t = origin + (cd - scale * (rot * cs));
+    let params = [scale, theta, t.x, t.y];
+    check_finite("similarity", &params)?;
+    Ok(Similarity { params })
+}
+
+/// Two-point (or more) alignment for quick interactive placement. Returns the
+/// least-squares similarity plus, when exactly two pairs are given, a note
+/// that the fit is exact: two pairs fully determine a similarity, so a zero
+/// RMSE says nothing about accuracy.
+pub fn quick_align(pairs: &[([f64; 2], [f64; 2])]) -> Result<(Similarity, Option<String>)> {
+    can_solve("similarity", pairs.len())?;
+    let t = fit_similarity_from_pairs(pairs)?;
+    let note = (pairs.len() == 2).then(|| {
+        "Exact fit from 2 pairs: residuals are zero by construction; add more points to estimate accuracy"
+            .to_string()
+    });
+    Ok((t, note))
+}
+
+/// Flag point pairs that were likely digitized reference-first (src and dst
+/// reversed). A fully reversed set is still self-consistent: its fit is just
+/// the inverse, and residuals compared in a common unit come out the same
+/// either way, so neither the fitted scale nor the residuals say which side
+/// is which. The image extents do: the pairs are flagged when some src point
+/// lies outside the map image (`map_wh`) or some dst point outside the
+/// reference (`ref_wh`), while every point fits once src and dst are
+/// exchanged. Returns false for images of the same size or with no pairs.
+pub fn detect_swapped(pairs: &[([f64; 2], [f64; 2])], map_wh: [f64; 2], ref_wh: [f64; 2]) -> bool {
+    let inside =
+        |p: &[f64; 2], wh: [f64; 2]| (0.0..=wh[0]).contains(&p[0]) && (0.0..=wh[1]).contains(&p[1]);
+    let fits = |pairs: &[([f64; 2], [f64; 2])]| {
+        pairs
+            .iter()
+            .all(|(s, d)| inside(s, map_wh) && inside(d, ref_wh))
+    };
+    !pairs.is_empty() && !fits(pairs) && fits(&swap_pairs(pairs))
 }
 
+/// Exchange src and dst in every pair.
+pub fn swap_pairs(pairs: &[([f64; 2], [f64; 2])]) -> Vec<([f64; 2], [f64; 2])> {
+    pairs.iter().map(|(s, d)| (*d, *s)).collect()
+}
+
+/// Least-squares affine fit. Both point sets are normalized first (centroid
+/// at the origin, RMS distance √2) so the design matrix stays well conditioned
+/// for very large pixel coordinates; the result is mapped back to the
+/// original frame.
+///
+/// Pairs with NaN/Inf coordinates are skipped, as in `pairs_from_constraints`.
+/// Rank-deficient configurations fail with `SolverError::Degenerate`.
 pub fn fit_affine_from_pairs(pairs: &[([f64; 2], [f64; 2])]) -> Result<Affine> {
+    fit_affine_weighted(pairs, &vec![1.0; pairs.len()])
+}
+
+/// Weighted least-squares affine: minimizes Σ wᵢ |T(srcᵢ) − dstᵢ|² (see
+/// `spatial_decluster_weights`), otherwise as `fit_affine_from_pairs`, which
+/// is this with unit weights. Zero-weight pairs still count toward the
+/// minimum of 3 but do not constrain the fit.
+pub fn fit_affine_weighted(pairs: &[([f64; 2], [f64; 2])], weights: &[f64]) -> Result<Affine> {
+    check_weights(pairs.len(), weights)?;
+    let (pairs, weights): (Vec<_>, Vec<f64>) = pairs
+        .iter()
+        .zip(weights)
+        .filter(|((s, d), _)| s.iter().chain(d.iter()).all(|v| v.is_finite()))
+        .map(|(p, w)| (*p, *w))
+        .unzip();
     let n = pairs.len();
     if n < 3 {
         return Err(anyhow!(
             "At least 3 pairs are required to fit an affine transform."
         ));
     }
+    let origin = local_origin(&pairs);
+    let (cs, ss) = normalization(pairs.iter().map(|p| p.0));
+    let (cd, sd) = normalization(pairs.iter().map(|p| (Vector2::from(p.1) - origin).into()));
     let mut a = nalgebra::DMatrix::<f64>::zeros(2 * n, 6);
     let mut b = nalgebra::DVector::<f64>::zeros(2 * n);
     for i in 0..n {
-        let src = pairs[i].0;
-        let dst = pairs[i].1;
-        a[(2 * i, 0)] = src[0];
-        a[(2 * i, 1)] = src[1];
-        a[(2 * i, 4)] = 1.0;
-        a[(2 * i + 1, 2)] = src[0];
-        a[(2 * i + 1, 3)] = src[1];
-        a[(2 * i + 1, 5)] = 1.0;
-        b[2 * i] = dst[0];
-        b[2 * i + 1] = dst[1];
+        // Rows scaled by √w turn weighted into ordinary least squares
+        let sw = weights[i].sqrt();
+        let src = (Vector2::from(pairs[i].0) - cs) / ss;
+        let dst = (Vector2::from(pairs[i].1) - origin - cd) / sd;
+        a[(2 * i, 0)] = sw * src[0];
+        a[(2 * i, 1)] = sw * src[1];
+        a[(2 * i, 4)] = sw;
+        a[(2 * i + 1, 2)] = sw * src[0];
+        a[(2 * i + 1, 3)] = sw * src[1];
+        a[(2 * i + 1, 5)] = sw;
+        b[2 * i] = sw * dst[0];
+        b[2 * i + 1] = sw * dst[1];
     }
     let decomp = a.svd(true, true);
+    // Inputs are normalized, so a tolerance relative to the largest singular
+    // value separates genuine rank loss from round-off
+    let tol = decomp.singular_values.max() * 1e-9;
+    let rank = decomp.rank(tol);
+    if rank < 6 {
+        return Err(SolverError::Degenerate { rank, required: 6 }.into());
+    }
     let x = decomp.solve(&b, 1e-6).map_err(|e| anyhow!(e.to_string()))?;
-    Ok(Affine {
-        params: [x[0], x[1], x[2], x[3], x[4], x[5]],
+    // dst = sd * (M' (src - cs) / ss + t') + cd + origin
+    let m = Matrix2::new(x[0], x[1], x[2], x[3]) * (sd / ss);
+    let t = origin + (cd + Vector2::new(x[4], x[5]) * sd - m * cs);
+    let params = [m.m11, m.m12, m.m21, m.m22, t.x, t.y];
+    check_finite("affine", &params)?;
+    Ok(Affine { params })
+}
+
+/// Per-pair weights that offset clustering: each pair's weight is inversely
+/// proportional to its local density, estimated as the number of map points
+/// (its own included) within `radius` map pixels of it, a uniform-kernel
+/// count. Weights are scaled to average 1, so an isolated point weighs
+/// as much as a whole cluster of k points inside the radius together. Pass
+/// them to `fit_affine_weighted`/`fit_similarity_weighted`. A non-positive or
+/// non-finite radius yields unit weights.
+pub fn spatial_decluster_weights(pairs: &[([f64; 2], [f64; 2])], radius: f64) -> Vec<f64> {
+    let n = pairs.len();
+    if !(radius.is_finite() && radius > 0.0) {
+        return vec![1.0; n];
+    }
+    let r2 = radius * radius;
+    let inverse_density: Vec<f64> = pairs
+        .iter()
+        .map(|(p, _)| {
+            let neighbors = pairs
+                .iter()
+                .filter(|(q, _)| (p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) <= r2)
+                .count();
+            1.0 / neighbors.max(1) as f64
+        })
+        .collect();
+    let mean = inverse_density.iter().sum::<f64>() / n.max(1) as f64;
+    inverse_density.iter().map(|w| w / mean).collect()
+}
+
+/// Fit a global affine, then a thin-plate spline to what it leaves over: the
+/// TPS control points are the affine-mapped map points and its targets the
+/// residual displacements to `dst`, so the spline models local wobble rather
+/// than the bulk transform. Returns the stack [Affine, Tps], applied in
+/// order. The spline is solved with the control points centered and scaled
+/// to unit extent, which keeps the kernel system well conditioned on large
+/// scans; `Tps::scale` records the unit so `apply` evaluates the same
+/// kernel. `lambda` (≥ 0) is added to that kernel's diagonal, so its effect
+/// does not depend on image size; 0 interpolates the control points
+/// exactly, larger values smooth.
+pub fn fit_affine_then_tps(pairs: &[([f64; 2], [f64; 2])], lambda: f64) -> Result<TransformStack> {
+    can_solve("tps", pairs.len())?;
+    if !(lambda.is_finite() && lambda >= 0.0) {
+        return Err(anyhow!("TPS lambda must be finite and ≥ 0; got {}", lambda));
+    }
+    let affine = fit_affine_from_pairs(pairs)?;
+    let q: Vec<Vector2<f64>> = pairs
+        .iter()
+        .map(|(src, _)| affine.apply(&Vector2::from(*src)))
+        .collect();
+    let n = q.len();
+    // Bounding-box center and larger side: the frame the system is solved in
+    let (lo, hi) = q.iter().fold(
+        (
+            Vector2::repeat(f64::INFINITY),
+            Vector2::repeat(f64::NEG_INFINITY),
+        ),
+        |(lo, hi), p| (lo.inf(p), hi.sup(p)),
+    );
+    let center = (lo + hi) / 2.0;
+    let extent = (hi - lo).max();
+    let scale = if extent.is_finite() && extent > 0.0 {
+        extent
+    } else {
+        1.0
+    };
+    let qn: Vec<Vector2<f64>> = q.iter().map(|p| (p - center) / scale).collect();
+    // [K + λI  P; Pᵀ  0] [w; c] = [r; 0], with U(r) = r² ln r as in `apply`
+    let mut l = nalgebra::DMatrix::<f64>::zeros(n + 3, n + 3);
+    let mut rhs = nalgebra::DMatrix::<f64>::zeros(n + 3, 2);
+    for i in 0..n {
+        for j in 0..n {
+            let r2 = (qn[i] - qn[j]).norm_squared();
+            l[(i, j)] = if r2 > 0.0 { 0.5 * r2 * r2.ln() } else { 0.0 };
+        }
+        l[(i, i)] += lambda;
+        for (k, p) in [1.0, qn[i].x, qn[i].y].into_iter().enumerate() {
+            l[(i, n + k)] = p;
+            l[(n + k, i)] = p;
+        }
+        let r = Vector2::from(pairs[i].1) - q[i];
+        rhs[(i, 0)] = r.x;
+        rhs[(i, 1)] = r.y;
+    }
+    let sol = l
+        .lu()
+        .solve(&rhs)
+        .ok_or_else(|| anyhow!("TPS system is singular (coincident control points?)"))?;
+    let weights = (0..n).map(|i| [sol[(i, 0)], sol[(i, 1)]]).collect();
+    // Identity plus the residual field's own affine part, taken back from
+    // the normalized frame to pixels
+    let (gx, gy) = (
+        Vector2::new(sol[(n + 1, 0)], sol[(n + 2, 0)]) / scale,
+        Vector2::new(sol[(n + 1, 1)], sol[(n + 2, 1)]) / scale,
+    );
+    let tps = Tps {
+        control_points: q.iter().map(|p| [p.x, p.y]).collect(),
+        lambda,
+        weights,
+        affine: [
+            1.0 + gx.x,
+            gx.y,
+            gy.x,
+            1.0 + gy.y,
+            sol[(n, 0)] - gx.dot(&center),
+            sol[(n, 1)] - gy.dot(&center),
+        ],
+        scale,
+    };
+    Ok(TransformStack {
+        transforms: vec![TransformKind::Affine(affine), TransformKind::Tps(tps)],
     })
 }
 
+/// Leverage of each pair in an affine fit: the diagonal of the hat matrix
+/// H = A (AᵀA)⁻¹ Aᵀ for the per-axis design rows [u, v, 1] (both output
+/// axes share it). Values lie in [1/n, 1] and sum to 3; points far from the
+/// others, e.g. alone in a corner, approach 1 and pull the fit toward
+/// themselves. A common flag is leverage above 2·3/n. Only source positions
+/// matter. Errors when the sources are collinear or fewer than 3.
+pub fn leverages(pairs: &[([f64; 2], [f64; 2])]) -> Result<Vec<f64>> {
+    let n = pairs.len();
+    // Centering and scaling the columns leaves H unchanged but keeps AᵀA
+    // well conditioned for large pixel coordinates
+    let (c, s) = normalization(pairs.iter().map(|p| p.0));
+    let rows: Vec<nalgebra::Vector3<f64>> = pairs
+        .iter()
+        .map(|(src, _)| {
+            let p = (Vector2::from(*src) - c) / s;
+            nalgebra::Vector3::new(p.x, p.y, 1.0)
+        })
+        .collect();
+    let ata: Matrix3<f64> = rows.iter().map(|r| r * r.transpose()).sum();
+    let tol = ata.norm() * 1e-9;
+    let rank = SVD::new(ata, false, false).rank(tol);
+    let inv = match ata.try_inverse() {
+        Some(inv) if n >= 3 && rank == 3 => inv,
+        _ => return Err(SolverError::Degenerate { rank, required: 3 }.into()),
+    };
+    Ok(rows.iter().map(|r| (r.transpose() * inv * r)[0]).collect())
+}
+
+/// Total-least-squares (orthogonal regression) affine fit.
+///
+/// `fit_affine_from_pairs` assumes source coordinates are exact and only
+/// destinations are noisy; with noise on both sides that biases the linear
+/// part toward zero (regression dilution). This fit minimizes perpendicular
+/// distances instead, solving each output row x' = a*u + b*v + c as a plane
+/// through the centered (u, v, x') points. Prefer it for image-to-image
+/// registration where neither side is ground truth and both are in pixels
+/// with comparable noise; keep OLS when the destination is a trusted
+/// reference or the two sides have very different units.
+pub fn fit_affine_tls(pairs: &[([f64; 2], [f64; 2])]) -> Result<Affine> {
+    let n = pairs.len();
+    if n < 3 {
+        return Err(anyhow!(
+            "At least 3 pairs are required to fit an affine transform."
+        ));
+    }
+    let nf = n as f64;
+    let cs = pairs
+        .iter()
+        .map(|p| Vector2::from(p.0))
+        .sum::<Vector2<f64>>()
+        / nf;
+    let cd = pairs
+        .iter()
+        .map(|p| Vector2::from(p.1))
+        .sum::<Vector2<f64>>()
+        / nf;
+    let mut row_params = [[0.0; 2]; 2];
+    for (axis, params) in row_params.iter_mut().enumerate() {
+        let mut z = nalgebra::DMatrix::<f64>::zeros(n, 3);
+        for (i, (src, dst)) in pairs.iter().enumerate() {
+            z[(i, 0)] = src[0] - cs.x;
+            z[(i, 1)] = src[1] - cs.y;
+            z[(i, 2)] = dst[axis] - cd[axis];
+        }
+        // Non-finite or overflowing coordinates are caught before the SVD,
+        // which is not guaranteed to terminate on NaN
+        check_finite("TLS affine", z.as_slice())?;
+        let svd = z.svd(false, true);
+        let v_t = svd.v_t.ok_or_else(|| anyhow!("SVD V_t matrix not found"))?;
+        // Normal of the best-fit plane: right singular vector of the
+        // smallest singular value
+        let w = v_t.row(svd.singular_values.imin());
+        if w[2].abs() < 1e-12 {
+            return Err(anyhow!("Degenerate configuration for TLS affine fit"));
+        }
+        *params = [-w[0] / w[2], -w[1] / w[2]];
+    }
+    let m = Matrix2::new(
+        row_params[0][0],
+        row_params[0][1],
+        row_params[1][0],
+        row_params[1][1],
+    );
+    let t = cd - m * cs;
+    let params = [m.m11, m.m12, m.m21, m.m22, t.x, t.y];
+    check_finite("TLS affine", &params)?;
+    Ok(Affine { params })
+}
+
+/// Local origin for destination coordinates: the first finite destination,
+/// rounded to a whole unit. Fits subtract it before solving and add it back
+/// to the translation, so world coordinates far from the CRS origin (UTM
+/// northings in the millions) are solved as small offsets: the subtraction
+/// is exact for points of similar magnitude, where the centroid of the raw
+/// coordinates would already carry their rounding error.
+fn local_origin(pairs: &[([f64; 2], [f64; 2])]) -> Vector2<f64> {
+    pairs
+        .iter()
+        .map(|p| p.1)
+        .find(|d| d.iter().all(|v| v.is_finite()))
+        .map(|d| Vector2::new(d[0].round(), d[1].round()))
+        .unwrap_or_else(Vector2::zeros)
+}
+
+/// Centroid and scale that map `points` to zero mean and RMS distance √2
+/// (Hartley normalization). Scale falls back to 1 for coincident points.
+fn normalization(points: impl Iterator<Item = [f64; 2]> + Clone) -> (Vector2<f64>, f64) {
+    let n = points.clone().count().max(1) as f64;
+    let c = points.clone().map(Vector2::from).sum::<Vector2<f64>>() / n;
+    let rms = (points
+        .map(|p| (Vector2::from(p) - c).norm_squared())
+        .sum::<f64>()
+        / n)
+        .sqrt();
+    let scale = if rms.is_finite() && rms > 0.0 {
+        rms / std::f64::consts::SQRT_2
+    } else {
+        1.0
+    };
+    (c, scale)
+}
+
+/// RANSAC over minimal 2-pair samples, refitting on the inliers of the best
+/// sample. With exactly 2 pairs the exact fit is returned directly; when the
+/// number of distinct samples (n choose 2) is at most `max_iters`, each is
+/// tried once in order instead of sampling at random. Random samples are
+/// seeded, so the same pairs always give the same fit.
 pub fn ransac_fit_similarity(
     pairs: &[([f64; 2], [f64; 2])],
     threshold_px: f64,
     max_iters: usize,
 ) -> Result<Similarity> {
-    if pairs.len() < 2 {
-        return Err(anyhow!("RANSAC needs ≥2 pairs; got {}", pairs.len()));
+    ransac_similarity(
+        pairs,
+        threshold_px,
+        max_iters,
+        None,
+        None,
+        fit_similarity_from_pairs,
+    )
+}
+
+/// `ransac_fit_similarity` that also stops once `max_millis` of wall-clock
+/// time have passed, whichever of the iteration count or the time budget
+/// comes first, and refits on the inliers of the best sample so far. At
+/// least one sample is always tried, so even a zero budget returns a model
+/// when one exists; keeps interactive calls responsive however large
+/// `max_iters` is.
+pub fn ransac_fit_similarity_budgeted(
+    pairs: &[([f64; 2], [f64; 2])],
+    threshold_px: f64,
+    max_iters: usize,
+    max_millis: u64,
+) -> Result<Similarity> {
+    // A budget too large to represent is no budget
+    let deadline =
+        std::time::Instant::now().checked_add(std::time::Duration::from_millis(max_millis));
+    ransac_similarity(
+        pairs,
+        threshold_px,
+        max_iters,
+        None,
+        deadline,
+        fit_similarity_from_pairs,
+    )
+}
+
+/// `ransac_fit_similarity` with scale locked to 1: minimal samples and the
+/// inlier refit use `fit_rigid_from_pairs`, so the result is a rotation and
+/// translation only (`params[0] == 1`).
+pub fn ransac_fit_rigid(
+    pairs: &[([f64; 2], [f64; 2])],
+    threshold_px: f64,
+    max_iters: usize,
+) -> Result<Similarity> {
+    ransac_similarity(
+        pairs,
+        threshold_px,
+        max_iters,
+        None,
+        None,
+        fit_rigid_from_pairs,
+    )
+}
+
+/// `ransac_fit_similarity` whose first hypothesis is `seed` (e.g. a coarse
+/// manual alignment), so a good prior wins unless a sample gathers more
+/// inliers.
+pub fn ransac_fit_similarity_seeded(
+    pairs: &[([f64; 2], [f64; 2])],
+    threshold_px: f64,
+    max_iters: usize,
+    seed: &Similarity,
+) -> Result<Similarity> {
+    ransac_similarity(
+        pairs,
+        threshold_px,
+        max_iters,
+        Some(seed),
+        None,
+        fit_similarity_from_pairs,
+    )
+}
+
+fn ransac_similarity(
+    pairs: &[([f64; 2], [f64; 2])],
+    threshold_px: f64,
+    max_iters: usize,
+    seed: Option<&Similarity>,
+    deadline: Option<std::time::Instant>,
+    fit: fn(&PairSlice) -> Result<Similarity>,
+) -> Result<Similarity> {
+    let (t, _) = ransac(
+        pairs,
+        2,
+        threshold_px,
+        max_iters,
+        seed.cloned(),
+        deadline,
+        fit,
+    )?;
+    Ok(t)
+}
+
+/// Empirical spread of similarity parameters across bootstrap resamples.
+/// Arrays follow `Similarity.params` order: [s, θ (rad), tx, ty].
+#[derive(Debug, Clone)]
+pub struct BootstrapStats {
+    /// Resamples that produced a fit (degenerate draws are skipped)
+    pub n_fits: usize,
+    pub mean: [f64; 4],
+    pub std_dev: [f64; 4],
+    /// 95% percentile interval bounds (2.5th and 97.5th percentiles)
+    pub lower: [f64; 4],
+    pub upper: [f64; 4],
+}
+
+/// Bootstrap the similarity fit: resample `pairs` with replacement
+/// `n_resamples` times, refit each, and summarize parameter spread. Rotations
+/// are unwrapped around the full-data fit so spreads near ±π stay small.
+/// `seed` makes the result reproducible.
+pub fn bootstrap_similarity(
+    pairs: &[([f64; 2], [f64; 2])],
+    n_resamples: usize,
+    seed: u64,
+) -> Result<BootstrapStats> {
+    use rand::{Rng, SeedableRng};
+    use std::f64::consts::PI;
+    let base = fit_similarity_from_pairs(pairs)?;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let mut samples: Vec<[f64; 4]> = Vec::with_capacity(n_resamples);
+    let mut draw = Vec::with_capacity(pairs.len());
+    for _ in 0..n_resamples {
+        draw.clear();
+        draw.extend((0..pairs.len()).map(|_| pairs[rng.gen_range(0..pairs.len())]));
+        if let Ok(t) = fit_similarity_from_pairs(&draw) {
+            let mut p = t.params;
+            p[1] = base.params[1] + (p[1] - base.params[1] + PI).rem_euclid(2.0 * PI) - PI;
+            if p.iter().all(|v| v.is_finite()) {
+                samples.push(p);
+            }
+        }
+    }
+    if samples.is_empty() {
+        return Err(anyhow!("bootstrap produced no valid fits"));
+    }
+    let n = samples.len() as f64;
+    let mut stats = BootstrapStats {
+        n_fits: samples.len(),
+        mean: [0.0; 4],
+        std_dev: [0.0; 4],
+        lower: [0.0; 4],
+        upper: [0.0; 4],
+    };
+    for k in 0..4 {
+        let mut col: Vec<f64> = samples.iter().map(|p| p[k]).collect();
+        let mean = col.iter().sum::<f64>() / n;
+        stats.mean[k] = mean;
+        stats.std_dev[k] = (col.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+        col.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let at = |q: f64| col[((col.len() - 1) as f64 * q).round() as usize];
+        stats.lower[k] = at(0.025);
+        stats.upper[k] = at(0.975);
+    }
+    Ok(stats)
+}
+
+/// Global transform model to fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FitMethod {
+    Similarity,
+    Affine,
+}
+
+impl FitMethod {
+    /// Lowercase name, as accepted by `min_pairs` and the CLI.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FitMethod::Similarity => "similarity",
+            FitMethod::Affine => "affine",
+        }
+    }
+}
+
+/// Self-contained outcome of a global solve, the JSON contract shared by the
+/// CLI and the desktop `solve_to_json` command.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SolveResult {
+    pub method: FitMethod,
+    pub transform: TransformKind,
+    pub metrics: QualityMetrics,
+    /// Usable point pairs after `pairs_from_constraints` filtering
+    pub n_pairs: usize,
+}
+
+/// Fit `method` to the point pairs in `constraints` and report metrics in
+/// pixels (see `residual_metrics`). No warnings are added.
+pub fn solve_constraints(constraints: &[ConstraintKind], method: FitMethod) -> Result<SolveResult> {
+    let pairs = pairs_from_constraints(constraints);
+    can_solve(method.as_str(), pairs.len())?;
+    let transform = match method {
+        FitMethod::Similarity => TransformKind::Similarity(fit_similarity_from_pairs(&pairs)?),
+        FitMethod::Affine => TransformKind::Affine(fit_affine_from_pairs(&pairs)?),
+    };
+    let metrics = residual_metrics(&transform, &pairs, constraints);
+    Ok(SolveResult {
+        method,
+        transform,
+        metrics,
+        n_pairs: pairs.len(),
+    })
+}
+
+/// Residual summary of `t` over `pairs` in their dst units, the one used by
+/// every solve and evaluation: RMSE, P90 (sorted residual at index
+/// floor(0.9 n)), per-axis RMSE, the sorted residuals and per-id residuals of
+/// the point pairs in `constraints`. `unit` is left at pixels and no
+/// warnings are added; callers convert and annotate.
+pub fn residual_metrics<T: Transform + Sync>(
+    t: &T,
+    pairs: &PairSlice,
+    constraints: &[ConstraintKind],
+) -> QualityMetrics {
+    let mut residuals = pair_residuals(t, pairs);
+    let rmse = residual_rmse(&residuals);
+    residuals.sort_by(|a, b| a.total_cmp(b));
+    let p90_error = match residuals.len() {
+        0 => 0.0,
+        n => residuals[((n as f64 * 0.9).floor() as usize).min(n - 1)],
+    };
+    let (rmse_x, rmse_y) = axis_rmse(t, pairs);
+    QualityMetrics {
+        rmse,
+        rmse_x,
+        rmse_y,
+        p90_error,
+        residuals,
+        residuals_by_id: residuals_by_id(t, constraints),
+        ..Default::default()
+    }
+}
+
+/// Residual |t(src) - dst| of every point pair in `constraints`, by id.
+pub fn residuals_by_id<T: Transform>(t: &T, constraints: &[ConstraintKind]) -> Vec<(u64, f64)> {
+    constraints
+        .iter()
+        .filter_map(|c| match c {
+            ConstraintKind::PointPair { id, src, dst, .. } => Some((
+                *id,
+                (t.apply(&Vector2::from(*src)) - Vector2::from(*dst)).norm(),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Iterations used by `fit_from_matches` (samples are seeded, so a given
+/// set of matches always gives the same fit).
+pub const MATCH_RANSAC_ITERS: usize = 1000;
+
+/// Result of `fit_from_matches`.
+#[derive(Debug, Clone)]
+pub struct MatchFit {
+    pub transform: TransformKind,
+    /// Indices into the `matches` slice that agree with `transform`
+    pub inliers: Vec<usize>,
+}
+
+/// Fit a transform from feature matches produced by an external detector /
+/// matcher (ORB, SIFT, a learned matcher, ...). `matches` holds
+/// `(src_index, dst_index)` pairs into `src_kps`/`dst_kps` (pixel
+/// coordinates). RANSAC with `ransac_threshold` pixels rejects wrong matches
+/// and the model is refit on all inliers.
+pub fn fit_from_matches(
+    src_kps: &[[f64; 2]],
+    dst_kps: &[[f64; 2]],
+    matches: &[(usize, usize)],
+    method: FitMethod,
+    ransac_threshold: f64,
+) -> Result<MatchFit> {
+    let mut pairs = Vec::with_capacity(matches.len());
+    for (k, &(i, j)) in matches.iter().enumerate() {
+        match (src_kps.get(i), dst_kps.get(j)) {
+            (Some(s), Some(d)) => pairs.push((*s, *d)),
+            _ => return Err(anyhow!("match {} ({}, {}) is out of range", k, i, j)),
+        }
+    }
+    let (transform, inliers) = match method {
+        FitMethod::Similarity => {
+            let (t, inl) = ransac(
+                &pairs,
+                2,
+                ransac_threshold,
+                MATCH_RANSAC_ITERS,
+                None,
+                None,
+                fit_similarity_from_pairs,
+            )?;
+            (TransformKind::Similarity(t), inl)
+        }
+        FitMethod::Affine => {
+            let (t, inl) = ransac(
+                &pairs,
+                3,
+                ransac_threshold,
+                MATCH_RANSAC_ITERS,
+                None,
+                None,
+                fit_affine_from_pairs,
+            )?;
+            (TransformKind::Affine(t), inl)
+        }
+    };
+    Ok(MatchFit { transform, inliers })
+}
+
+/// (src, dst) pixel pairs as taken by the fitters.
+type PairSlice = [([f64; 2], [f64; 2])];
+
+/// Seed for RANSAC's random sampling, so a fit is reproducible run to run.
+const RANSAC_SEED: u64 = 0x5eed;
+
+/// Generic RANSAC: fit minimal samples of `sample_size`, keep the model with
+/// the most pairs within `threshold_px`, and refit on those inliers. Returns
+/// the model and the inlier indices. `prior`, when given, is the first
+/// hypothesis. With exactly `sample_size` pairs the exact fit is returned
+/// directly; when there are at most `max_iters` distinct samples each is
+/// tried once in order, otherwise `max_iters` are drawn with an RNG seeded
+/// from `RANSAC_SEED`. Stops early at `deadline`, after at least one
+/// hypothesis.
+fn ransac<T: Transform>(
+    pairs: &[([f64; 2], [f64; 2])],
+    sample_size: usize,
+    threshold_px: f64,
+    max_iters: usize,
+    prior: Option<T>,
+    deadline: Option<std::time::Instant>,
+    fit: fn(&PairSlice) -> Result<T>,
+) -> Result<(T, Vec<usize>)> {
+    use rand::SeedableRng;
+    let n = pairs.len();
+    if n < sample_size {
+        return Err(anyhow!("RANSAC needs ≥{} pairs; got {}", sample_size, n));
     }
     if !threshold_px.is_finite() || threshold_px <= 0.0 {
         return Err(anyhow!("RANSAC threshold must be positive and finite"));
     }
-    let mut best_inliers = 0usize;
-    let mut best_transform = Similarity {
-        params: [1.0, 0.0, 0.0, 0.0],
+    let inliers_of = |t: &T| -> Vec<usize> {
+        pairs
+            .iter()
+            .enumerate()
+            .filter(|(_, (s, d))| {
+                (t.apply(&Vector2::from(*s)) - Vector2::from(*d)).norm() < threshold_px
+            })
+            .map(|(i, _)| i)
+            .collect()
     };
-    for _ in 0..max_iters {
-        let sample: Vec<_> = pairs
-            .choose_multiple(&mut rand::thread_rng(), 2)
-            .cloned()
-            .collect();
-        if sample.len() < 2 {
-            continue;
-        }
-        if let Ok(transform) = fit_similarity_from_pairs(&sample) {
-            let mut inliers = 0;
-            for (src, dst) in pairs {
-                let src_vec = Vector2::from(*src);
-                let dst_vec = Vector2::from(*dst);
-                let predicted_dst = transform.apply(&src_vec);
-                if (predicted_dst - dst_vec).norm() < threshold_px {
-                    inliers += 1;
-                }
-            }
-            if inliers > best_inliers {
-                best_inliers = inliers;
-                let all_inliers: Vec<_> = pairs
-                    .iter()
-                    .filter(|(src, dst)| {
-                        let src_vec = Vector2::from(*src);
-                        let dst_vec = Vector2::from(*dst);
-                        let predicted_dst = transform.apply(&src_vec);
-                        (predicted_dst - dst_vec).norm() < threshold_px
-                    })
-                    .cloned()
-                    .collect();
-                if let Ok(refit_transform) = fit_similarity_from_pairs(&all_inliers) {
-                    best_transform = refit_transform;
-                }
-            }
+    if n == sample_size {
+        // Every sample is the whole set
+        let t = fit(pairs)?;
+        let inliers = inliers_of(&t);
+        return Ok((t, inliers));
+    }
+    let exhaustive = binomial(n, sample_size).is_some_and(|c| c <= max_iters);
+    let samples: Box<dyn Iterator<Item = Vec<usize>>> = if exhaustive {
+        Box::new(combinations(n, sample_size).into_iter())
+    } else {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(RANSAC_SEED);
+        Box::new(
+            (0..max_iters)
+                .map(move |_| rand::seq::index::sample(&mut rng, n, sample_size).into_vec()),
+        )
+    };
+    let hypotheses = prior.into_iter().chain(samples.filter_map(|idx| {
+        let sample: Vec<_> = idx.iter().map(|&i| pairs[i]).collect();
+        fit(&sample).ok()
+    }));
+    let mut best: Vec<usize> = Vec::new();
+    for t in hypotheses {
+        let inliers = inliers_of(&t);
+        if inliers.len() > best.len() {
+            best = inliers;
+        }
+        if deadline.is_some_and(|d| std::time::Instant::now() >= d) {
+            break;
         }
     }
-    if best_inliers == 0 {
+    if best.len() < sample_size {
         return Err(anyhow!("RANSAC failed to find a model"));
     }
-    Ok(best_transform)
+    let inlier_pairs: Vec<_> = best.iter().map(|&i| pairs[i]).collect();
+    let t = fit(&inlier_pairs)?;
+    let inliers = inliers_of(&t);
+    Ok((t, inliers))
+}
+
+/// n choose k, or None on overflow.
+fn binomial(n: usize, k: usize) -> Option<usize> {
+    if k > n {
+        return Some(0);
+    }
+    (0..k.min(n - k)).try_fold(1usize, |c, i| Some(c.checked_mul(n - i)? / (i + 1)))
+}
+
+/// Every k-subset of 0..n as ascending indices, in lexicographic order.
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut out = Vec::new();
+    let mut idx: Vec<usize> = (0..k).collect();
+    if k > n {
+        return out;
+    }
+    loop {
+        out.push(idx.clone());
+        let Some(i) = (0..k).rev().find(|&i| idx[i] < n - k + i) else {
+            return out;
+        };
+        idx[i] += 1;
+        for j in i + 1..k {
+            idx[j] = idx[j - 1] + 1;
+        }
+    }
+}
+
+/// Gauss-Newton step limit for `refine_from_prior`.
+pub const REFINE_MAX_ITERS: usize = 50;
+
+/// Outcome of `refine_from_prior`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Refinement {
+    pub transform: TransformKind,
+    /// Gauss-Newton steps taken: 0 when the prior already fits exactly, and
+    /// always 0 for similarity/affine, which are solved in closed form
+    pub iterations: usize,
+    /// RMSE (pixels) of the prior and of the refined transform
+    pub prior_rmse: f64,
+    pub rmse: f64,
+    pub warnings: Vec<String>,
+}
+
+/// Refine a known transform (e.g. from a prior project or a coarse manual
+/// alignment) against `pairs`. A homography prior is the starting guess for
+/// Gauss-Newton on its 8 free parameters (in Hartley-normalized
+/// coordinates). Similarity and affine have a unique least-squares optimum,
+/// so they are refit directly and the prior is only checked: a warning is
+/// added when its RMSE is more than twice the refit's plus one pixel. TPS and
+/// FFD priors are not supported yet.
+pub fn refine_from_prior(prior: &TransformKind, pairs: &PairSlice) -> Result<Refinement> {
+    let rmse_of = |t: &TransformKind| residual_rmse(&pair_residuals(t, pairs));
+    let (transform, iterations) = match prior {
+        TransformKind::Similarity(_) => (
+            TransformKind::Similarity(fit_similarity_from_pairs(pairs)?),
+            0,
+        ),
+        TransformKind::Affine(_) => (TransformKind::Affine(fit_affine_from_pairs(pairs)?), 0),
+        TransformKind::Homography(h) => {
+            let (h, iterations) = refine_homography(h, pairs)?;
+            (TransformKind::Homography(h), iterations)
+        }
+        TransformKind::Tps(_) | TransformKind::Ffd(_) => {
+            return Err(anyhow!(
+                "refinement from a TPS or FFD prior is not supported yet"
+            ))
+        }
+    };
+    let (prior_rmse, rmse) = (rmse_of(prior), rmse_of(&transform));
+    let mut warnings = Vec::new();
+    if prior_rmse > 2.0 * rmse + 1.0 {
+        warnings.push(format!(
+            "Prior RMSE is {:.3} px but {:.3} px after refitting; the prior does not match these points",
+            prior_rmse, rmse
+        ));
+    }
+    Ok(Refinement {
+        transform,
+        iterations,
+        prior_rmse,
+        rmse,
+        warnings,
+    })
+}
+
+/// Gauss-Newton for a homography with h22 fixed to 1, started at `prior`.
+fn refine_homography(prior: &Homography, pairs: &PairSlice) -> Result<(Homography, usize)> {
+    can_solve("homography", pairs.len())?;
+    // Similarity normalizations N (forward) and their inverses
+    let norm = |c: Vector2<f64>, s: f64| {
+        (
+            Matrix3::new(
+                1.0 / s,
+                0.0,
+                -c.x / s,
+                0.0,
+                1.0 / s,
+                -c.y / s,
+                0.0,
+                0.0,
+                1.0,
+            ),
+            Matrix3::new(s, 0.0, c.x, 0.0, s, c.y, 0.0, 0.0, 1.0),
+        )
+    };
+    let (cs, ss) = normalization(pairs.iter().map(|p| p.0));
+    let (cd, sd) = normalization(pairs.iter().map(|p| p.1));
+    let ((ns, ns_inv), (nd, nd_inv)) = (norm(cs, ss), norm(cd, sd));
+    let pts: Vec<(Vector2<f64>, Vector2<f64>)> = pairs
+        .iter()
+        .map(|(s, d)| ((Vector2::from(*s) - cs) / ss, (Vector2::from(*d) - cd) / sd))
+        .collect();
+    let mut h = nd * Matrix3::from_row_slice(&prior.params) * ns_inv;
+    if !h[(2, 2)].is_normal() {
+        return Err(anyhow!("prior homography has h22 = 0 after normalization"));
+    }
+    h /= h[(2, 2)];
+    let mut iterations = 0;
+    for _ in 0..REFINE_MAX_ITERS {
+        let mut jtj = SMatrix::<f64, 8, 8>::zeros();
+        let mut jtr = SVector::<f64, 8>::zeros();
+        for (s, d) in &pts {
+            let w = h[(2, 0)] * s.x + h[(2, 1)] * s.y + 1.0;
+            let u = (h[(0, 0)] * s.x + h[(0, 1)] * s.y + h[(0, 2)]) / w;
+            let v = (h[(1, 0)] * s.x + h[(1, 1)] * s.y + h[(1, 2)]) / w;
+            let (x, y) = (s.x / w, s.y / w);
+            let ju = SVector::<f64, 8>::from([x, y, 1.0 / w, 0.0, 0.0, 0.0, -u * x, -u * y]);
+            let jv = SVector::<f64, 8>::from([0.0, 0.0, 0.0, x, y, 1.0 / w, -v * x, -v * y]);
+            jtj += ju * ju.transpose() + jv * jv.transpose();
+            jtr += ju * (u - d.x) + jv * (v - d.y);
+        }
+        if jtr.norm() < 1e-14 {
+            break;
+        }
+        let delta = jtj
+            .cholesky()
+            .ok_or(SolverError::Degenerate {
+                rank: SVD::new(jtj, false, false).rank(1e-12),
+                required: 8,
+            })?
+            .solve(&-jtr);
+        for (k, dk) in delta.iter().enumerate() {
+            h[(k / 3, k % 3)] += dk;
+        }
+        iterations += 1;
+        if delta.norm() < 1e-12 {
+            break;
+        }
+    }
+    let mut out = nd_inv * h * ns;
+    if out[(2, 2)].is_normal() {
+        out /= out[(2, 2)];
+    }
+    let mut params = [0.0; 9];
+    for (k, p) in params.iter_mut().enumerate() {
+        *p = out[(k / 3, k % 3)];
+    }
+    Ok((Homography { params }, iterations))
+}
+
+/// Destination coordinates a global fit targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinateSpace {
+    /// Reference image pixels (`dst`)
+    #[default]
+    Pixels,
+    /// Reference CRS units (`dst_real`)
+    World,
+    /// Local meter plane around the reference origin (`dst_local`); residuals
+    /// come out in meters and pixel anisotropy drops out
+    LocalMeters,
+}
+
+/// `constraints` with each point pair's `dst` replaced by its coordinates in
+/// `space`; other constraints are kept as is. Errors naming the point pairs
+/// that lack the chosen coordinates (e.g. added before the reference was
+/// georeferenced).
+pub fn constraints_in_space(
+    constraints: &[ConstraintKind],
+    space: CoordinateSpace,
+) -> Result<Vec<ConstraintKind>> {
+    let mut missing = Vec::new();
+    let out = constraints
+        .iter()
+        .map(|c| match c {
+            ConstraintKind::PointPair {
+                id,
+                dst_real,
+                dst_local,
+                ..
+            } => {
+                let coords = match space {
+                    CoordinateSpace::Pixels => return c.clone(),
+                    CoordinateSpace::World => *dst_real,
+                    CoordinateSpace::LocalMeters => *dst_local,
+                };
+                let mut c = c.clone();
+                match (coords, &mut c) {
+                    (Some(p), ConstraintKind::PointPair { dst, .. }) => *dst = p,
+                    _ => missing.push(*id),
+                }
+                c
+            }
+            _ => c.clone(),
+        })
+        .collect();
+    if !missing.is_empty() {
+        return Err(anyhow!(
+            "point pairs {:?} have no {:?} coordinates; set a georeferenced reference and re-add them",
+            missing,
+            space
+        ));
+    }
+    Ok(out)
+}
+
+/// `pairs_from_constraints` with destinations in `space` (see
+/// `constraints_in_space`).
+pub fn pairs_in_space(
+    constraints: &[ConstraintKind],
+    space: CoordinateSpace,
+) -> Result<Vec<([f64; 2], [f64; 2])>> {
+    Ok(pairs_from_constraints(&constraints_in_space(
+        constraints,
+        space,
+    )?))
 }
 
 /// Extract point-pair constraints as (src, dst) pixel-space pairs.
@@ -166,6 +1321,27 @@ pub fn ransac_fit_similarity(
 /// with NaNs/Infs, duplicates (exact equality on all four coordinates), and
 /// degenerate pairs where src == dst within 1e-12 in L2 norm.
 pub fn pairs_from_constraints(constraints: &[ConstraintKind]) -> Vec<([f64; 2], [f64; 2])> {
+    pairs_from_constraints_with_tol(constraints, 0.0)
+}
+
+/// `pairs_from_constraints` where a pair also counts as a duplicate when both
+/// its src and dst lie within `dedup_tol_px` (L2, pixels) of an earlier kept
+/// pair; the first occurrence wins. A tolerance of 0 means exact duplicates
+/// only, and so does a negative or non-finite one.
+///
+/// Each pair is compared with every kept pair, so this is O(n²); fine for
+/// hand-placed control points, but large automatic match sets should be
+/// bucketed on a grid of `dedup_tol_px` cells first.
+pub fn pairs_from_constraints_with_tol(
+    constraints: &[ConstraintKind],
+    dedup_tol_px: f64,
+) -> Vec<([f64; 2], [f64; 2])> {
+    let tol = match dedup_tol_px.is_finite() {
+        true => dedup_tol_px.max(0.0),
+        false => 0.0,
+    };
+    let tol_sq = tol * tol;
+    let near = |a: &[f64; 2], b: &[f64; 2]| (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) <= tol_sq;
     let mut out: Vec<([f64; 2], [f64; 2])> = Vec::new();
     for c in constraints {
         if let ConstraintKind::PointPair { src, dst, .. } = c {
@@ -180,8 +1356,11 @@ pub fn pairs_from_constraints(constraints: &[ConstraintKind]) -> Vec<([f64; 2],
                 continue;
             }
             let pair = (*src, *dst);
-            // exact duplicate filter
-            if out.iter().any(|p| p.0 == pair.0 && p.1 == pair.1) {
+            // duplicate filter (exact when the tolerance is 0)
+            if out
+                .iter()
+                .any(|p| near(&p.0, &pair.0) && near(&p.1, &pair.1))
+            {
                 continue;
             }
             out.push(pair);
@@ -190,40 +1369,969 @@ pub fn pairs_from_constraints(constraints: &[ConstraintKind]) -> Vec<([f64; 2],
     out
 }
 
+/// Minimum `coverage_ratio` for control points to count as well distributed.
+pub const WELL_DISTRIBUTED_COVERAGE: f64 = 0.25;
+
+/// Convex hull of `points` by Andrew's monotone chain: vertices in
+/// counter-clockwise order for a y-up frame (clockwise on screen, where y
+/// points down), starting at the smallest x, without repeating the first.
+/// Empty when there are fewer than three distinct points or all are
+/// collinear, since no polygon encloses them.
+pub fn convex_hull(points: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let mut pts = points.to_vec();
+    pts.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    pts.dedup();
+    if pts.len() < 3 {
+        return Vec::new();
+    }
+    let cross = |o: [f64; 2], a: [f64; 2], b: [f64; 2]| {
+        (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+    };
+    let mut hull: Vec<[f64; 2]> = Vec::with_capacity(2 * pts.len());
+    for &p in &pts {
+        while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    let lower_len = hull.len() + 1;
+    for &p in pts.iter().rev().skip(1) {
+        while hull.len() >= lower_len && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0
+        {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    hull.pop();
+    if hull.len() < 3 {
+        return Vec::new();
+    }
+    hull
+}
+
+/// Fraction of the `img_wh` (width, height) image covered by the convex hull
+/// of the source points, clamped to [0, 1]. Fits are only well constrained
+/// inside this hull; far outside it errors grow quickly.
+pub fn coverage_ratio(pairs: &[([f64; 2], [f64; 2])], img_wh: [f64; 2]) -> f64 {
+    let image_area = img_wh[0] * img_wh[1];
+    if image_area <= 0.0 {
+        return 0.0;
+    }
+    let pts: Vec<[f64; 2]> = pairs.iter().map(|(src, _)| *src).collect();
+    let hull = convex_hull(&pts);
+    if hull.is_empty() {
+        return 0.0;
+    }
+    let twice_area: f64 = (0..hull.len())
+        .map(|i| {
+            let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum();
+    (twice_area.abs() / 2.0 / image_area).clamp(0.0, 1.0)
+}
+
+/// Whether the source points' hull covers at least `WELL_DISTRIBUTED_COVERAGE`
+/// of the image.
+pub fn well_distributed(pairs: &[([f64; 2], [f64; 2])], img_wh: [f64; 2]) -> bool {
+    coverage_ratio(pairs, img_wh) >= WELL_DISTRIBUTED_COVERAGE
+}
+
+/// Image quadrants ("upper-left", "upper-right", "lower-left", "lower-right")
+/// that contain no source point, for telling users where to add points.
+pub fn empty_quadrants(pairs: &[([f64; 2], [f64; 2])], img_wh: [f64; 2]) -> Vec<&'static str> {
+    let names = ["upper-left", "upper-right", "lower-left", "lower-right"];
+    let mut seen = [false; 4];
+    for (src, _) in pairs {
+        let right = src[0] >= img_wh[0] / 2.0;
+        let lower = src[1] >= img_wh[1] / 2.0;
+        seen[(lower as usize) * 2 + right as usize] = true;
+    }
+    names
+        .iter()
+        .zip(seen)
+        .filter(|(_, s)| !s)
+        .map(|(n, _)| *n)
+        .collect()
+}
+
+/// Candidates per image axis searched by `suggest_control_points`.
+pub const SUGGEST_GRID: usize = 16;
+
+/// Up to `n` map pixel locations where new control points would most improve
+/// coverage of the `img_wh` image. Greedy farthest-point sampling: the
+/// candidates form a `SUGGEST_GRID`² lattice inset 5% from the image edges,
+/// and each pick is the candidate farthest from every existing source point
+/// and every earlier pick. Clustered points therefore draw suggestions to the
+/// opposite corners first, then to the largest remaining gaps.
+pub fn suggest_control_points(
+    pairs: &[([f64; 2], [f64; 2])],
+    img_wh: [f64; 2],
+    n: usize,
+) -> Vec<[f64; 2]> {
+    if !(img_wh[0] > 0.0 && img_wh[1] > 0.0) {
+        return Vec::new();
+    }
+    let at = |k: usize, len: f64| len * (0.05 + 0.9 * k as f64 / (SUGGEST_GRID - 1) as f64);
+    let candidates: Vec<[f64; 2]> = (0..SUGGEST_GRID)
+        .flat_map(|j| (0..SUGGEST_GRID).map(move |i| (i, j)))
+        .map(|(i, j)| [at(i, img_wh[0]), at(j, img_wh[1])])
+        .collect();
+    // Squared distance from each candidate to the nearest taken point
+    let mut nearest: Vec<f64> = candidates
+        .iter()
+        .map(|c| {
+            pairs
+                .iter()
+                .map(|(p, _)| (c[0] - p[0]).powi(2) + (c[1] - p[1]).powi(2))
+                .fold(f64::INFINITY, f64::min)
+        })
+        .collect();
+    let mut picks = Vec::new();
+    while picks.len() < n {
+        let Some((best, _)) = nearest
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| **d > 0.0)
+            .max_by(|a, b| a.1.total_cmp(b.1))
+        else {
+            break;
+        };
+        let pick = candidates[best];
+        for (c, d) in candidates.iter().zip(nearest.iter_mut()) {
+            *d = d.min((c[0] - pick[0]).powi(2) + (c[1] - pick[1]).powi(2));
+        }
+        picks.push(pick);
+    }
+    picks
+}
+
+/// Groups of point-pair ids whose destinations coincide (within `tol`
+/// pixels) while their sources differ, a common data-entry error that pins
+/// one reference location to several map locations. Coincidence is
+/// transitive: destinations chained by steps within `tol` form one group, so
+/// the result does not depend on constraint order. Each group is sorted and
+/// has at least two ids, and groups are ordered by their smallest id; exact
+/// duplicate pairs are not reported.
+pub fn duplicate_destinations(constraints: &[ConstraintKind], tol: f64) -> Vec<Vec<u64>> {
+    let points: Vec<(u64, [f64; 2], [f64; 2])> = constraints
+        .iter()
+        .filter_map(|c| match c {
+            ConstraintKind::PointPair { id, src, dst, .. } => Some((*id, *src, *dst)),
+            _ => None,
+        })
+        .collect();
+    let near = |a: [f64; 2], b: [f64; 2]| (a[0] - b[0]).hypot(a[1] - b[1]) <= tol;
+    // Union-find over every pair of destinations within `tol`
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let mut parent: Vec<usize> = (0..points.len()).collect();
+    for i in 0..points.len() {
+        for j in i + 1..points.len() {
+            if near(points[i].2, points[j].2) {
+                let (ri, rj) = (root(&mut parent, i), root(&mut parent, j));
+                parent[ri.max(rj)] = ri.min(rj);
+            }
+        }
+    }
+    let mut groups: std::collections::BTreeMap<usize, Vec<usize>> = Default::default();
+    for i in 0..points.len() {
+        let r = root(&mut parent, i);
+        groups.entry(r).or_default().push(i);
+    }
+    let mut out: Vec<Vec<u64>> = groups
+        .into_values()
+        .filter(|g| {
+            g.iter()
+                .any(|&i| g.iter().any(|&j| !near(points[i].1, points[j].1)))
+        })
+        .map(|g| {
+            let mut ids: Vec<u64> = g.iter().map(|&j| points[j].0).collect();
+            ids.sort_unstable();
+            ids
+        })
+        .collect();
+    out.sort();
+    out
+}
+
+/// Fit a similarity to the point pairs in `constraints`, then reject it if
+/// any pair's residual exceeds `max_acceptable_residual` (pixels). The error
+/// names the worst point so a mis-clicked correspondence can be fixed instead
+/// of silently distorting the fit. `None` disables the check.
+pub fn fit_similarity_validated(
+    constraints: &[ConstraintKind],
+    max_acceptable_residual: Option<f64>,
+) -> Result<Similarity> {
+    let t = fit_similarity_from_pairs(&pairs_from_constraints(constraints))?;
+    check_max_residual(&t, constraints, max_acceptable_residual)?;
+    Ok(t)
+}
+
+/// Affine counterpart of `fit_similarity_validated`.
+pub fn fit_affine_validated(
+    constraints: &[ConstraintKind],
+    max_acceptable_residual: Option<f64>,
+) -> Result<Affine> {
+    let t = fit_affine_from_pairs(&pairs_from_constraints(constraints))?;
+    check_max_residual(&t, constraints, max_acceptable_residual)?;
+    Ok(t)
+}
+
+/// The residual check of `fit_similarity_validated`, for a transform fitted
+/// elsewhere: errors naming the worst point pair in `constraints` when its
+/// residual exceeds `max_acceptable_residual` (pixels). `None` always passes.
+pub fn check_max_residual<T: Transform>(
+    t: &T,
+    constraints: &[ConstraintKind],
+    max_acceptable_residual: Option<f64>,
+) -> Result<()> {
+    let Some(limit) = max_acceptable_residual else {
+        return Ok(());
+    };
+    let worst = constraints
+        .iter()
+        .filter_map(|c| match c {
+            ConstraintKind::PointPair { id, src, dst, .. } => {
+                let r = (t.apply(&Vector2::from(*src)) - Vector2::from(*dst)).norm();
+                r.is_finite().then_some((*id, r))
+            }
+            _ => None,
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    match worst {
+        Some((id, r)) if r > limit => Err(anyhow!(
+            "point {} has residual {:.3} px (limit {}); likely a mistaken correspondence",
+            id,
+            r,
+            limit
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Fit an affine, then repeatedly drop the pair with the largest residual and
+/// refit until every residual is at most `residual_threshold` or only
+/// `min_points` pairs remain (never fewer than the 3 an affine needs).
+/// Returns the final fit and the removed indices into `pairs`, worst first,
+/// so the user can review what was discarded.
+pub fn fit_affine_cleaned(
+    pairs: &[([f64; 2], [f64; 2])],
+    residual_threshold: f64,
+    min_points: usize,
+) -> Result<(Affine, Vec<usize>)> {
+    let min_points = min_points.max(3);
+    let mut kept: Vec<usize> = (0..pairs.len()).collect();
+    let mut removed = Vec::new();
+    loop {
+        let subset: Vec<_> = kept.iter().map(|&i| pairs[i]).collect();
+        let t = fit_affine_from_pairs(&subset)?;
+        if kept.len() <= min_points {
+            return Ok((t, removed));
+        }
+        let worst = subset
+            .iter()
+            .enumerate()
+            .map(|(k, (src, dst))| {
+                let r = (t.apply(&Vector2::from(*src)) - Vector2::from(*dst)).norm();
+                (k, r)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match worst {
+            Some((k, r)) if r > residual_threshold => removed.push(kept.remove(k)),
+            _ => return Ok((t, removed)),
+        }
+    }
+}
+
+/// Scales (output units per input pixel) outside this range are implausible
+/// for an image-to-image fit.
+pub const SANITY_SCALE_RANGE: (f64, f64) = (1e-3, 1e3);
+/// Translations longer than this many (scaled) image diagonals are flagged.
+pub const SANITY_MAX_TRANSLATION_DIAGONALS: f64 = 100.0;
+
+/// Human-readable warnings for implausible fitted parameters: a scale outside
+/// `SANITY_SCALE_RANGE` (for affines, either singular value), or a
+/// translation longer than `SANITY_MAX_TRANSLATION_DIAGONALS` times the
+/// image diagonal (`img_wh` is the source image size in pixels) at the
+/// fitted scale. Only similarity and affine transforms are checked.
+pub fn sanity_check(t: &TransformKind, img_wh: [f64; 2]) -> Vec<String> {
+    let (scales, translation) = match t {
+        TransformKind::Similarity(s) => (vec![s.params[0].abs()], [s.params[2], s.params[3]]),
+        TransformKind::Affine(a) => {
+            let [m11, m12, m21, m22, tx, ty] = a.params;
+            let sv = Matrix2::new(m11, m12, m21, m22).singular_values();
+            (vec![sv[0], sv[1]], [tx, ty])
+        }
+        _ => return Vec::new(),
+    };
+    let mut warnings = Vec::new();
+    let (lo, hi) = SANITY_SCALE_RANGE;
+    for s in &scales {
+        if !(lo..=hi).contains(s) {
+            warnings.push(format!(
+                "Fitted scale {:.3e} is outside the plausible range {:e}..{:e}; check the control points",
+                s, lo, hi
+            ));
+        }
+    }
+    let scale = scales.iter().cloned().fold(0.0, f64::max).max(1.0);
+    let diag = img_wh[0].hypot(img_wh[1]);
+    let shift = translation[0].hypot(translation[1]);
+    if diag > 0.0 && shift > SANITY_MAX_TRANSLATION_DIAGONALS * diag * scale {
+        warnings.push(format!(
+            "Fitted translation {:.3e} is over {} image diagonals; the map may be placed far from the reference",
+            shift, SANITY_MAX_TRANSLATION_DIAGONALS
+        ));
+    }
+    warnings
+}
+
+/// Local linear behavior of a transform at one point, from its Jacobian
+/// J = R(rotation) * [[scale_x, shear * scale_y], [0, scale_y]].
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LocalJacobian {
+    /// Length of the image of a unit step along u
+    pub scale_x: f64,
+    /// Scale along v after removing shear; negative when the map is mirrored
+    pub scale_y: f64,
+    /// Direction of the image of +u, radians counter-clockwise (same sense
+    /// as `Similarity.params[1]`)
+    pub rotation: f64,
+    /// Shear factor (0 for similarities)
+    pub shear: f64,
+}
+
+/// Numerically differentiate `t` at `p` (central differences) and decompose
+/// the Jacobian. For similarities and affines this is constant; for spatially
+/// varying transforms it gives the local scale and rotation at `p`.
+pub fn local_jacobian<T: Transform>(t: &T, p: [f64; 2]) -> LocalJacobian {
+    const H: f64 = 0.5;
+    let p = Vector2::from(p);
+    let du =
+        (t.apply(&(p + Vector2::new(H, 0.0))) - t.apply(&(p - Vector2::new(H, 0.0)))) / (2.0 * H);
+    let dv =
+        (t.apply(&(p + Vector2::new(0.0, H))) - t.apply(&(p - Vector2::new(0.0, H)))) / (2.0 * H);
+    let scale_x = du.norm();
+    let rotation = du.y.atan2(du.x);
+    let (m12, scale_y) = if scale_x > 0.0 {
+        (du.dot(&dv) / scale_x, (du.x * dv.y - du.y * dv.x) / scale_x)
+    } else {
+        (0.0, dv.norm())
+    };
+    let shear = if scale_y != 0.0 { m12 / scale_y } else { 0.0 };
+    LocalJacobian {
+        scale_x,
+        scale_y,
+        rotation,
+        shear,
+    }
+}
+
+/// Most pieces `densify_ring` splits one edge into, whatever the spacing.
+pub const MAX_DENSIFY_STEPS: usize = 10_000;
+
+/// Insert vertices along each edge of the closed ring `points` (the last
+/// vertex connects back to the first) so no segment is longer than
+/// `max_segment_px`, up to `MAX_DENSIFY_STEPS` pieces per edge. Original
+/// vertices are kept; the ring is not repeated at the end. Errors unless
+/// `max_segment_px` is positive and finite.
+pub fn densify_ring(points: &[[f64; 2]], max_segment_px: f64) -> Result<Vec<[f64; 2]>> {
+    if !(max_segment_px.is_finite() && max_segment_px > 0.0) {
+        return Err(anyhow!(
+            "densify spacing must be positive and finite, got {}",
+            max_segment_px
+        ));
+    }
+    if points.len() < 2 {
+        return Ok(points.to_vec());
+    }
+    let mut out = Vec::with_capacity(points.len());
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        let len = (b[0] - a[0]).hypot(b[1] - a[1]);
+        // f64::max keeps an edge with NaN coordinates at one step
+        let steps = (len / max_segment_px)
+            .ceil()
+            .max(1.0)
+            .min(MAX_DENSIFY_STEPS as f64) as usize;
+        for k in 0..steps {
+            let f = k as f64 / steps as f64;
+            out.push([a[0] + f * (b[0] - a[0]), a[1] + f * (b[1] - a[1])]);
+        }
+    }
+    Ok(out)
+}
+
+/// Map a polygon's vertices through `t`. With `densify_px`, edges are first
+/// split (see `densify_ring`) so curved mappings render as curves; straight
+/// lines stay straight under similarities and affines, which need none.
+/// Errors when `densify_px` is not a positive, finite spacing.
+pub fn transform_polygon<T: Transform>(
+    t: &T,
+    points: &[[f64; 2]],
+    densify_px: Option<f64>,
+) -> Result<Vec<[f64; 2]>> {
+    let ring = match densify_px {
+        Some(d) => densify_ring(points, d)?,
+        None => points.to_vec(),
+    };
+    Ok(ring
+        .iter()
+        .map(|p| {
+            let q = t.apply(&Vector2::from(*p));
+            [q.x, q.y]
+        })
+        .collect())
+}
+
+/// Chunk length for residual reductions. Squares are summed per chunk and the
+/// partial sums added in chunk order, so the result does not depend on the
+/// `rayon` feature or on the thread count.
+const RESIDUAL_CHUNK: usize = 4096;
+
+/// Euclidean residual |t(src) - dst| of each pair, in pair order. Computed in
+/// parallel when the `rayon` feature is enabled.
+pub fn pair_residuals<T: Transform + Sync>(t: &T, pairs: &[([f64; 2], [f64; 2])]) -> Vec<f64> {
+    let residual = |(src, dst): &([f64; 2], [f64; 2])| {
+        (t.apply(&Vector2::from(*src)) - Vector2::from(*dst)).norm()
+    };
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        pairs.par_iter().map(residual).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        pairs.iter().map(residual).collect()
+    }
+}
+
+/// A linear feature traced on the map (pixels) and the same feature on the
+/// reference, with vertex i of one corresponding to vertex i of the other.
+pub type PolylinePair = (Vec<[f64; 2]>, Vec<[f64; 2]>);
+
+/// Angular misalignment of linear features: for each (map, reference)
+/// polyline pair with corresponding vertices, the mean difference in degrees
+/// between the bearing of each map segment after `t` and its reference
+/// segment, wrapped to (-180, 180]. Positive means the fitted segments turn
+/// counter-clockwise from the reference in the (x right, y down) pixel frame,
+/// i.e. clockwise on screen. A consistent value across features points to a
+/// rotation error that point RMSE can hide. Only the first min(len) vertices
+/// of each pair are compared; segments of zero length on either side are
+/// skipped, and a pair with no usable segment gives NaN. For polygons, repeat
+/// the first vertex at the end to include the closing edge.
+pub fn angular_residuals<T: Transform>(t: &T, polyline_pairs: &[PolylinePair]) -> Vec<f64> {
+    polyline_pairs
+        .iter()
+        .map(|(map, reference)| {
+            let mapped: Vec<Vector2<f64>> =
+                map.iter().map(|p| t.apply(&Vector2::from(*p))).collect();
+            let (sum, n) = mapped
+                .windows(2)
+                .zip(reference.windows(2))
+                .filter_map(|(m, r)| {
+                    let dm = m[1] - m[0];
+                    let dr = Vector2::from(r[1]) - Vector2::from(r[0]);
+                    if dm.norm_squared() == 0.0 || dr.norm_squared() == 0.0 {
+                        return None;
+                    }
+                    // Signed angle from dr to dm
+                    Some(dr.perp(&dm).atan2(dr.dot(&dm)).to_degrees())
+                })
+                .fold((0.0, 0usize), |(sum, n), d| (sum + d, n + 1));
+            if n == 0 {
+                f64::NAN
+            } else {
+                sum / n as f64
+            }
+        })
+        .collect()
+}
+
+/// Root mean square of `residuals` (0 when empty), reduced in fixed chunks so
+/// serial and parallel builds give bit-identical results.
+pub fn residual_rmse(residuals: &[f64]) -> f64 {
+    if residuals.is_empty() {
+        return 0.0;
+    }
+    let chunk_sum = |c: &[f64]| c.iter().map(|r| r * r).sum::<f64>();
+    #[cfg(feature = "rayon")]
+    let partials: Vec<f64> = {
+        use rayon::prelude::*;
+        residuals
+            .par_chunks(RESIDUAL_CHUNK)
+            .map(chunk_sum)
+            .collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let partials: Vec<f64> = residuals.chunks(RESIDUAL_CHUNK).map(chunk_sum).collect();
+    (partials.iter().sum::<f64>() / residuals.len() as f64).sqrt()
+}
+
+/// Per-axis RMSE (x, y) of `t` over `pairs`, from the signed component
+/// residuals (predicted - observed). A large gap between the two points at an
+/// aspect problem, e.g. a scanner stretching one axis.
+pub fn axis_rmse<T: Transform>(t: &T, pairs: &[([f64; 2], [f64; 2])]) -> (f64, f64) {
+    if pairs.is_empty() {
+        return (0.0, 0.0);
+    }
+    let (sx, sy) = pairs.iter().fold((0.0, 0.0), |(sx, sy), (src, dst)| {
+        let r = t.apply(&Vector2::from(*src)) - Vector2::from(*dst);
+        (sx + r.x * r.x, sy + r.y * r.y)
+    });
+    let n = pairs.len() as f64;
+    ((sx / n).sqrt(), (sy / n).sqrt())
+}
+
+/// Standard ellipse of the signed residuals (predicted - observed) of a fit:
+/// a centroid away from the origin is a systematic bias, and an elongated
+/// ellipse means errors favor one direction.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct ResidualEllipse {
+    /// Mean residual vector
+    pub center: [f64; 2],
+    /// Standard deviation along the major axis
+    pub major: f64,
+    /// Standard deviation along the minor axis
+    pub minor: f64,
+    /// Direction of the major axis, radians counter-clockwise from +x in
+    /// (-π/2, π/2]
+    pub angle: f64,
+}
+
+/// Residual centroid and the eigen-decomposition of the 2x2 (population)
+/// covariance of the signed residuals of `t` over `pairs`. All zero for no
+/// pairs.
+pub fn residual_ellipse<T: Transform>(t: &T, pairs: &[([f64; 2], [f64; 2])]) -> ResidualEllipse {
+    if pairs.is_empty() {
+        return ResidualEllipse::default();
+    }
+    let n = pairs.len() as f64;
+    let residuals: Vec<Vector2<f64>> = pairs
+        .iter()
+        .map(|(src, dst)| t.apply(&Vector2::from(*src)) - Vector2::from(*dst))
+        .collect();
+    let mean = residuals.iter().sum::<Vector2<f64>>() / n;
+    let (mut sxx, mut syy, mut sxy) = (0.0, 0.0, 0.0);
+    for r in &residuals {
+        let d = r - mean;
+        sxx += d.x * d.x;
+        syy += d.y * d.y;
+        sxy += d.x * d.y;
+    }
+    let (sxx, syy, sxy) = (sxx / n, syy / n, sxy / n);
+    let half_trace = 0.5 * (sxx + syy);
+    let spread = (0.25 * (sxx - syy).powi(2) + sxy * sxy).sqrt();
+    let mut angle = 0.5 * (2.0 * sxy).atan2(sxx - syy);
+    if angle <= -std::f64::consts::FRAC_PI_2 {
+        angle += std::f64::consts::PI;
+    }
+    ResidualEllipse {
+        center: [mean.x, mean.y],
+        major: (half_trace + spread).sqrt(),
+        minor: (half_trace - spread).max(0.0).sqrt(),
+        angle,
+    }
+}
+
+/// Per-pair (trend, noise) vectors from `detrend_residuals`.
+pub type Detrended = (Vec<[f64; 2]>, Vec<[f64; 2]>);
+
+/// Split signed residual vectors (one per pair, at its source position) into
+/// a smooth trend, the least-squares polynomial surface of total `degree` in
+/// the source coordinates fitted to each component, and the noise left over
+/// (`residual - trend`). A trend carrying most of the error is structure a
+/// higher-order model could absorb; noise near the residual size means the
+/// fit is at its noise floor. Underdetermined surfaces (fewer pairs than
+/// terms, or collinear sources) use the minimum-norm solution, so the trend
+/// then passes through the residuals it can. Degree 0 is the mean residual.
+/// Errors unless there is exactly one residual per pair.
+pub fn detrend_residuals(
+    pairs: &[([f64; 2], [f64; 2])],
+    residuals: &[[f64; 2]],
+    degree: usize,
+) -> Result<Detrended> {
+    if pairs.len() != residuals.len() {
+        return Err(anyhow!(
+            "detrending needs one residual per pair; got {} pairs and {} residuals",
+            pairs.len(),
+            residuals.len()
+        ));
+    }
+    if pairs.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    // Monomials u^i v^j (i + j <= degree) of centered, scaled coordinates
+    let (c, s) = normalization(pairs.iter().map(|p| p.0));
+    let terms = (degree + 1) * (degree + 2) / 2;
+    let design = nalgebra::DMatrix::from_fn(pairs.len(), terms, |r, k| {
+        let p = (Vector2::from(pairs[r].0) - c) / s;
+        // Term k is the k-th of the triangle whose row i holds total degree i
+        let (mut i, mut k) = (0, k);
+        while k > i {
+            k -= i + 1;
+            i += 1;
+        }
+        p.x.powi((i - k) as i32) * p.y.powi(k as i32)
+    });
+    let values = nalgebra::DMatrix::from_fn(pairs.len(), 2, |r, k| residuals[r][k]);
+    let svd = design.clone().svd(true, true);
+    let eps = svd.singular_values.max() * 1e-10;
+    let trend = match svd.solve(&values, eps) {
+        Ok(coef) => design * coef,
+        Err(_) => nalgebra::DMatrix::zeros(pairs.len(), 2),
+    };
+    let trend: Vec<[f64; 2]> = (0..pairs.len())
+        .map(|r| [trend[(r, 0)], trend[(r, 1)]])
+        .collect();
+    let noise = residuals
+        .iter()
+        .zip(&trend)
+        .map(|(r, t)| [r[0] - t[0], r[1] - t[1]])
+        .collect();
+    Ok((trend, noise))
+}
+
+/// Default inverse-distance-weighting power for `idw_interpolate`. Higher
+/// powers keep each sample's influence more local; 2 is the usual choice.
+pub const IDW_POWER: f64 = 2.0;
+
+/// Inverse-distance-weighted value at `at` from `samples` (position, value):
+/// sum(v_i / d_i^power) / sum(1 / d_i^power). Returns a sample's own value
+/// when `at` coincides with it, None when there are no samples.
+pub fn idw_interpolate(samples: &[([f64; 2], f64)], at: [f64; 2], power: f64) -> Option<f64> {
+    let (mut num, mut den) = (0.0, 0.0);
+    for (p, v) in samples {
+        let d = (p[0] - at[0]).hypot(p[1] - at[1]);
+        if d == 0.0 {
+            return Some(*v);
+        }
+        let w = d.powf(-power);
+        num += w * v;
+        den += w;
+    }
+    (den > 0.0).then(|| num / den)
+}
+
+/// Vertical quality check for point pairs that carry elevations.
+#[derive(Debug, Clone)]
+pub struct VerticalFit {
+    /// Best-fit plane dz = a*u + b*v + c over source pixel coordinates
+    pub plane: [f64; 3],
+    pub rmse: f64,
+    /// Signed vertical residual (observed - plane) per constraint id
+    pub residuals_by_id: Vec<(u64, f64)>,
+}
+
+/// Fit a plane to the vertical values of `PointPair` constraints that have a
+/// `dst_z`, and report each point's departure from it. The vertical value is
+/// `dst_z - src_z` when both are present (a tilted datum offset), otherwise
+/// `dst_z` alone. Full 3D transforms are out of scope: the horizontal fit is
+/// unaffected by elevations.
+///
+/// Returns Ok(None) when no pair has elevations, and an error when fewer than
+/// 3 do (a plane is undetermined).
+pub fn vertical_residuals(constraints: &[ConstraintKind]) -> Result<Option<VerticalFit>> {
+    let pts: Vec<(u64, [f64; 2], f64)> = constraints
+        .iter()
+        .filter_map(|c| match c {
+            ConstraintKind::PointPair {
+                id,
+                src,
+                src_z,
+                dst_z: Some(dz),
+                ..
+            } => {
+                let v = dz - src_z.unwrap_or(0.0);
+                (v.is_finite() && src[0].is_finite() && src[1].is_finite())
+                    .then_some((*id, *src, v))
+            }
+            _ => None,
+        })
+        .collect();
+    if pts.is_empty() {
+        return Ok(None);
+    }
+    let n = pts.len();
+    if n < 3 {
+        return Err(anyhow!(
+            "At least 3 points with elevations are required (got {})",
+            n
+        ));
+    }
+    let mut a = nalgebra::DMatrix::<f64>::zeros(n, 3);
+    let mut b = nalgebra::DVector::<f64>::zeros(n);
+    for (i, (_, src, v)) in pts.iter().enumerate() {
+        a[(i, 0)] = src[0];
+        a[(i, 1)] = src[1];
+        a[(i, 2)] = 1.0;
+        b[i] = *v;
+    }
+    let x = a
+        .svd(true, true)
+        .solve(&b, 1e-9)
+        .map_err(|e| anyhow!(e.to_string()))?;
+    let plane = [x[0], x[1], x[2]];
+    let residuals_by_id: Vec<(u64, f64)> = pts
+        .iter()
+        .map(|(id, src, v)| (*id, v - (plane[0] * src[0] + plane[1] * src[1] + plane[2])))
+        .collect();
+    let rmse = (residuals_by_id.iter().map(|(_, r)| r * r).sum::<f64>() / n as f64).sqrt();
+    Ok(Some(VerticalFit {
+        plane,
+        rmse,
+        residuals_by_id,
+    }))
+}
+
+/// Where integer pixel coordinates sit within a pixel.
+///
+/// This app (and ESRI world files) put integer coordinates at pixel centers.
+/// GDAL's pixel/line space puts (0, 0) at the top-left corner of the first
+/// pixel, so its center is (0.5, 0.5); pipelines fed GDAL pixel/line input
+/// (`gdaltransform`, GCPs given to `gdal_translate -gcp`, VRT/geotransform
+/// based workflows) want `Corner`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PixelOrigin {
+    #[default]
+    Center,
+    Corner,
+}
+
+/// One `+step` of a PROJ pipeline.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjStep {
+    /// Pure shift `+proj=affine +xoff= +yoff=`, e.g. the pixel-corner to
+    /// pixel-center conversion
+    Offset { xoff: f64, yoff: f64 },
+    /// Full `+proj=affine`: x' = s11*x + s12*y + xoff; y' = s21*x + s22*y + yoff
+    Affine {
+        xoff: f64,
+        yoff: f64,
+        s11: f64,
+        s12: f64,
+        s21: f64,
+        s22: f64,
+    },
+    /// Any other step, given as its options without `+step`
+    /// (e.g. `"+proj=utm +zone=10 +inv"`)
+    Raw(String),
+}
+
+impl std::fmt::Display for ProjStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjStep::Offset { xoff, yoff } => {
+                write!(f, "+proj=affine +xoff={} +yoff={}", xoff, yoff)
+            }
+            ProjStep::Affine {
+                xoff,
+                yoff,
+                s11,
+                s12,
+                s21,
+                s22,
+            } => write!(
+                f,
+                "+proj=affine +xoff={:.17} +yoff={:.17} +s11={:.17} +s12={:.17} +s21={:.17} +s22={:.17}",
+                xoff, yoff, s11, s12, s21, s22
+            ),
+            ProjStep::Raw(opts) => f.write_str(opts.trim()),
+        }
+    }
+}
+
+/// A PROJ pipeline as a list of steps, so callers can inspect or extend it
+/// (e.g. push a CRS conversion after the pixel-to-world step) before
+/// rendering it with `to_string()`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProjPipeline {
+    pub steps: Vec<ProjStep>,
+}
+
+impl ProjPipeline {
+    /// Pixel-to-world pipeline for an affine. The fitted mapping uses pixel
+    /// centers at integer coordinates; with `PixelOrigin::Corner` a -0.5 px
+    /// shift is applied to the input first.
+    pub fn from_affine(aff: &Affine, origin: PixelOrigin) -> Self {
+        let [a, b, c, d, tx, ty] = aff.params;
+        let mut steps = Vec::new();
+        if origin == PixelOrigin::Corner {
+            steps.push(ProjStep::Offset {
+                xoff: -0.5,
+                yoff: -0.5,
+            });
+        }
+        steps.push(ProjStep::Affine {
+            xoff: tx,
+            yoff: ty,
+            s11: a,
+            s12: b,
+            s21: c,
+            s22: d,
+        });
+        Self { steps }
+    }
+
+    /// Pixel-to-world pipeline for a similarity (see `from_affine`).
+    pub fn from_similarity(sim: &Similarity, origin: PixelOrigin) -> Self {
+        Self::from_affine(&similarity_to_affine(sim), origin)
+    }
+
+    /// Append a step, returning the pipeline for chaining.
+    pub fn then(mut self, step: ProjStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+}
+
+impl std::fmt::Display for ProjPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("+proj=pipeline")?;
+        for step in &self.steps {
+            write!(f, " +step {}", step)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a pixel-to-world PROJ string made only of `+proj=affine` steps
+/// (a bare step or a `+proj=pipeline`, as `affine_to_proj` writes) back into
+/// one affine [a,b,c,d,tx,ty]. Steps compose in order; absent `+s11`/`+s22`
+/// default to 1 and other terms to 0, as in PROJ. `origin` is the pixel
+/// convention the pipeline's input uses: with `Corner` the result is shifted
+/// so it takes pixel-center coordinates like the rest of the crate. Any other
+/// step, `+inv` or an unparsable value is an error.
+pub fn proj_to_affine(pipeline: &str, origin: PixelOrigin) -> Result<Affine> {
+    let tokens: Vec<&str> = pipeline.split_whitespace().collect();
+    let body = match tokens.first() {
+        Some(&"+proj=pipeline") => &tokens[1..],
+        Some(_) => &tokens[..],
+        None => return Err(anyhow!("empty PROJ string")),
+    };
+    let mut steps: Vec<Vec<&str>> = Vec::new();
+    for tok in body {
+        if *tok == "+step" {
+            steps.push(Vec::new());
+        } else if let Some(step) = steps.last_mut() {
+            step.push(tok);
+        } else {
+            steps.push(vec![tok]);
+        }
+    }
+    let mut total = Affine {
+        params: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+    };
+    if origin == PixelOrigin::Corner {
+        total.params[4] = 0.5;
+        total.params[5] = 0.5;
+    }
+    for step in steps.iter().filter(|s| !s.is_empty()) {
+        if !step.contains(&"+proj=affine") {
+            return Err(anyhow!(
+                "only +proj=affine steps can be converted; got `{}`",
+                step.join(" ")
+            ));
+        }
+        let mut p = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        for tok in step.iter().filter(|t| **t != "+proj=affine") {
+            let (key, value) = tok.split_once('=').unwrap_or((tok, ""));
+            let slot = match key {
+                "+s11" => 0,
+                "+s12" => 1,
+                "+s21" => 2,
+                "+s22" => 3,
+                "+xoff" => 4,
+                "+yoff" => 5,
+                _ => return Err(anyhow!("unsupported affine option `{}`", tok)),
+            };
+            p[slot] = value
+                .parse()
+                .map_err(|_| anyhow!("invalid value in `{}`", tok))?;
+        }
+        total = compose_affine(&total, &Affine { params: p });
+    }
+    Ok(total)
+}
+
 /// Return PROJ pipeline string for a similarity transform.
-/// Mapping uses pixel centers at integer coordinates (no implicit 0.5 offset):
-/// x = a*u + b*v + c; y = d*u + e*v + f
-pub fn similarity_to_proj(sim: &Similarity) -> String {
-    let s = sim.params[0];
-    let th = sim.params[1];
-    let tx = sim.params[2];
-    let ty = sim.params[3];
-    let c = th.cos();
-    let si = th.sin();
-    let a = s * c;
-    let b = -s * si;
-    let d = s * si;
-    let e = s * c;
-    format!(
-        "+proj=pipeline +step +proj=affine +xoff={:.17} +yoff={:.17} +s11={:.17} +s12={:.17} +s21={:.17} +s22={:.17}",
-        tx, ty, a, b, d, e
-    )
+/// The fitted mapping uses pixel centers at integer coordinates:
+/// x = a*u + b*v + c; y = d*u + e*v + f. With `PixelOrigin::Corner` a
+/// -0.5 px shift is applied to the input first.
+pub fn similarity_to_proj(sim: &Similarity, origin: PixelOrigin) -> String {
+    ProjPipeline::from_similarity(sim, origin).to_string()
 }
 
 /// Return PROJ pipeline string for an affine transform.
 /// Affine params: [a,b,c,d,tx,ty] where
-/// x = a*u + b*v + tx; y = c*u + d*v + ty
-pub fn affine_to_proj(aff: &Affine) -> String {
-    let a = aff.params[0];
-    let b = aff.params[1];
-    let c = aff.params[2];
-    let d = aff.params[3];
-    let tx = aff.params[4];
-    let ty = aff.params[5];
-    format!(
-        "+proj=pipeline +step +proj=affine +xoff={:.17} +yoff={:.17} +s11={:.17} +s12={:.17} +s21={:.17} +s22={:.17}",
-        tx, ty, a, b, c, d
-    )
+/// x = a*u + b*v + tx; y = c*u + d*v + ty (pixel-center convention; see
+/// `PixelOrigin`)
+pub fn affine_to_proj(aff: &Affine, origin: PixelOrigin) -> String {
+    ProjPipeline::from_affine(aff, origin).to_string()
+}
+
+/// Rotation of a similarity as a compass bearing: degrees clockwise from
+/// north, normalized to [0, 360).
+///
+/// `params[1]` is radians counter-clockwise from +x in a y-up frame. Pixel
+/// frames are y-down, so the same angle reads as clockwise on screen: the
+/// map's "up" (0,-1) lands at bearing `theta` in the reference image. A
+/// value of 12.3 means the map content is turned 12.3° clockwise.
+pub fn similarity_bearing_deg(sim: &Similarity) -> f64 {
+    sim.params[1].to_degrees().rem_euclid(360.0)
+}
+
+/// Deviation of a similarity's scale from 1 in parts per million:
+/// (s - 1) * 1e6, so 1.003 gives 3000 ppm (0.3%) and a shrunken scan is
+/// negative. Only meaningful when map and reference share units, e.g. a scan
+/// fitted to the same sheet at the same resolution.
+pub fn scale_deviation_ppm(sim: &Similarity) -> f64 {
+    (sim.params[0] - 1.0) * 1e6
+}
+
+/// Scan resolution (dots per inch) needed for each scanned pixel to cover
+/// `target_ground_m` meters on the ground on a 1:`map_scale` paper map
+/// (`map_scale` is the denominator, e.g. 24000). One pixel must span
+/// `target_ground_m * 1000 / map_scale` mm of paper, so DPI is 25.4 mm per
+/// inch divided by that; 1:24000 at 1 m needs 609.6 DPI.
+pub fn required_scan_dpi(map_scale: f64, target_ground_m: f64) -> f64 {
+    25.4 / (target_ground_m * 1000.0 / map_scale)
+}
+
+/// Scale denominator at which `extent_m` meters of ground fill `paper_m`
+/// meters of paper (both along the same direction, e.g. widths): a 5000 m
+/// extent on 0.5 m of paper is 1:10000. Round with `nice_map_scale` for a
+/// standard scale.
+pub fn scale_for_paper(extent_m: f64, paper_m: f64) -> f64 {
+    extent_m / paper_m
+}
+
+/// Mantissas of the standard cartographic scale series within a decade:
+/// 1:10000, 1:20000, 1:25000, 1:50000, 1:100000 and so on.
+const NICE_SCALE_STEPS: [f64; 4] = [1.0, 2.0, 2.5, 5.0];
+
+/// Smallest standard scale denominator (see `NICE_SCALE_STEPS`) at least
+/// `denominator`, so the extent still fits the paper: 10400 becomes 20000
+/// and an exact 25000 stays. Non-finite or non-positive input is returned
+/// unchanged.
+pub fn nice_map_scale(denominator: f64) -> f64 {
+    if !(denominator.is_finite() && denominator > 0.0) {
+        return denominator;
+    }
+    let decade = 10f64.powf(denominator.log10().floor());
+    NICE_SCALE_STEPS
+        .iter()
+        .chain(&[10.0])
+        .map(|m| m * decade)
+        // Tolerate the rounding in log10/powf for exact standard values
+        .find(|v| *v >= denominator * (1.0 - 1e-12))
+        .unwrap_or(10.0 * decade)
 }
 
 /// Return the inverse of a similarity transform.
@@ -265,3 +2373,116 @@ pub fn compose_similarity(a: &Similarity, b: &Similarity) -> Similarity {
         params: [s, theta, t.x, t.y],
     }
 }
+
+/// Chain `a_to_b` (e.g. map A pixels -> image B pixels) with `b_to_c` (e.g.
+/// B's georeferencing, pixels -> world) into a single A -> C transform.
+/// Two similarities stay a similarity; any other similarity/affine mix is
+/// composed as an affine. Other transform kinds are not supported.
+pub fn chain_transforms(a_to_b: &TransformKind, b_to_c: &TransformKind) -> Result<TransformKind> {
+    let as_affine = |t: &TransformKind| match t {
+        TransformKind::Similarity(s) => Ok(similarity_to_affine(s)),
+        TransformKind::Affine(a) => Ok(a.clone()),
+        _ => Err(anyhow!(
+            "only similarity and affine transforms can be chained"
+        )),
+    };
+    match (a_to_b, b_to_c) {
+        (TransformKind::Similarity(a), TransformKind::Similarity(b)) => {
+            Ok(TransformKind::Similarity(compose_similarity(a, b)))
+        }
+        _ => Ok(TransformKind::Affine(compose_affine(
+            &as_affine(a_to_b)?,
+            &as_affine(b_to_c)?,
+        ))),
+    }
+}
+
+/// Blend two similarities with factor `t` (0 = `a`, 1 = `b`), e.g. across a
+/// mosaic seam. Scale is interpolated geometrically (linearly if either scale
+/// is non-positive), rotation along the shortest arc so blends across the ±π
+/// wrap stay continuous, and translation linearly. The angle is returned in
+/// (-π, π].
+pub fn blend_similarity(a: &Similarity, b: &Similarity, t: f64) -> Similarity {
+    use std::f64::consts::PI;
+    let lerp = |x: f64, y: f64| x + (y - x) * t;
+    let (sa, sb) = (a.params[0], b.params[0]);
+    let s = if sa > 0.0 && sb > 0.0 {
+        sa.powf(1.0 - t) * sb.powf(t)
+    } else {
+        lerp(sa, sb)
+    };
+    let delta = (b.params[1] - a.params[1] + PI).rem_euclid(2.0 * PI) - PI;
+    let mut theta = a.params[1] + delta * t;
+    if theta > PI {
+        theta -= 2.0 * PI;
+    } else if theta <= -PI {
+        theta += 2.0 * PI;
+    }
+    Similarity {
+        params: [
+            s,
+            theta,
+            lerp(a.params[2], b.params[2]),
+            lerp(a.params[3], b.params[3]),
+        ],
+    }
+}
+
+/// Express a similarity transform as affine params [a,b,c,d,tx,ty].
+pub fn similarity_to_affine(sim: &Similarity) -> Affine {
+    let s = sim.params[0];
+    let th = sim.params[1];
+    let (c, si) = (th.cos(), th.sin());
+    Affine {
+        params: [s * c, -s * si, s * si, s * c, sim.params[2], sim.params[3]],
+    }
+}
+
+/// Re-express `aff` with input coordinates measured from `center` (e.g. the
+/// image center) instead of pixel (0, 0): the result maps `p - center` to the
+/// same point `aff` maps `p` to. The linear part is unchanged; the
+/// translation becomes the world position of `center`, which is easier to
+/// read alongside rotation and scale. No refit is involved.
+pub fn affine_about_point(aff: &Affine, center: [f64; 2]) -> Affine {
+    let [a, b, c, d, _, _] = aff.params;
+    let t = aff.apply(&Vector2::from(center));
+    Affine {
+        params: [a, b, c, d, t.x, t.y],
+    }
+}
+
+/// Compose two affine transforms: result = b ∘ a
+pub fn compose_affine(a: &Affine, b: &Affine) -> Affine {
+    let [a0, a1, a2, a3, a4, a5] = a.params;
+    let [b0, b1, b2, b3, b4, b5] = b.params;
+    Affine {
+        params: [
+            b0 * a0 + b1 * a2,
+            b0 * a1 + b1 * a3,
+            b2 * a0 + b3 * a2,
+            b2 * a1 + b3 * a3,
+            b0 * a4 + b1 * a5 + b4,
+            b2 * a4 + b3 * a5 + b5,
+        ],
+    }
+}
+
+/// Reorder a world-file affine [A, B, D, E, C, F] into a GDAL GeoTransform.
+///
+/// GDAL's six-tuple is [originX, pixelWidth, rowRotation, originY,
+/// colRotation, pixelHeight], evaluated as X = GT0 + col*GT1 + row*GT2 and
+/// Y = GT3 + col*GT4 + row*GT5. The linear terms keep their signs (a north-up
+/// image has a negative pixelHeight in both conventions); only the origin
+/// moves, from the first pixel's center (world file) to its outer top-left
+/// corner (GDAL), i.e. half a pixel back along both axes.
+pub fn affine_to_gdal_geotransform(affine: &[f64; 6]) -> [f64; 6] {
+    let [a, b, d, e, c, f] = *affine;
+    [c - 0.5 * a - 0.5 * b, a, b, f - 0.5 * d - 0.5 * e, d, e]
+}
+
+/// Inverse of [`affine_to_gdal_geotransform`]: GDAL GeoTransform to
+/// world-file order, moving the origin from the pixel corner to its center.
+pub fn gdal_geotransform_to_affine(gt: &[f64; 6]) -> [f64; 6] {
+    let [x0, a, b, y0, d, e] = *gt;
+    [a, b, d, e, x0 + 0.5 * a + 0.5 * b, y0 + 0.5 * d + 0.5 * e]
+}
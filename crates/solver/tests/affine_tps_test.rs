@@ -0,0 +1,114 @@
+use approx::assert_relative_eq;
+use nalgebra::Vector2;
+use solver::{fit_affine_then_tps, Transform};
+use types::TransformKind;
+
+/// Affine bulk transform plus a smooth local wobble.
+fn warped_pairs() -> Vec<([f64; 2], [f64; 2])> {
+    let mut pairs = Vec::new();
+    for i in 0..5 {
+        for j in 0..4 {
+            let (u, v) = (i as f64 * 100.0 + j as f64 * 7.0, j as f64 * 120.0);
+            let x = 1.5 * u - 0.2 * v + 300.0 + 4.0 * (u / 90.0).sin();
+            let y = 0.1 * u + 1.4 * v - 50.0 + 3.0 * (v / 70.0).cos();
+            pairs.push(([u, v], [x, y]));
+        }
+    }
+    pairs
+}
+
+#[test]
+fn test_affine_then_tps_interpolates_control_points() {
+    let pairs = warped_pairs();
+    let stack = fit_affine_then_tps(&pairs, 1e-9).unwrap();
+    assert_eq!(stack.transforms.len(), 2);
+    assert!(matches!(stack.transforms[0], TransformKind::Affine(_)));
+    let TransformKind::Tps(tps) = &stack.transforms[1] else {
+        panic!("second transform should be a TPS");
+    };
+    // The spline only carries the wobble: its affine part stays near identity
+    assert_relative_eq!(tps.affine[0], 1.0, epsilon = 0.05);
+    assert_relative_eq!(tps.affine[3], 1.0, epsilon = 0.05);
+    for (src, dst) in &pairs {
+        let p = stack.apply(&Vector2::from(*src));
+        assert_relative_eq!(p.x, dst[0], epsilon = 1e-6);
+        assert_relative_eq!(p.y, dst[1], epsilon = 1e-6);
+    }
+}
+
+#[test]
+fn test_affine_then_tps_on_affine_data_has_no_wobble() {
+    let pairs: Vec<_> = warped_pairs()
+        .into_iter()
+        .map(|([u, v], _)| ([u, v], [2.0 * u + 0.3 * v + 5.0, -0.4 * u + 1.1 * v - 8.0]))
+        .collect();
+    let stack = fit_affine_then_tps(&pairs, 0.0).unwrap();
+    let TransformKind::Tps(tps) = &stack.transforms[1] else {
+        panic!("second transform should be a TPS");
+    };
+    for w in &tps.weights {
+        assert!(w[0].abs() < 1e-9 && w[1].abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_affine_then_tps_rejects_bad_input() {
+    let pairs = warped_pairs();
+    assert!(fit_affine_then_tps(&pairs[..2], 0.0).is_err());
+    assert!(fit_affine_then_tps(&pairs, -1.0).is_err());
+}
+
+#[test]
+fn test_affine_then_tps_on_a_20k_px_scan() {
+    // A 20000x15000 px scan: raw r² ln r reaches ~1e10 here, so the kernel
+    // system is only well conditioned once coordinates are normalized
+    let mut pairs = Vec::new();
+    for i in 0..8 {
+        for j in 0..6 {
+            let u = i as f64 * 2800.0 + 150.0 + (j * 37 % 11) as f64 * 13.0;
+            let v = j as f64 * 2900.0 + 80.0 + (i * 29 % 7) as f64 * 17.0;
+            let x = 0.5 * u - 0.02 * v + 1200.0 + 6.0 * (u / 3100.0).sin();
+            let y = 0.03 * u + 0.5 * v - 400.0 + 4.0 * (v / 2700.0).cos();
+            pairs.push(([u, v], [x, y]));
+        }
+    }
+    let stack = fit_affine_then_tps(&pairs, 0.0).unwrap();
+    for (src, dst) in &pairs {
+        let p = stack.apply(&Vector2::from(*src));
+        assert_relative_eq!(p.x, dst[0], epsilon = 1e-6);
+        assert_relative_eq!(p.y, dst[1], epsilon = 1e-6);
+    }
+    // Between control points the spline stays within the wobble's size
+    let TransformKind::Affine(bulk) = &stack.transforms[0] else {
+        panic!("first transform should be an affine");
+    };
+    let mid = Vector2::new(9950.0, 7400.0);
+    let off = stack.apply(&mid) - bulk.apply(&mid);
+    assert!(off.norm() < 15.0, "{:?}", off);
+    let TransformKind::Tps(tps) = &stack.transforms[1] else {
+        panic!("second transform should be a TPS");
+    };
+    // Control points live in the bulk affine's output frame (~0.5x the scan)
+    let span = |k: usize| {
+        let v = tps.control_points.iter().map(|c| c[k]);
+        v.clone().fold(f64::NEG_INFINITY, f64::max) - v.fold(f64::INFINITY, f64::min)
+    };
+    assert_relative_eq!(tps.scale, span(0).max(span(1)));
+    assert!(tps.scale > 9000.0, "{}", tps.scale);
+    // Smoothing is measured in the normalized frame: the same lambda on a
+    // 1/10 copy of the scan leaves residuals exactly 10x smaller
+    let small: Vec<_> = pairs
+        .iter()
+        .map(|(s, d)| ([s[0] / 10.0, s[1] / 10.0], [d[0] / 10.0, d[1] / 10.0]))
+        .collect();
+    let big = fit_affine_then_tps(&pairs, 0.01).unwrap();
+    let little = fit_affine_then_tps(&small, 0.01).unwrap();
+    let mut smoothed = 0.0f64;
+    for ((src, dst), (s, d)) in pairs.iter().zip(&small) {
+        let rb = big.apply(&Vector2::from(*src)) - Vector2::from(*dst);
+        let rs = little.apply(&Vector2::from(*s)) - Vector2::from(*d);
+        assert_relative_eq!(rb, rs * 10.0, epsilon = 1e-6);
+        smoothed = smoothed.max(rb.norm());
+    }
+    assert!(smoothed > 1e-3, "lambda should smooth: {}", smoothed);
+}
@@ -0,0 +1,41 @@
+use approx::assert_relative_eq;
+use nalgebra::Vector2;
+use solver::{angular_residuals, Transform};
+use types::Similarity;
+
+#[test]
+fn test_small_rotation_gives_consistent_angular_residual() {
+    // Reference features are the map features rotated by 2 degrees and
+    // scaled; the fit got the scale and shift but missed the rotation
+    let truth = Similarity {
+        params: [1.5, 2f64.to_radians(), 40.0, -10.0],
+    };
+    let fit = Similarity {
+        params: [1.5, 0.0, 40.0, -10.0],
+    };
+    let road = vec![[0.0, 0.0], [100.0, 20.0], [180.0, 90.0], [260.0, 95.0]];
+    let coast = vec![[50.0, 300.0], [40.0, 200.0], [90.0, 120.0]];
+    let through = |pts: &[[f64; 2]]| -> Vec<[f64; 2]> {
+        pts.iter()
+            .map(|p| {
+                let q = truth.apply(&Vector2::from(*p));
+                [q.x, q.y]
+            })
+            .collect()
+    };
+    let pairs = vec![
+        (road.clone(), through(&road)),
+        (coast.clone(), through(&coast)),
+    ];
+    let r = angular_residuals(&fit, &pairs);
+    assert_eq!(r.len(), 2);
+    for a in &r {
+        assert_relative_eq!(*a, -2.0, epsilon = 1e-9);
+    }
+    // The true transform leaves no angular residual
+    for a in angular_residuals(&truth, &pairs) {
+        assert!(a.abs() < 1e-9, "{}", a);
+    }
+    // A single vertex has no segment to compare
+    assert!(angular_residuals(&fit, &[(vec![[0.0, 0.0]], vec![[1.0, 1.0]])])[0].is_nan());
+}
@@ -0,0 +1,74 @@
+use approx::assert_relative_eq;
+use nalgebra::Vector2;
+use solver::{chain_transforms, fit_affine_from_pairs, fit_similarity_from_pairs, Transform};
+use types::{Affine, Similarity, TransformKind};
+
+fn grid() -> Vec<[f64; 2]> {
+    (0..4)
+        .flat_map(|i| (0..3).map(move |j| [i as f64 * 300.0 + 20.0, j as f64 * 250.0 + 35.0]))
+        .collect()
+}
+
+#[test]
+fn test_chained_affine_matches_direct_fit() {
+    // A -> B (pixels) and B -> world (world-file style, y flipped)
+    let a_to_b = Affine {
+        params: [0.8, 0.1, -0.05, 0.75, 40.0, -12.0],
+    };
+    let b_to_world = Affine {
+        params: [2.0, 0.0, 0.0, -2.0, 431000.0, 5270000.0],
+    };
+    let ab_pairs: Vec<_> = grid()
+        .into_iter()
+        .map(|a| {
+            let b = a_to_b.apply(&Vector2::from(a));
+            (a, [b.x, b.y])
+        })
+        .collect();
+    let fitted = TransformKind::Affine(fit_affine_from_pairs(&ab_pairs).unwrap());
+    let chained = chain_transforms(&fitted, &TransformKind::Affine(b_to_world.clone())).unwrap();
+
+    let direct_pairs: Vec<_> = ab_pairs
+        .iter()
+        .map(|(a, b)| {
+            let w = b_to_world.apply(&Vector2::from(*b));
+            (*a, [w.x, w.y])
+        })
+        .collect();
+    let direct = fit_affine_from_pairs(&direct_pairs).unwrap();
+    let TransformKind::Affine(chained) = chained else {
+        panic!("expected an affine");
+    };
+    for (c, d) in chained.params.iter().zip(direct.params.iter()) {
+        assert_relative_eq!(c, d, epsilon = 1e-6, max_relative = 1e-9);
+    }
+}
+
+#[test]
+fn test_two_similarities_chain_to_a_similarity() {
+    let a_to_b = Similarity {
+        params: [0.5, 0.3, 10.0, 20.0],
+    };
+    let b_to_c = Similarity {
+        params: [3.0, -0.1, -100.0, 5.0],
+    };
+    let pairs: Vec<_> = grid()
+        .into_iter()
+        .map(|a| {
+            let c = b_to_c.apply(&a_to_b.apply(&Vector2::from(a)));
+            (a, [c.x, c.y])
+        })
+        .collect();
+    let direct = fit_similarity_from_pairs(&pairs).unwrap();
+    let chained = chain_transforms(
+        &TransformKind::Similarity(a_to_b),
+        &TransformKind::Similarity(b_to_c),
+    )
+    .unwrap();
+    let TransformKind::Similarity(chained) = chained else {
+        panic!("expected a similarity");
+    };
+    for (c, d) in chained.params.iter().zip(direct.params.iter()) {
+        assert_relative_eq!(c, d, epsilon = 1e-9);
+    }
+}
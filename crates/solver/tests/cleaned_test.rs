@@ -0,0 +1,48 @@
+use approx::assert_relative_eq;
+use solver::{fit_affine_cleaned, fit_affine_from_pairs};
+
+/// dst = [[1.5, 0.2], [-0.1, 0.9]] * src + (10, -4) on a 4x4 grid
+fn clean_pairs() -> Vec<([f64; 2], [f64; 2])> {
+    let mut v = Vec::new();
+    for i in 0..4 {
+        for j in 0..4 {
+            let (u, w) = (i as f64 * 50.0, j as f64 * 40.0);
+            v.push(([u, w], [1.5 * u + 0.2 * w + 10.0, -0.1 * u + 0.9 * w - 4.0]));
+        }
+    }
+    v
+}
+
+#[test]
+fn test_cleaned_removes_planted_blunders_worst_first() {
+    let clean = clean_pairs();
+    let mut pairs = clean.clone();
+    pairs[5].1[0] += 40.0; // moderate blunder
+    pairs[11].1[1] -= 150.0; // gross blunder
+    let (fit, removed) = fit_affine_cleaned(&pairs, 1.0, 4).unwrap();
+    assert_eq!(removed, vec![11, 5]);
+
+    let subset: Vec<_> = clean
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != 5 && *i != 11)
+        .map(|(_, p)| *p)
+        .collect();
+    let expected = fit_affine_from_pairs(&subset).unwrap();
+    for (a, b) in fit.params.iter().zip(expected.params.iter()) {
+        assert_relative_eq!(a, b, epsilon = 1e-8);
+    }
+}
+
+#[test]
+fn test_cleaned_stops_at_min_points() {
+    let mut pairs = clean_pairs()[..5].to_vec();
+    pairs[0].1[0] += 30.0;
+    pairs[1].1[1] += 30.0;
+    // Allowing only one removal keeps 4 of 5 points even though one is bad
+    let (_, removed) = fit_affine_cleaned(&pairs, 0.5, 4).unwrap();
+    assert_eq!(removed.len(), 1);
+    // The floor is never below the 3 points an affine needs
+    let (_, removed) = fit_affine_cleaned(&pairs, 0.0, 0).unwrap();
+    assert_eq!(removed.len(), 2);
+}
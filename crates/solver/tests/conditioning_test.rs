@@ -0,0 +1,90 @@
+use nalgebra::{DMatrix, DVector, Vector2};
+use solver::{fit_affine_from_pairs, Transform};
+use types::Affine;
+
+/// The pre-normalization solver: design matrix in raw pixel coordinates.
+fn naive_affine(pairs: &[([f64; 2], [f64; 2])]) -> [f64; 6] {
+    let n = pairs.len();
+    let mut a = DMatrix::<f64>::zeros(2 * n, 6);
+    let mut b = DVector::<f64>::zeros(2 * n);
+    for (i, (src, dst)) in pairs.iter().enumerate() {
+        a[(2 * i, 0)] = src[0];
+        a[(2 * i, 1)] = src[1];
+        a[(2 * i, 4)] = 1.0;
+        a[(2 * i + 1, 2)] = src[0];
+        a[(2 * i + 1, 3)] = src[1];
+        a[(2 * i + 1, 5)] = 1.0;
+        b[2 * i] = dst[0];
+        b[2 * i + 1] = dst[1];
+    }
+    let x = a.svd(true, true).solve(&b, 1e-6).unwrap();
+    [x[0], x[1], x[2], x[3], x[4], x[5]]
+}
+
+fn max_residual(params: [f64; 6], pairs: &[([f64; 2], [f64; 2])]) -> f64 {
+    let t = Affine { params };
+    pairs
+        .iter()
+        .map(|(s, d)| (t.apply(&Vector2::from(*s)) - Vector2::from(*d)).norm())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn test_normalized_affine_accurate_at_huge_coordinates() {
+    // A small patch of control points deep inside a ~100M-pixel-wide frame
+    let truth = Affine {
+        params: [0.5, 0.01, -0.02, 0.5, 1234.0, -5678.0],
+    };
+    let pairs: Vec<([f64; 2], [f64; 2])> = (0..12)
+        .map(|i| {
+            let src = [
+                1.0e8 + (i % 4) as f64 * 97.0,
+                1.0e8 + (i / 4) as f64 * 131.0,
+            ];
+            let d = truth.apply(&Vector2::from(src));
+            (src, [d.x, d.y])
+        })
+        .collect();
+
+    let fit = fit_affine_from_pairs(&pairs).unwrap();
+    let normalized = max_residual(fit.params, &pairs);
+    let naive = max_residual(naive_affine(&pairs), &pairs);
+    assert!(normalized < 1e-4, "normalized residual {}", normalized);
+    assert!(
+        naive > 100.0 * normalized,
+        "naive {} vs normalized {}",
+        naive,
+        normalized
+    );
+    for k in 0..4 {
+        assert!((fit.params[k] - truth.params[k]).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_affine_accurate_at_utm_scale_destinations() {
+    // Map pixels to UTM meters: northings in the millions, 5 cm pixels
+    let truth = Affine {
+        params: [0.05, 0.0012, 0.0009, -0.05, 431234.567, 5270987.654],
+    };
+    let pairs: Vec<([f64; 2], [f64; 2])> = (0..20)
+        .map(|i| {
+            let src = [(i % 5) as f64 * 997.0 + 13.0, (i / 5) as f64 * 811.0 + 7.0];
+            let d = truth.apply(&Vector2::from(src));
+            (src, [d.x, d.y])
+        })
+        .collect();
+    let fit = fit_affine_from_pairs(&pairs).unwrap();
+    let local = max_residual(fit.params, &pairs);
+    let naive = max_residual(naive_affine(&pairs), &pairs);
+    // Within an ulp or so of the coordinates themselves (~1e-9 m here)
+    assert!(local < 2e-9, "local-origin residual {}", local);
+    assert!(naive > 10.0 * local, "naive {} vs local {}", naive, local);
+    // The translation carries the large magnitude and comes back exactly
+    // rather than with the centroid's rounding error
+    assert!((fit.params[4] - truth.params[4]).abs() < 1e-10);
+    assert!((fit.params[5] - truth.params[5]).abs() < 1e-10);
+    for k in 0..4 {
+        assert!((fit.params[k] - truth.params[k]).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,104 @@
+use approx::assert_relative_eq;
+use solver::{
+    convex_hull, coverage_ratio, empty_quadrants, suggest_control_points, well_distributed,
+};
+
+fn pairs(src: &[[f64; 2]]) -> Vec<([f64; 2], [f64; 2])> {
+    src.iter().map(|s| (*s, *s)).collect()
+}
+
+#[test]
+fn test_clustered_points_have_low_coverage() {
+    let img = [1000.0, 800.0];
+    let clustered = pairs(&[
+        [100.0, 100.0],
+        [150.0, 100.0],
+        [150.0, 140.0],
+        [100.0, 140.0],
+        [125.0, 120.0],
+    ]);
+    assert_relative_eq!(
+        coverage_ratio(&clustered, img),
+        50.0 * 40.0 / 800000.0,
+        epsilon = 1e-12
+    );
+    assert!(!well_distributed(&clustered, img));
+    assert_eq!(
+        empty_quadrants(&clustered, img),
+        vec!["upper-right", "lower-left", "lower-right"]
+    );
+}
+
+#[test]
+fn test_spread_points_have_high_coverage() {
+    let img = [1000.0, 800.0];
+    let spread = pairs(&[
+        [50.0, 40.0],
+        [950.0, 40.0],
+        [950.0, 760.0],
+        [50.0, 760.0],
+        [500.0, 400.0],
+    ]);
+    assert_relative_eq!(
+        coverage_ratio(&spread, img),
+        900.0 * 720.0 / 800000.0,
+        epsilon = 1e-12
+    );
+    assert!(well_distributed(&spread, img));
+    assert!(empty_quadrants(&spread, img).is_empty());
+    // Collinear points enclose no area
+    assert_eq!(
+        coverage_ratio(&pairs(&[[0.0, 0.0], [10.0, 10.0], [20.0, 20.0]]), img),
+        0.0
+    );
+}
+
+#[test]
+fn test_suggestions_fill_the_empty_regions() {
+    let img = [1000.0, 800.0];
+    let clustered = pairs(&[
+        [100.0, 100.0],
+        [150.0, 100.0],
+        [150.0, 140.0],
+        [100.0, 140.0],
+    ]);
+    let picks = suggest_control_points(&clustered, img, 3);
+    assert_eq!(picks.len(), 3);
+    // The far corner comes first, then the other two empty corners
+    assert_relative_eq!(picks[0][0], 950.0, epsilon = 1e-9);
+    assert_relative_eq!(picks[0][1], 760.0, epsilon = 1e-9);
+    assert_eq!(
+        empty_quadrants(&pairs(&picks), img),
+        vec!["upper-left"],
+        "{:?}",
+        picks
+    );
+    // Adding them makes the set well distributed
+    let mut all = clustered.clone();
+    all.extend(pairs(&picks));
+    assert!(well_distributed(&all, img));
+
+    assert!(suggest_control_points(&clustered, img, 0).is_empty());
+    assert!(suggest_control_points(&clustered, [0.0, 800.0], 2).is_empty());
+    assert_eq!(suggest_control_points(&[], img, 2).len(), 2);
+}
+
+#[test]
+fn test_convex_hull_drops_interior_and_edge_points() {
+    let pts = [
+        [0.0, 0.0],
+        [4.0, 0.0],
+        [2.0, 0.0], // on an edge
+        [4.0, 3.0],
+        [1.0, 1.0], // interior
+        [0.0, 3.0],
+        [4.0, 3.0], // duplicate
+        [2.0, 4.0],
+    ];
+    assert_eq!(
+        convex_hull(&pts),
+        vec![[0.0, 0.0], [4.0, 0.0], [4.0, 3.0], [2.0, 4.0], [0.0, 3.0]]
+    );
+    assert!(convex_hull(&pts[..2]).is_empty());
+    assert!(convex_hull(&[[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]]).is_empty());
+}
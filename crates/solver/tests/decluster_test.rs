@@ -0,0 +1,76 @@
+use approx::assert_relative_eq;
+use nalgebra::Vector2;
+use solver::{
+    fit_affine_from_pairs, fit_affine_weighted, fit_similarity_from_pairs, fit_similarity_weighted,
+    spatial_decluster_weights, Transform,
+};
+
+fn truth(p: [f64; 2]) -> [f64; 2] {
+    [
+        1.2 * p[0] - 0.1 * p[1] + 40.0,
+        0.1 * p[0] + 1.2 * p[1] - 25.0,
+    ]
+}
+
+/// Four exact corner points plus a dense cluster whose targets are all
+/// biased 5 px in x (e.g. a misread local feature clicked repeatedly).
+fn clustered_pairs() -> Vec<([f64; 2], [f64; 2])> {
+    let mut pairs: Vec<_> = [[0.0, 0.0], [1000.0, 0.0], [0.0, 1000.0], [1000.0, 1000.0]]
+        .into_iter()
+        .map(|p| (p, truth(p)))
+        .collect();
+    for k in 0..20 {
+        let p = [500.0 + (k % 5) as f64 * 2.0, 500.0 + (k / 5) as f64 * 2.0];
+        let [x, y] = truth(p);
+        pairs.push((p, [x + 5.0, y]));
+    }
+    pairs
+}
+
+fn max_corner_error<T: Transform>(t: &T, pairs: &[([f64; 2], [f64; 2])]) -> f64 {
+    pairs[..4]
+        .iter()
+        .map(|(s, d)| (t.apply(&Vector2::from(*s)) - Vector2::from(*d)).norm())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn test_cluster_is_down_weighted() {
+    let pairs = clustered_pairs();
+    let w = spatial_decluster_weights(&pairs, 50.0);
+    assert_eq!(w.len(), pairs.len());
+    assert_relative_eq!(w.iter().sum::<f64>(), pairs.len() as f64, epsilon = 1e-9);
+    // Each cluster point has 20 neighbours, each corner only itself
+    for c in &w[4..] {
+        assert_relative_eq!(w[0] / c, 20.0, epsilon = 1e-9);
+    }
+
+    let plain = fit_affine_from_pairs(&pairs).unwrap();
+    let balanced = fit_affine_weighted(&pairs, &w).unwrap();
+    let (e_plain, e_balanced) = (
+        max_corner_error(&plain, &pairs),
+        max_corner_error(&balanced, &pairs),
+    );
+    assert!(e_balanced < 0.5 * e_plain, "{} vs {}", e_balanced, e_plain);
+
+    let plain = fit_similarity_from_pairs(&pairs).unwrap();
+    let balanced = fit_similarity_weighted(&pairs, &w).unwrap();
+    assert!(max_corner_error(&balanced, &pairs) < 0.5 * max_corner_error(&plain, &pairs));
+}
+
+#[test]
+fn test_unit_weights_match_unweighted_fits() {
+    let pairs = clustered_pairs();
+    let ones = vec![1.0; pairs.len()];
+    assert_eq!(spatial_decluster_weights(&pairs, 0.0), ones);
+    assert_eq!(
+        fit_affine_weighted(&pairs, &ones).unwrap().params,
+        fit_affine_from_pairs(&pairs).unwrap().params
+    );
+    assert_eq!(
+        fit_similarity_weighted(&pairs, &ones).unwrap().params,
+        fit_similarity_from_pairs(&pairs).unwrap().params
+    );
+    assert!(fit_affine_weighted(&pairs, &ones[1..]).is_err());
+    assert!(fit_affine_weighted(&pairs, &vec![0.0; pairs.len()]).is_err());
+}
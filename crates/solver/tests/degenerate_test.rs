@@ -0,0 +1,67 @@
+use solver::{fit_affine_from_pairs, fit_similarity_from_pairs, SolverError};
+
+#[test]
+fn test_collinear_sources_report_rank() {
+    // Source points on one line: u and v columns are dependent
+    let pairs: Vec<_> = (0..5)
+        .map(|i| {
+            let t = i as f64 * 10.0;
+            ([t, 2.0 * t + 1.0], [t + 3.0, -t])
+        })
+        .collect();
+    let err = fit_affine_from_pairs(&pairs).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<SolverError>(),
+        Some(&SolverError::Degenerate {
+            rank: 4,
+            required: 6
+        })
+    );
+}
+
+#[test]
+fn test_coincident_sources_are_degenerate() {
+    let pairs = vec![([5.0, 5.0], [1.0, 1.0]); 4];
+    let err = fit_affine_from_pairs(&pairs).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<SolverError>(),
+        Some(SolverError::Degenerate { rank, .. }) if *rank < 6
+    ));
+}
+
+#[test]
+fn test_non_finite_pairs_are_skipped() {
+    let mut pairs = vec![
+        ([0.0, 0.0], [1.0, 2.0]),
+        ([10.0, 0.0], [21.0, 2.0]),
+        ([0.0, 10.0], [1.0, 22.0]),
+    ];
+    pairs.push(([f64::NAN, 3.0], [4.0, 5.0]));
+    pairs.push(([3.0, 3.0], [f64::INFINITY, 5.0]));
+    let fit = fit_affine_from_pairs(&pairs).unwrap();
+    assert!(fit.params.iter().all(|p| p.is_finite()));
+    assert!((fit.params[0] - 2.0).abs() < 1e-9);
+    // Dropping the bad pairs can leave too few to fit
+    assert!(fit_affine_from_pairs(&pairs[2..]).is_err());
+}
+
+#[test]
+fn test_overflowing_coordinates_report_numerical() {
+    // Finite inputs whose centroid sums overflow to infinity
+    let big = 1.5e308;
+    let pairs = vec![
+        ([0.0, 0.0], [big, big]),
+        ([10.0, 0.0], [big, -big]),
+        ([0.0, 10.0], [-big, big]),
+        ([10.0, 10.0], [big, big]),
+    ];
+    for err in [
+        fit_affine_from_pairs(&pairs).unwrap_err(),
+        fit_similarity_from_pairs(&pairs).unwrap_err(),
+    ] {
+        assert!(matches!(
+            err.downcast_ref::<SolverError>(),
+            Some(SolverError::Numerical(_))
+        ));
+    }
+}
@@ -0,0 +1,54 @@
+use solver::detrend_residuals;
+
+#[test]
+fn test_planted_linear_trend_is_recovered() {
+    let mut pairs = Vec::new();
+    let mut planted = Vec::new();
+    let mut residuals = Vec::new();
+    for i in 0..8 {
+        for j in 0..6 {
+            let (u, v) = (i as f64 * 100.0, j as f64 * 80.0);
+            pairs.push(([u, v], [u, v]));
+            // Linear trend plus deterministic zero-mean jitter
+            let t = [0.01 * u - 2.0, -0.005 * v + 0.002 * u];
+            let n = [
+                0.3 * ((i * 7 + j * 3) % 5) as f64 - 0.6,
+                0.2 * ((i * 3 + j * 5) % 4) as f64 - 0.3,
+            ];
+            planted.push(t);
+            residuals.push([t[0] + n[0], t[1] + n[1]]);
+        }
+    }
+    let (trend, noise) = detrend_residuals(&pairs, &residuals, 1).unwrap();
+    assert_eq!(trend.len(), pairs.len());
+    for ((t, p), (n, r)) in trend.iter().zip(&planted).zip(noise.iter().zip(&residuals)) {
+        assert!((t[0] - p[0]).abs() < 0.2 && (t[1] - p[1]).abs() < 0.2);
+        // The split is exact
+        assert!((t[0] + n[0] - r[0]).abs() < 1e-12 && (t[1] + n[1] - r[1]).abs() < 1e-12);
+    }
+    let rms = |v: &[[f64; 2]]| {
+        (v.iter().map(|r| r[0] * r[0] + r[1] * r[1]).sum::<f64>() / v.len() as f64).sqrt()
+    };
+    // What is left is the jitter, much smaller than the trend
+    assert!(rms(&noise) < 0.5, "{}", rms(&noise));
+    assert!(rms(&trend) > 3.0 * rms(&noise));
+
+    // Degree 0 removes only the mean
+    let (mean, _) = detrend_residuals(&pairs, &residuals, 0).unwrap();
+    let avg = residuals.iter().map(|r| r[0]).sum::<f64>() / residuals.len() as f64;
+    assert!(mean.iter().all(|m| (m[0] - avg).abs() < 1e-9));
+}
+
+#[test]
+fn test_underdetermined_trend_is_finite() {
+    let pairs = vec![([0.0, 0.0], [0.0, 0.0]), ([10.0, 0.0], [0.0, 0.0])];
+    let residuals = [[1.0, -1.0], [3.0, 1.0]];
+    let (trend, noise) = detrend_residuals(&pairs, &residuals, 2).unwrap();
+    assert!(trend.iter().chain(&noise).flatten().all(|x| x.is_finite()));
+    // Two points on a line: a surface can pass through both
+    assert!(noise.iter().flatten().all(|x| x.abs() < 1e-9));
+    assert_eq!(detrend_residuals(&[], &[], 1).unwrap(), (vec![], vec![]));
+    // A residual list that doesn't match the pairs is an error, not a panic
+    let err = detrend_residuals(&pairs, &residuals[..1], 1).unwrap_err();
+    assert!(err.to_string().contains("one residual per pair"), "{}", err);
+}
@@ -0,0 +1,46 @@
+use approx::assert_relative_eq;
+use solver::residual_ellipse;
+use types::Affine;
+
+fn identity() -> Affine {
+    Affine {
+        params: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+    }
+}
+
+#[test]
+fn test_elongated_residuals_give_oriented_ellipse() {
+    // Residuals (identity prediction - dst) spread ±3 along 30° and ±1
+    // across it, around a bias of (0.5, -0.25)
+    let angle = 30f64.to_radians();
+    let (c, s) = (angle.cos(), angle.sin());
+    let mut pairs = Vec::new();
+    for (i, (a, b)) in [(3.0, 1.0), (3.0, -1.0), (-3.0, 1.0), (-3.0, -1.0)]
+        .iter()
+        .enumerate()
+    {
+        let r = [0.5 + c * a - s * b, -0.25 + s * a + c * b];
+        let src = [i as f64 * 100.0, i as f64 * 37.0];
+        pairs.push((src, [src[0] - r[0], src[1] - r[1]]));
+    }
+    let e = residual_ellipse(&identity(), &pairs);
+    assert_relative_eq!(e.center[0], 0.5, epsilon = 1e-12);
+    assert_relative_eq!(e.center[1], -0.25, epsilon = 1e-12);
+    assert_relative_eq!(e.major, 3.0, epsilon = 1e-12);
+    assert_relative_eq!(e.minor, 1.0, epsilon = 1e-12);
+    assert_relative_eq!(e.angle, angle, epsilon = 1e-12);
+}
+
+#[test]
+fn test_axis_direction_is_folded_into_half_turn() {
+    // Spread along -60° is the same axis as +120°; reported as -60°
+    let angle = (-60f64).to_radians();
+    let pairs: Vec<_> = [-2.0, -1.0, 1.0, 2.0]
+        .iter()
+        .map(|a| ([0.0, 0.0], [-a * angle.cos(), -a * angle.sin()]))
+        .collect();
+    let e = residual_ellipse(&identity(), &pairs);
+    assert_relative_eq!(e.angle, angle, epsilon = 1e-12);
+    assert_relative_eq!(e.minor, 0.0, epsilon = 1e-6);
+    assert_eq!(residual_ellipse(&identity(), &[]), Default::default());
+}
@@ -0,0 +1,30 @@
+use approx::assert_relative_eq;
+use solver::{affine_to_gdal_geotransform, gdal_geotransform_to_affine};
+
+#[test]
+fn north_up_world_file_maps_to_corner_origin() {
+    // 10 m pixels, first pixel centered at (500005, 4999995)
+    let esri = [10.0, 0.0, 0.0, -10.0, 500_005.0, 4_999_995.0];
+    let gt = affine_to_gdal_geotransform(&esri);
+    assert_eq!(gt, [500_000.0, 10.0, 0.0, 5_000_000.0, 0.0, -10.0]);
+}
+
+#[test]
+fn esri_gdal_round_trip_with_rotation() {
+    let esri = [0.8, -0.35, 0.3, -0.9, 1234.5, -678.25];
+    let gt = affine_to_gdal_geotransform(&esri);
+    // Both forms must place pixel (col, row) at the same world point, with
+    // GDAL addressing pixel centers at +0.5
+    for (col, row) in [(0.0, 0.0), (12.0, 7.0), (-3.0, 40.0)] {
+        let xe = esri[0] * col + esri[1] * row + esri[4];
+        let ye = esri[2] * col + esri[3] * row + esri[5];
+        let xg = gt[0] + (col + 0.5) * gt[1] + (row + 0.5) * gt[2];
+        let yg = gt[3] + (col + 0.5) * gt[4] + (row + 0.5) * gt[5];
+        assert_relative_eq!(xe, xg, epsilon = 1e-9);
+        assert_relative_eq!(ye, yg, epsilon = 1e-9);
+    }
+    let back = gdal_geotransform_to_affine(&gt);
+    for (x, y) in back.iter().zip(esri.iter()) {
+        assert_relative_eq!(x, y, epsilon = 1e-9);
+    }
+}
@@ -0,0 +1,26 @@
+use approx::assert_relative_eq;
+use solver::{idw_interpolate, IDW_POWER};
+
+#[test]
+fn test_idw_exact_at_samples_and_weighted_between() {
+    let samples = [([0.0, 0.0], 1.0), ([10.0, 0.0], 3.0)];
+    assert_eq!(idw_interpolate(&samples, [10.0, 0.0], IDW_POWER), Some(3.0));
+    // Midpoint: equal weights
+    assert_relative_eq!(
+        idw_interpolate(&samples, [5.0, 0.0], IDW_POWER).unwrap(),
+        2.0
+    );
+    // At x = 2.5, d = 2.5 and 7.5: weights 1/6.25 and 1/56.25 (ratio 9:1)
+    assert_relative_eq!(
+        idw_interpolate(&samples, [2.5, 0.0], IDW_POWER).unwrap(),
+        (9.0 * 1.0 + 3.0) / 10.0,
+        epsilon = 1e-12
+    );
+    // Power 1: weights 1/2.5 and 1/7.5 (ratio 3:1)
+    assert_relative_eq!(
+        idw_interpolate(&samples, [2.5, 0.0], 1.0).unwrap(),
+        (3.0 * 1.0 + 3.0) / 4.0,
+        epsilon = 1e-12
+    );
+    assert_eq!(idw_interpolate(&[], [0.0, 0.0], IDW_POWER), None);
+}
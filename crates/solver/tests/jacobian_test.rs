@@ -0,0 +1,45 @@
+use approx::assert_relative_eq;
+use solver::{local_jacobian, similarity_to_affine};
+use types::{Affine, Similarity};
+
+#[test]
+fn test_affine_jacobian_is_constant_and_decomposes() {
+    // R(θ) * [[sx, k*sy], [0, sy]] with θ = 0.4, sx = 2, sy = 0.5, k = 0.3
+    let (theta, sx, sy, k) = (0.4f64, 2.0, 0.5, 0.3);
+    let (c, s) = (theta.cos(), theta.sin());
+    let m = [[sx, k * sy], [0.0, sy]];
+    let aff = Affine {
+        params: [
+            c * m[0][0] - s * m[1][0],
+            c * m[0][1] - s * m[1][1],
+            s * m[0][0] + c * m[1][0],
+            s * m[0][1] + c * m[1][1],
+            1000.0,
+            -250.0,
+        ],
+    };
+    let j0 = local_jacobian(&aff, [0.0, 0.0]);
+    for p in [[512.0, 384.0], [-3000.0, 12000.0]] {
+        let j = local_jacobian(&aff, p);
+        assert_relative_eq!(j.scale_x, j0.scale_x, epsilon = 1e-9);
+        assert_relative_eq!(j.scale_y, j0.scale_y, epsilon = 1e-9);
+        assert_relative_eq!(j.rotation, j0.rotation, epsilon = 1e-9);
+        assert_relative_eq!(j.shear, j0.shear, epsilon = 1e-9);
+    }
+    assert_relative_eq!(j0.scale_x, sx, epsilon = 1e-9);
+    assert_relative_eq!(j0.scale_y, sy, epsilon = 1e-9);
+    assert_relative_eq!(j0.rotation, theta, epsilon = 1e-9);
+    assert_relative_eq!(j0.shear, k, epsilon = 1e-9);
+}
+
+#[test]
+fn test_similarity_jacobian_has_no_shear() {
+    let sim = Similarity {
+        params: [0.25, -1.2, 5.0, 6.0],
+    };
+    let j = local_jacobian(&similarity_to_affine(&sim), [40.0, 90.0]);
+    assert_relative_eq!(j.scale_x, 0.25, epsilon = 1e-12);
+    assert_relative_eq!(j.scale_y, 0.25, epsilon = 1e-12);
+    assert_relative_eq!(j.rotation, -1.2, epsilon = 1e-12);
+    assert_relative_eq!(j.shear, 0.0, epsilon = 1e-12);
+}
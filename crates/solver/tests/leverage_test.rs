@@ -0,0 +1,51 @@
+use approx::assert_relative_eq;
+use solver::{leverages, SolverError};
+
+fn at(src: [f64; 2]) -> ([f64; 2], [f64; 2]) {
+    (src, [src[0] * 0.5, src[1] * 0.5])
+}
+
+#[test]
+fn test_isolated_point_has_high_leverage() {
+    // 3x3 cluster near the origin plus one point alone in the far corner
+    let mut pairs = Vec::new();
+    for i in 0..3 {
+        for j in 0..3 {
+            pairs.push(at([100.0 + 10.0 * i as f64, 100.0 + 10.0 * j as f64]));
+        }
+    }
+    pairs.push(at([4000.0, 3000.0]));
+    let h = leverages(&pairs).unwrap();
+    assert_eq!(h.len(), pairs.len());
+    assert_relative_eq!(h.iter().sum::<f64>(), 3.0, epsilon = 1e-9);
+    let isolated = h[9];
+    let clustered = h[..9].iter().cloned().fold(0.0, f64::max);
+    assert!(isolated > 0.99, "{}", isolated);
+    assert!(clustered < 0.5, "{}", clustered);
+    assert!(isolated > 2.0 * clustered);
+    assert!(isolated > 2.0 * 3.0 / pairs.len() as f64);
+    for v in &h {
+        assert!(*v >= 1.0 / pairs.len() as f64 - 1e-12 && *v <= 1.0 + 1e-12);
+    }
+}
+
+#[test]
+fn test_symmetric_layout_has_equal_leverage() {
+    let pairs: Vec<_> = [[0.0, 0.0], [10.0, 0.0], [0.0, 10.0], [10.0, 10.0]]
+        .iter()
+        .map(|p| at(*p))
+        .collect();
+    for v in leverages(&pairs).unwrap() {
+        assert_relative_eq!(v, 0.75, epsilon = 1e-12);
+    }
+}
+
+#[test]
+fn test_collinear_sources_are_degenerate() {
+    let pairs: Vec<_> = (0..5).map(|i| at([i as f64, 2.0 * i as f64])).collect();
+    let err = leverages(&pairs).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<SolverError>(),
+        Some(SolverError::Degenerate { required: 3, .. })
+    ));
+}
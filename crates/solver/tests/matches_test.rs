@@ -0,0 +1,87 @@
+use approx::assert_relative_eq;
+use nalgebra::Vector2;
+use solver::{fit_from_matches, FitMethod, Transform};
+use types::{Affine, Similarity, TransformKind};
+
+struct Scene {
+    src: Vec<[f64; 2]>,
+    dst: Vec<[f64; 2]>,
+    matches: Vec<(usize, usize)>,
+}
+
+/// 30 keypoints on a grid, their images under `f`, matches in shuffled index
+/// order with the last 6 deliberately wrong.
+fn scene(f: impl Fn([f64; 2]) -> [f64; 2]) -> Scene {
+    let src: Vec<[f64; 2]> = (0..30)
+        .map(|i| [(i % 6) as f64 * 40.0 + 3.0, (i / 6) as f64 * 35.0 + 7.0])
+        .collect();
+    // dst keypoints stored in reverse order so match indices differ
+    let dst: Vec<[f64; 2]> = src.iter().rev().map(|p| f(*p)).collect();
+    let mut matches: Vec<(usize, usize)> = (0..24).map(|i| (i, 29 - i)).collect();
+    for i in 24..30 {
+        matches.push((i, (i * 7) % 24)); // wrong partner
+    }
+    Scene { src, dst, matches }
+}
+
+#[test]
+fn test_fit_from_matches_similarity_rejects_outliers() {
+    let truth = Similarity {
+        params: [1.3, -0.4, 50.0, 20.0],
+    };
+    let Scene { src, dst, matches } = scene(|p| {
+        let q = truth.apply(&Vector2::from(p));
+        [q.x, q.y]
+    });
+    let fit = fit_from_matches(&src, &dst, &matches, FitMethod::Similarity, 1.0).unwrap();
+    assert_eq!(fit.inliers, (0..24).collect::<Vec<_>>());
+    let TransformKind::Similarity(t) = fit.transform else {
+        panic!("expected similarity");
+    };
+    for k in 0..4 {
+        assert_relative_eq!(t.params[k], truth.params[k], epsilon = 1e-9);
+    }
+}
+
+#[test]
+fn test_fit_from_matches_affine_and_bad_index() {
+    let truth = Affine {
+        params: [1.1, 0.2, -0.1, 0.9, -5.0, 12.0],
+    };
+    let Scene { src, dst, matches } = scene(|p| {
+        let q = truth.apply(&Vector2::from(p));
+        [q.x, q.y]
+    });
+    let fit = fit_from_matches(&src, &dst, &matches, FitMethod::Affine, 1.0).unwrap();
+    assert_eq!(fit.inliers.len(), 24);
+    let TransformKind::Affine(t) = fit.transform else {
+        panic!("expected affine");
+    };
+    for k in 0..6 {
+        assert_relative_eq!(t.params[k], truth.params[k], epsilon = 1e-9);
+    }
+
+    let err = fit_from_matches(&src, &dst, &[(0, 99)], FitMethod::Affine, 1.0).unwrap_err();
+    assert!(err.to_string().contains("out of range"));
+}
+
+#[test]
+fn test_fit_from_matches_is_reproducible() {
+    // Noise close to the threshold, so which pairs count as inliers depends
+    // on the samples drawn
+    let Scene { src, dst, matches } = scene(|p| {
+        let wobble = (p[0] * 0.37 + p[1] * 0.11).sin() * 0.9;
+        [1.1 * p[0] - 5.0 + wobble, 0.9 * p[1] + 12.0 - wobble]
+    });
+    let first = fit_from_matches(&src, &dst, &matches, FitMethod::Affine, 1.0).unwrap();
+    for _ in 0..5 {
+        let again = fit_from_matches(&src, &dst, &matches, FitMethod::Affine, 1.0).unwrap();
+        assert_eq!(again.inliers, first.inliers);
+        let (TransformKind::Affine(a), TransformKind::Affine(b)) =
+            (&again.transform, &first.transform)
+        else {
+            panic!("expected affine");
+        };
+        assert_eq!(a.params, b.params);
+    }
+}
@@ -0,0 +1,52 @@
+use solver::{can_solve, min_pairs, quick_align, solve_constraints, FitMethod, SolverError};
+
+#[test]
+fn test_min_pairs_per_method() {
+    for (method, n) in [
+        ("similarity", 2),
+        ("affine", 3),
+        ("homography", 4),
+        ("polynomial2", 6),
+        ("tps", 3),
+    ] {
+        assert_eq!(min_pairs(method), Some(n), "{}", method);
+        assert!(can_solve(method, n).is_ok());
+        assert_eq!(
+            can_solve(method, n - 1),
+            Err(SolverError::TooFewPairs {
+                method: method.to_string(),
+                required: n,
+                got: n - 1,
+            })
+        );
+    }
+    assert_eq!(min_pairs("bogus"), None);
+    assert_eq!(
+        can_solve("bogus", 10),
+        Err(SolverError::UnknownMethod("bogus".into()))
+    );
+}
+
+#[test]
+fn test_fits_enforce_minimum() {
+    let pairs = [([0.0, 0.0], [1.0, 1.0])];
+    let err = quick_align(&pairs).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<SolverError>(),
+        Some(SolverError::TooFewPairs { required: 2, .. })
+    ));
+    let list: Vec<types::ConstraintKind> = (0..2)
+        .map(|i| types::ConstraintKind::PointPair {
+            id: i,
+            src: [i as f64, 0.0],
+            dst: [i as f64, 1.0],
+            dst_real: None,
+            dst_local: None,
+            src_z: None,
+            dst_z: None,
+            weight: 1.0,
+        })
+        .collect();
+    let err = solve_constraints(&list, FitMethod::Affine).unwrap_err();
+    assert_eq!(err.to_string(), "affine needs ≥3 pairs; got 2");
+}
@@ -1,6 +1,22 @@
-use solver::pairs_from_constraints;
+use solver::{
+    duplicate_destinations, pairs_from_constraints, pairs_from_constraints_with_tol,
+    pairs_in_space, CoordinateSpace,
+};
 use types::ConstraintKind;
 
+fn pp(id: u64, src: [f64; 2], dst: [f64; 2]) -> ConstraintKind {
+    ConstraintKind::PointPair {
+        id,
+        src,
+        dst,
+        dst_real: None,
+        dst_local: None,
+        src_z: None,
+        dst_z: None,
+        weight: 1.0,
+    }
+}
+
 #[test]
 fn test_pairs_extraction_filters_bad() {
     let v = vec![
@@ -11,6 +27,8 @@ fn test_pairs_extraction_filters_bad() {
             dst: [10.0, 0.0],
             dst_real: None,
             dst_local: None,
+            src_z: None,
+            dst_z: None,
             weight: 1.0,
         },
         // duplicate
@@ -20,6 +38,8 @@ fn test_pairs_extraction_filters_bad() {
             dst: [10.0, 0.0],
             dst_real: None,
             dst_local: None,
+            src_z: None,
+            dst_z: None,
             weight: 1.0,
         },
         // degenerate (src == dst)
@@ -29,6 +49,8 @@ fn test_pairs_extraction_filters_bad() {
             dst: [5.0, 5.0],
             dst_real: None,
             dst_local: None,
+            src_z: None,
+            dst_z: None,
             weight: 1.0,
         },
         // NaN
@@ -38,6 +60,8 @@ fn test_pairs_extraction_filters_bad() {
             dst: [2.0, 3.0],
             dst_real: None,
             dst_local: None,
+            src_z: None,
+            dst_z: None,
             weight: 1.0,
         },
     ];
@@ -47,3 +71,108 @@ fn test_pairs_extraction_filters_bad() {
     assert_eq!(pairs[0].0, [0.0, 0.0]);
     assert_eq!(pairs[0].1, [10.0, 0.0]);
 }
+
+#[test]
+fn test_duplicate_destinations_reports_conflicting_ids() {
+    let v = vec![
+        pp(1, [10.0, 10.0], [100.0, 100.0]),
+        pp(2, [50.0, 80.0], [100.2, 99.9]),
+        pp(3, [0.0, 0.0], [300.0, 300.0]),
+        // exact duplicate of 3: same source, not a conflict
+        pp(4, [0.0, 0.0], [300.0, 300.0]),
+    ];
+    assert_eq!(duplicate_destinations(&v, 0.5), vec![vec![1, 2]]);
+    assert!(duplicate_destinations(&v, 0.1).is_empty());
+}
+
+#[test]
+fn test_duplicate_destinations_chain_regardless_of_order() {
+    // 1-2 and 2-3 are within tolerance, 1-3 is not: still one group
+    let a = pp(1, [0.0, 0.0], [100.0, 100.0]);
+    let b = pp(2, [40.0, 0.0], [100.4, 100.0]);
+    let c = pp(3, [80.0, 0.0], [100.8, 100.0]);
+    let d = pp(4, [0.0, 40.0], [500.0, 500.0]);
+    let e = pp(5, [0.0, 80.0], [500.1, 500.0]);
+    let orders = [
+        [&a, &b, &c, &d, &e],
+        [&a, &c, &b, &e, &d],
+        [&c, &a, &e, &b, &d],
+        [&e, &c, &d, &a, &b],
+    ];
+    for order in orders {
+        let v: Vec<ConstraintKind> = order.into_iter().cloned().collect();
+        assert_eq!(
+            duplicate_destinations(&v, 0.5),
+            vec![vec![1, 2, 3], vec![4, 5]]
+        );
+    }
+}
+
+#[test]
+fn test_near_duplicates_collapse_within_tolerance() {
+    let v = vec![
+        pp(1, [100.0, 100.0], [50.0, 50.0]),
+        // 0.3 px on both sides: same click
+        pp(2, [100.3, 100.0], [50.0, 50.2]),
+        // src close but dst 2 px away: a different correspondence
+        pp(3, [100.2, 100.1], [52.0, 50.0]),
+        pp(4, [300.0, 20.0], [150.0, 10.0]),
+    ];
+    assert_eq!(pairs_from_constraints(&v).len(), 4);
+    let kept = pairs_from_constraints_with_tol(&v, 0.5);
+    assert_eq!(kept.len(), 3);
+    // First occurrence wins
+    assert_eq!(kept[0], ([100.0, 100.0], [50.0, 50.0]));
+    assert_eq!(kept[1].1, [52.0, 50.0]);
+    assert_eq!(pairs_from_constraints_with_tol(&v, 3.0).len(), 2);
+}
+
+#[test]
+fn test_invalid_dedup_tolerance_means_exact_duplicates_only() {
+    let v = vec![
+        pp(1, [100.0, 100.0], [50.0, 50.0]),
+        pp(2, [100.3, 100.0], [50.0, 50.2]),
+        pp(3, [100.0, 100.0], [50.0, 50.0]),
+    ];
+    for tol in [-0.5, f64::NAN, f64::INFINITY] {
+        let kept = pairs_from_constraints_with_tol(&v, tol);
+        assert_eq!(kept.len(), 2, "tolerance {}", tol);
+    }
+}
+
+#[test]
+fn test_pairs_in_space_picks_destination_and_requires_it() {
+    let pair = |id, dst_real, dst_local| ConstraintKind::PointPair {
+        id,
+        src: [id as f64, 1.0],
+        dst: [5.0, 5.0 + id as f64],
+        dst_real,
+        dst_local,
+        src_z: None,
+        dst_z: None,
+        weight: 1.0,
+    };
+    let v = vec![
+        pair(1, Some([500000.0, 4.1e6]), Some([-12.0, 3.5])),
+        ConstraintKind::Anchor {
+            id: 9,
+            point: [0.0, 0.0],
+        },
+        pair(2, Some([500010.0, 4.1e6]), None),
+    ];
+    assert_eq!(
+        pairs_in_space(&v, CoordinateSpace::Pixels).unwrap(),
+        pairs_from_constraints(&v)
+    );
+    let world = pairs_in_space(&v, CoordinateSpace::World).unwrap();
+    assert_eq!(
+        world,
+        vec![
+            ([1.0, 1.0], [500000.0, 4.1e6]),
+            ([2.0, 1.0], [500010.0, 4.1e6])
+        ]
+    );
+    let err = pairs_in_space(&v, CoordinateSpace::LocalMeters).unwrap_err();
+    assert!(err.to_string().contains("[2]"), "{}", err);
+    assert!(pairs_in_space(&v[..1], CoordinateSpace::LocalMeters).is_ok());
+}
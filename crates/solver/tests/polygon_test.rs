@@ -0,0 +1,61 @@
+use approx::assert_relative_eq;
+use solver::{densify_ring, transform_polygon, MAX_DENSIFY_STEPS};
+use types::Affine;
+
+const SQUARE: [[f64; 2]; 4] = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]];
+
+#[test]
+fn test_affine_maps_square_to_parallelogram() {
+    // x = 2u + 0.5v + 3, y = 0.25u + v - 1
+    let aff = Affine {
+        params: [2.0, 0.5, 0.25, 1.0, 3.0, -1.0],
+    };
+    let out = transform_polygon(&aff, &SQUARE, None).unwrap();
+    let expected = [[3.0, -1.0], [23.0, 1.5], [28.0, 11.5], [8.0, 9.0]];
+    assert_eq!(out.len(), 4);
+    for (o, e) in out.iter().zip(expected.iter()) {
+        assert_relative_eq!(o[0], e[0], epsilon = 1e-12);
+        assert_relative_eq!(o[1], e[1], epsilon = 1e-12);
+    }
+    // Opposite sides stay parallel and equal
+    assert_relative_eq!(
+        out[1][0] - out[0][0],
+        out[2][0] - out[3][0],
+        epsilon = 1e-12
+    );
+    assert_relative_eq!(
+        out[1][1] - out[0][1],
+        out[2][1] - out[3][1],
+        epsilon = 1e-12
+    );
+}
+
+#[test]
+fn test_densify_splits_every_edge_including_closing_one() {
+    let ring = densify_ring(&SQUARE, 2.5).unwrap();
+    // Four 10 px edges in four 2.5 px pieces each
+    assert_eq!(ring.len(), 16);
+    assert_eq!(ring[0], SQUARE[0]);
+    assert_eq!(ring[4], SQUARE[1]);
+    assert_eq!(ring[15], [0.0, 2.5]);
+    let aff = Affine {
+        params: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+    };
+    assert_eq!(transform_polygon(&aff, &SQUARE, Some(2.5)).unwrap(), ring);
+}
+
+#[test]
+fn test_densify_spacing_is_validated_and_bounded() {
+    let edge = [[0.0, 0.0], [10_000.0, 0.0]];
+    // Two 10,000 px edges (there and back) at 1e-9 px would be 2e13 points
+    let ring = densify_ring(&edge, 1e-9).unwrap();
+    assert_eq!(ring.len(), 2 * MAX_DENSIFY_STEPS);
+    assert_eq!(ring[MAX_DENSIFY_STEPS], edge[1]);
+    for bad in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(densify_ring(&SQUARE, bad).is_err(), "{}", bad);
+    }
+    let aff = Affine {
+        params: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+    };
+    assert!(transform_polygon(&aff, &SQUARE, Some(0.0)).is_err());
+}
@@ -0,0 +1,101 @@
+use solver::{
+    affine_to_proj, proj_to_affine, similarity_to_proj, PixelOrigin, ProjPipeline, ProjStep,
+};
+use types::{Affine, Similarity};
+
+/// The string format `affine_to_proj` produced before it built a pipeline
+fn legacy_affine_string(p: [f64; 6], corner: bool) -> String {
+    format!(
+        "+proj=pipeline{} +step +proj=affine +xoff={:.17} +yoff={:.17} +s11={:.17} +s12={:.17} +s21={:.17} +s22={:.17}",
+        if corner {
+            " +step +proj=affine +xoff=-0.5 +yoff=-0.5"
+        } else {
+            ""
+        },
+        p[4],
+        p[5],
+        p[0],
+        p[1],
+        p[2],
+        p[3]
+    )
+}
+
+#[test]
+fn test_pipeline_matches_string_functions() {
+    let aff = Affine {
+        params: [0.5, -0.1, 0.2, -0.5, 431000.25, 5270000.75],
+    };
+    for (origin, corner) in [(PixelOrigin::Center, false), (PixelOrigin::Corner, true)] {
+        let expected = legacy_affine_string(aff.params, corner);
+        assert_eq!(
+            ProjPipeline::from_affine(&aff, origin).to_string(),
+            expected
+        );
+        assert_eq!(affine_to_proj(&aff, origin), expected);
+    }
+
+    let sim = Similarity {
+        params: [2.0, 0.3, 10.0, -20.0],
+    };
+    let (c, s) = (0.3f64.cos(), 0.3f64.sin());
+    let expected = legacy_affine_string([2.0 * c, -2.0 * s, 2.0 * s, 2.0 * c, 10.0, -20.0], true);
+    assert_eq!(
+        ProjPipeline::from_similarity(&sim, PixelOrigin::Corner).to_string(),
+        expected
+    );
+    assert_eq!(similarity_to_proj(&sim, PixelOrigin::Corner), expected);
+}
+
+#[test]
+fn test_pipeline_steps_can_be_inspected_and_extended() {
+    let aff = Affine {
+        params: [1.0, 0.0, 0.0, -1.0, 500000.0, 4100000.0],
+    };
+    let pipeline = ProjPipeline::from_affine(&aff, PixelOrigin::Corner)
+        .then(ProjStep::Raw("+proj=utm +zone=10 +inv".to_string()));
+    assert_eq!(pipeline.steps.len(), 3);
+    assert_eq!(
+        pipeline.steps[0],
+        ProjStep::Offset {
+            xoff: -0.5,
+            yoff: -0.5
+        }
+    );
+    assert!(matches!(
+        pipeline.steps[1],
+        ProjStep::Affine { xoff, s22, .. } if xoff == 500000.0 && s22 == -1.0
+    ));
+    assert!(pipeline
+        .to_string()
+        .ends_with(" +step +proj=utm +zone=10 +inv"));
+}
+
+#[test]
+fn test_proj_to_affine_round_trips_both_origins() {
+    let aff = Affine {
+        params: [0.75, -0.125, 0.2, -1.5, 480123.25, 4_310_987.5],
+    };
+    for origin in [PixelOrigin::Center, PixelOrigin::Corner] {
+        let back = proj_to_affine(&affine_to_proj(&aff, origin), origin).unwrap();
+        for (x, y) in back.params.iter().zip(aff.params.iter()) {
+            assert!((x - y).abs() <= 1e-9 * y.abs().max(1.0), "{:?}", origin);
+        }
+    }
+    // A bare step works too, with PROJ's defaults for absent terms
+    let bare = proj_to_affine("+proj=affine +xoff=10 +s22=-2", PixelOrigin::Center).unwrap();
+    assert_eq!(bare.params, [1.0, 0.0, 0.0, -2.0, 10.0, 0.0]);
+}
+
+#[test]
+fn test_proj_to_affine_rejects_non_affine_steps() {
+    let aff = Affine {
+        params: [1.0, 0.0, 0.0, -1.0, 500000.0, 4100000.0],
+    };
+    let with_utm = ProjPipeline::from_affine(&aff, PixelOrigin::Center)
+        .then(ProjStep::Raw("+proj=utm +zone=10 +inv".to_string()))
+        .to_string();
+    assert!(proj_to_affine(&with_utm, PixelOrigin::Center).is_err());
+    assert!(proj_to_affine("+proj=affine +s11=abc", PixelOrigin::Center).is_err());
+    assert!(proj_to_affine("", PixelOrigin::Center).is_err());
+}
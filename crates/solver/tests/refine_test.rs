@@ -0,0 +1,100 @@
+use approx::assert_relative_eq;
+use nalgebra::Vector2;
+use solver::{ransac_fit_similarity_seeded, refine_from_prior, Transform};
+use types::{Affine, Homography, Similarity, Tps, TransformKind};
+
+fn truth() -> Homography {
+    Homography {
+        params: [1.1, 0.05, 20.0, -0.03, 0.95, -15.0, 2e-4, -1e-4, 1.0],
+    }
+}
+
+fn grid_pairs<T: Transform>(t: &T) -> Vec<([f64; 2], [f64; 2])> {
+    let mut pairs = Vec::new();
+    for i in 0..4 {
+        for j in 0..3 {
+            let src = [i as f64 * 300.0, j as f64 * 250.0];
+            let q = t.apply(&Vector2::from(src));
+            pairs.push((src, [q.x, q.y]));
+        }
+    }
+    pairs
+}
+
+fn assert_homography_eq(a: &TransformKind, b: &Homography) {
+    let TransformKind::Homography(a) = a else {
+        panic!("expected homography, got {:?}", a);
+    };
+    for (x, y) in a.params.iter().zip(b.params) {
+        assert_relative_eq!(*x, y, epsilon = 1e-9, max_relative = 1e-7);
+    }
+}
+
+#[test]
+fn test_seeding_with_truth_converges_immediately() {
+    let pairs = grid_pairs(&truth());
+    let r = refine_from_prior(&TransformKind::Homography(truth()), &pairs).unwrap();
+    assert!(r.iterations <= 1, "took {} steps", r.iterations);
+    assert!(r.rmse < 1e-9);
+    assert!(r.warnings.is_empty());
+    assert_homography_eq(&r.transform, &truth());
+}
+
+#[test]
+fn test_homography_refines_from_rough_prior() {
+    let pairs = grid_pairs(&truth());
+    // Shifted by 8 px, 3% too large and without perspective
+    let rough = Homography {
+        params: [1.13, 0.05, 28.0, -0.03, 0.98, -15.0, 0.0, 0.0, 1.0],
+    };
+    let r = refine_from_prior(&TransformKind::Homography(rough), &pairs).unwrap();
+    assert!(r.iterations > 1 && r.iterations < 20, "{}", r.iterations);
+    assert!(r.prior_rmse > 5.0);
+    assert!(r.rmse < 1e-6, "{}", r.rmse);
+    assert_homography_eq(&r.transform, &truth());
+}
+
+#[test]
+fn test_linear_prior_is_validated_against_refit() {
+    let t = Affine {
+        params: [0.5, 0.1, -0.1, 0.5, 30.0, -12.0],
+    };
+    let pairs = grid_pairs(&t);
+    let good = refine_from_prior(&TransformKind::Affine(t.clone()), &pairs).unwrap();
+    assert_eq!(good.iterations, 0);
+    assert!(good.warnings.is_empty());
+    let off = Affine {
+        params: [0.5, 0.1, -0.1, 0.5, 60.0, -12.0],
+    };
+    let bad = refine_from_prior(&TransformKind::Affine(off), &pairs).unwrap();
+    assert_relative_eq!(bad.prior_rmse, 30.0, epsilon = 1e-9);
+    assert_eq!(bad.warnings.len(), 1);
+    let TransformKind::Affine(fit) = bad.transform else {
+        panic!("expected affine");
+    };
+    for (a, b) in fit.params.iter().zip(t.params) {
+        assert_relative_eq!(*a, b, epsilon = 1e-9);
+    }
+    let tps = TransformKind::Tps(Tps {
+        control_points: vec![],
+        lambda: 0.0,
+        weights: vec![],
+        affine: t.params,
+        scale: 1.0,
+    });
+    assert!(refine_from_prior(&tps, &pairs).is_err());
+}
+
+#[test]
+fn test_seeded_ransac_keeps_good_prior_without_sampling() {
+    let t = Similarity {
+        params: [1.5, 0.2, 5.0, -3.0],
+    };
+    let mut pairs = grid_pairs(&t);
+    pairs.push(([100.0, 100.0], [-100.0, -100.0]));
+    // No random samples at all: the seed's inliers decide the fit
+    let fit = ransac_fit_similarity_seeded(&pairs, 1.0, 0, &t).unwrap();
+    for k in 0..4 {
+        assert_relative_eq!(fit.params[k], t.params[k], epsilon = 1e-9);
+    }
+}
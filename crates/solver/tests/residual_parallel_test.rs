@@ -0,0 +1,56 @@
+use approx::assert_relative_eq;
+use nalgebra::Vector2;
+use solver::{pair_residuals, residual_rmse, Transform};
+use types::Affine;
+
+fn truth() -> Affine {
+    Affine {
+        params: [0.98, 0.05, -0.04, 1.01, 120.0, -35.0],
+    }
+}
+
+/// Dense synthetic set (feature-matching sized) with small deterministic noise
+fn dense_pairs(t: &Affine, n: usize) -> Vec<([f64; 2], [f64; 2])> {
+    (0..n)
+        .map(|i| {
+            let src = [(i % 317) as f64 * 6.5, (i / 317) as f64 * 4.25];
+            let p = t.apply(&Vector2::from(src));
+            let k = i as f64;
+            [
+                src,
+                [p.x + 0.7 * (k * 0.37).sin(), p.y + 0.4 * (k * 1.13).cos()],
+            ]
+        })
+        .map(|[s, d]| (s, d))
+        .collect()
+}
+
+#[test]
+fn test_residuals_match_serial_loop() {
+    let t = truth();
+    let pairs = dense_pairs(&t, 100_000);
+    let residuals = pair_residuals(&t, &pairs);
+    assert_eq!(residuals.len(), pairs.len());
+    let mut sum_sq = 0.0;
+    for ((src, dst), r) in pairs.iter().zip(&residuals) {
+        let expected = (t.apply(&Vector2::from(*src)) - Vector2::from(*dst)).norm();
+        assert_eq!(*r, expected);
+        sum_sq += expected * expected;
+    }
+    let serial = (sum_sq / pairs.len() as f64).sqrt();
+    let rmse = residual_rmse(&residuals);
+    assert_relative_eq!(rmse, serial, max_relative = 1e-12);
+    // Chunked reduction is order-stable: repeated runs agree to the bit
+    for _ in 0..5 {
+        assert_eq!(
+            residual_rmse(&pair_residuals(&t, &pairs)).to_bits(),
+            rmse.to_bits()
+        );
+    }
+}
+
+#[test]
+fn test_residual_rmse_empty_and_small() {
+    assert_eq!(residual_rmse(&[]), 0.0);
+    assert_relative_eq!(residual_rmse(&[3.0, 4.0]), (12.5f64).sqrt());
+}
@@ -0,0 +1,41 @@
+use solver::sanity_check;
+use types::{Affine, Similarity, TransformKind};
+
+const IMG: [f64; 2] = [3000.0, 2000.0];
+
+#[test]
+fn test_plausible_fits_have_no_warnings() {
+    let sim = TransformKind::Similarity(Similarity {
+        params: [0.5, 0.2, 120.0, -40.0],
+    });
+    assert!(sanity_check(&sim, IMG).is_empty());
+    let aff = TransformKind::Affine(Affine {
+        params: [1.1, 0.1, -0.05, 0.9, 2000.0, 1500.0],
+    });
+    assert!(sanity_check(&aff, IMG).is_empty());
+}
+
+#[test]
+fn test_pathological_scale_is_flagged() {
+    let sim = TransformKind::Similarity(Similarity {
+        params: [5e-5, 0.0, 10.0, 10.0],
+    });
+    let w = sanity_check(&sim, IMG);
+    assert_eq!(w.len(), 1);
+    assert!(w[0].contains("scale"), "{:?}", w);
+    // A collapsed affine axis is caught through its smaller singular value
+    let aff = TransformKind::Affine(Affine {
+        params: [1.0, 1.0, 1.0, 1.0001, 0.0, 0.0],
+    });
+    assert!(sanity_check(&aff, IMG)[0].contains("scale"));
+}
+
+#[test]
+fn test_huge_translation_is_flagged() {
+    let sim = TransformKind::Similarity(Similarity {
+        params: [1.0, 0.0, 5e8, -3e8],
+    });
+    let w = sanity_check(&sim, IMG);
+    assert_eq!(w.len(), 1);
+    assert!(w[0].contains("translation"), "{:?}", w);
+}
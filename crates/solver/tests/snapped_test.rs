@@ -0,0 +1,115 @@
+use approx::assert_relative_eq;
+use solver::{fit_similarity_from_pairs, fit_similarity_snapped};
+
+/// dst = 1.5 * src + (20, -8), axis aligned, with a few tenths of a pixel of
+/// click noise that a free fit reads as a small rotation
+fn noisy_axis_aligned() -> Vec<([f64; 2], [f64; 2])> {
+    let noise = [
+        [0.3, -0.2],
+        [-0.4, 0.1],
+        [0.2, 0.4],
+        [-0.1, -0.3],
+        [0.35, 0.25],
+        [-0.3, 0.2],
+    ];
+    let src = [
+        [0.0, 0.0],
+        [800.0, 10.0],
+        [20.0, 600.0],
+        [790.0, 610.0],
+        [400.0, 300.0],
+        [100.0, 450.0],
+    ];
+    src.iter()
+        .zip(noise.iter())
+        .map(|(s, e)| (*s, [1.5 * s[0] + 20.0 + e[0], 1.5 * s[1] - 8.0 + e[1]]))
+        .collect()
+}
+
+#[test]
+fn test_snaps_to_zero_and_refits_scale_translation() {
+    let pairs = noisy_axis_aligned();
+    let free = fit_similarity_from_pairs(&pairs).unwrap();
+    assert!(free.params[1] != 0.0);
+    let snapped = fit_similarity_snapped(&pairs, 90.0).unwrap();
+    assert_eq!(snapped.params[1], 0.0);
+
+    // With no rotation, the residual is x/y translation and scale only:
+    // each axis is a shared-slope regression dst = s*src + t
+    let n = pairs.len() as f64;
+    let mean = |side: usize, axis: usize| {
+        pairs
+            .iter()
+            .map(|p| if side == 0 { p.0[axis] } else { p.1[axis] })
+            .sum::<f64>()
+            / n
+    };
+    let (msx, msy) = (mean(0, 0), mean(0, 1));
+    let (mdx, mdy) = (mean(1, 0), mean(1, 1));
+    let num: f64 = pairs
+        .iter()
+        .map(|p| (p.0[0] - msx) * (p.1[0] - mdx) + (p.0[1] - msy) * (p.1[1] - mdy))
+        .sum();
+    let den: f64 = pairs
+        .iter()
+        .map(|p| (p.0[0] - msx).powi(2) + (p.0[1] - msy).powi(2))
+        .sum();
+    let s = num / den;
+    assert_relative_eq!(snapped.params[0], s, epsilon = 1e-12);
+    assert_relative_eq!(snapped.params[2], mdx - s * msx, epsilon = 1e-9);
+    assert_relative_eq!(snapped.params[3], mdy - s * msy, epsilon = 1e-9);
+    assert_relative_eq!(snapped.params[0], 1.5, epsilon = 1e-3);
+}
+
+#[test]
+fn test_snaps_to_nearest_quarter_turn() {
+    // Rotate the same data by ~91 degrees
+    let a = 91f64.to_radians();
+    let pairs: Vec<_> = noisy_axis_aligned()
+        .into_iter()
+        .map(|(s, d)| {
+            (
+                s,
+                [
+                    a.cos() * d[0] - a.sin() * d[1],
+                    a.sin() * d[0] + a.cos() * d[1],
+                ],
+            )
+        })
+        .collect();
+    let snapped = fit_similarity_snapped(&pairs, 90.0).unwrap();
+    assert_relative_eq!(
+        snapped.params[1],
+        std::f64::consts::FRAC_PI_2,
+        epsilon = 1e-12
+    );
+    assert!(fit_similarity_snapped(&pairs, 0.0).is_err());
+}
+
+#[test]
+fn test_snap_angle_and_pairs_are_validated() {
+    let pairs = noisy_axis_aligned();
+    for bad in [-90.0, f64::NAN, f64::INFINITY, 180.5, 360.0] {
+        assert!(fit_similarity_snapped(&pairs, bad).is_err(), "{}", bad);
+    }
+    assert!(fit_similarity_snapped(&pairs, 180.0).is_ok());
+    let mut broken = pairs.clone();
+    broken[0].1[0] = f64::NAN;
+    assert!(fit_similarity_snapped(&broken, 90.0).is_err());
+}
+
+#[test]
+fn test_snapped_fit_in_world_coordinates() {
+    // The same data placed at UTM-sized destinations
+    let (x0, y0) = (512_345.0, 4_123_456.0);
+    let pairs: Vec<_> = noisy_axis_aligned()
+        .into_iter()
+        .map(|(s, d)| (s, [d[0] + x0, d[1] + y0]))
+        .collect();
+    let local = fit_similarity_snapped(&noisy_axis_aligned(), 90.0).unwrap();
+    let world = fit_similarity_snapped(&pairs, 90.0).unwrap();
+    assert_eq!(world.params[1], local.params[1]);
+    assert_relative_eq!(world.params[0], local.params[0], epsilon = 1e-12);
+    assert_relative_eq!(world.params[2], local.params[2] + x0, epsilon = 1e-6);
+    assert_relative_eq!(world.params[3], local.params[3] + y0, epsilon = 1e-6);
+}
@@ -0,0 +1,62 @@
+use solver::{solve_constraints, FitMethod, SolveResult};
+use types::{ConstraintKind, TransformKind};
+
+fn pp(id: u64, src: [f64; 2], dst: [f64; 2]) -> ConstraintKind {
+    ConstraintKind::PointPair {
+        id,
+        src,
+        dst,
+        dst_real: None,
+        dst_local: None,
+        src_z: None,
+        dst_z: None,
+        weight: 1.0,
+    }
+}
+
+fn constraints() -> Vec<ConstraintKind> {
+    vec![
+        pp(1, [0.0, 0.0], [10.0, 5.0]),
+        pp(2, [100.0, 0.0], [210.3, 5.0]),
+        pp(3, [0.0, 100.0], [10.0, 204.8]),
+        pp(4, [100.0, 100.0], [209.9, 205.1]),
+    ]
+}
+
+#[test]
+fn test_solve_result_round_trips_through_json() {
+    let result = solve_constraints(&constraints(), FitMethod::Affine).unwrap();
+    assert_eq!(result.n_pairs, 4);
+    assert!(matches!(result.transform, TransformKind::Affine(_)));
+    assert_eq!(result.metrics.residuals_by_id.len(), 4);
+
+    let json = serde_json::to_string(&result).unwrap();
+    assert!(json.contains(r#""method":"affine""#), "{}", json);
+    let back: SolveResult = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.method, FitMethod::Affine);
+    assert_eq!(back.n_pairs, result.n_pairs);
+    // serde_json may round the last bit of a float when parsing
+    let close = |a: f64, b: f64| (a - b).abs() <= 1e-12 * a.abs().max(1.0);
+    assert!(close(back.metrics.rmse, result.metrics.rmse));
+    let (TransformKind::Affine(a), TransformKind::Affine(b)) = (&back.transform, &result.transform)
+    else {
+        panic!("expected affines");
+    };
+    assert!(a
+        .params
+        .iter()
+        .zip(b.params.iter())
+        .all(|(x, y)| close(*x, *y)));
+    assert_eq!(back.metrics.residuals_by_id.len(), 4);
+}
+
+#[test]
+fn test_solve_result_similarity_metrics() {
+    let result = solve_constraints(&constraints(), FitMethod::Similarity).unwrap();
+    let TransformKind::Similarity(sim) = &result.transform else {
+        panic!("expected a similarity");
+    };
+    assert!((sim.params[0] - 2.0).abs() < 0.01);
+    assert!(result.metrics.rmse > 0.0);
+    assert!(result.metrics.p90_error >= result.metrics.rmse * 0.5);
+}
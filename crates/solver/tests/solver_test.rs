@@ -3,8 +3,12 @@ mod tests {
     use approx::assert_relative_eq;
     use nalgebra::Vector2;
     use solver::{
-        affine_to_proj, compose_similarity, fit_affine_from_pairs, fit_similarity_from_pairs,
-        invert_similarity, ransac_fit_similarity, Transform,
+        affine_about_point, affine_to_proj, axis_rmse, blend_similarity, bootstrap_similarity,
+        compose_affine, compose_similarity, fit_affine_from_pairs, fit_rigid_from_pairs,
+        fit_similarity_from_pairs, invert_similarity, nice_map_scale, quick_align,
+        ransac_fit_rigid, ransac_fit_similarity, ransac_fit_similarity_budgeted, required_scan_dpi,
+        scale_deviation_ppm, scale_for_paper, similarity_bearing_deg, similarity_to_affine,
+        similarity_to_proj, PixelOrigin, Transform,
     };
     use types::{Affine, Similarity};
 
@@ -70,6 +74,105 @@ mod tests {
         assert_relative_eq!(t.params[3], true_t.params[3], epsilon = 1e-2);
     }
 
+    #[test]
+    fn test_ransac_fit_rigid_recovers_rotation_and_translation() {
+        let true_t = Similarity {
+            params: [1.0, -0.35, 120.0, 40.0],
+        };
+        let mut pairs = Vec::new();
+        for i in 0..5 {
+            for j in 0..4 {
+                let p = Vector2::new(i as f64 * 50.0, j as f64 * 40.0);
+                let q = true_t.apply(&p);
+                // Small alternating noise so the refit is a real least squares
+                let e = if (i + j) % 2 == 0 { 0.05 } else { -0.05 };
+                pairs.push(([p.x, p.y], [q.x + e, q.y - e]));
+            }
+        }
+        // Outliers, including a block that agrees with a 2x scaled model
+        pairs.push(([10.0, 10.0], [-500.0, 900.0]));
+        pairs.push(([70.0, 15.0], [3.0, -250.0]));
+        for k in 0..3 {
+            let p = Vector2::new(k as f64 * 30.0 + 5.0, 7.0);
+            let q = true_t.apply(&(p * 2.0));
+            pairs.push(([p.x, p.y], [q.x, q.y]));
+        }
+        let t = ransac_fit_rigid(&pairs, 1.0, 1000).unwrap();
+        assert_eq!(t.params[0], 1.0);
+        assert_relative_eq!(t.params[1], true_t.params[1], epsilon = 1e-3);
+        assert_relative_eq!(t.params[2], true_t.params[2], epsilon = 0.05);
+        assert_relative_eq!(t.params[3], true_t.params[3], epsilon = 0.05);
+
+        // The plain rigid fit is pulled off by the outliers
+        let naive = fit_rigid_from_pairs(&pairs).unwrap();
+        assert!((naive.params[2] - true_t.params[2]).abs() > 1.0);
+    }
+
+    #[test]
+    fn test_ransac_time_budget_returns_model_quickly() {
+        let true_t = Similarity {
+            params: [1.2, 0.3, -4.0, 9.0],
+        };
+        // ~2M exhaustive samples over 2000 pairs would take minutes
+        let pairs: Vec<_> = (0..2000)
+            .map(|i| {
+                let p = Vector2::new((i % 50) as f64 * 10.0, (i / 50) as f64 * 10.0);
+                let q = true_t.apply(&p);
+                ([p.x, p.y], [q.x, q.y])
+            })
+            .collect();
+        let start = std::time::Instant::now();
+        let t = ransac_fit_similarity_budgeted(&pairs, 1.0, usize::MAX, 20).unwrap();
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        for k in 0..4 {
+            assert_relative_eq!(t.params[k], true_t.params[k], epsilon = 1e-6);
+        }
+        // A zero budget still tries one sample
+        assert!(ransac_fit_similarity_budgeted(&pairs, 1.0, usize::MAX, 0).is_ok());
+        // An unrepresentable budget does not overflow
+        assert!(ransac_fit_similarity_budgeted(&pairs[..10], 1.0, usize::MAX, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_ransac_two_pairs_is_exact_fit_without_iterating() {
+        let true_t = Similarity {
+            params: [0.8, -1.1, 12.0, 7.5],
+        };
+        let pairs: Vec<_> = [[3.0, 4.0], [-20.0, 9.0]]
+            .iter()
+            .map(|p| {
+                let q = true_t.apply(&Vector2::from(*p));
+                (*p, [q.x, q.y])
+            })
+            .collect();
+        // usize::MAX iterations would never finish if the loop ran
+        let t = ransac_fit_similarity(&pairs, 1.0, usize::MAX).unwrap();
+        for k in 0..4 {
+            assert_relative_eq!(t.params[k], true_t.params[k], epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_ransac_small_sets_try_each_sample_once() {
+        // 3 good pairs and 1 outlier: 6 distinct samples, all tried once
+        // even with an enormous iteration budget
+        let true_t = Similarity {
+            params: [2.0, 0.5, -4.0, 1.0],
+        };
+        let mut pairs: Vec<_> = [[0.0, 0.0], [10.0, 0.0], [0.0, 10.0]]
+            .iter()
+            .map(|p| {
+                let q = true_t.apply(&Vector2::from(*p));
+                (*p, [q.x, q.y])
+            })
+            .collect();
+        pairs.push(([10.0, 10.0], [500.0, -500.0]));
+        let t = ransac_fit_similarity(&pairs, 0.5, usize::MAX).unwrap();
+        for k in 0..4 {
+            assert_relative_eq!(t.params[k], true_t.params[k], epsilon = 1e-9);
+        }
+    }
+
     #[test]
     fn test_similarity_invert_compose() {
         let t = Similarity {
@@ -89,9 +192,210 @@ mod tests {
         let aff = Affine {
             params: [1.0, 0.0, 0.0, 1.0, 5.0, -2.0],
         };
-        let proj = affine_to_proj(&aff);
+        let proj = affine_to_proj(&aff, PixelOrigin::Center);
         assert!(proj.contains("+proj=pipeline"));
         assert!(proj.contains("+xoff=5"));
         assert!(proj.contains("+yoff=-2"));
     }
+
+    #[test]
+    fn test_compose_affine_matches_sequential_apply() {
+        let sim = Similarity {
+            params: [1.2, 0.3, 4.0, -2.0],
+        };
+        let a = similarity_to_affine(&sim);
+        let b = Affine {
+            params: [2.0, 0.5, -0.25, 1.5, 100.0, 200.0],
+        };
+        let composed = compose_affine(&a, &b);
+        let p = Vector2::new(3.0, -7.0);
+        let expected = b.apply(&sim.apply(&p));
+        let q = composed.apply(&p);
+        assert_relative_eq!(q.x, expected.x, epsilon = 1e-9);
+        assert_relative_eq!(q.y, expected.y, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_similarity_bearing_deg_clockwise_from_north() {
+        let t = Similarity {
+            params: [1.0, 30f64.to_radians(), 0.0, 0.0],
+        };
+        assert_relative_eq!(similarity_bearing_deg(&t), 30.0, epsilon = 1e-9);
+        // Map "up" (0,-1) should point 30° clockwise from up in y-down pixels
+        let up = t.apply(&Vector2::new(0.0, -1.0));
+        let bearing = up.x.atan2(-up.y).to_degrees();
+        assert_relative_eq!(bearing, 30.0, epsilon = 1e-9);
+
+        let ccw = Similarity {
+            params: [1.0, -10f64.to_radians(), 0.0, 0.0],
+        };
+        assert_relative_eq!(similarity_bearing_deg(&ccw), 350.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_scale_deviation_ppm() {
+        let scan = Similarity {
+            params: [1.003, 0.2, 5.0, -3.0],
+        };
+        assert_relative_eq!(scale_deviation_ppm(&scan), 3000.0, epsilon = 1e-6);
+        let shrunk = Similarity {
+            params: [0.9995, 0.0, 0.0, 0.0],
+        };
+        assert_relative_eq!(scale_deviation_ppm(&shrunk), -500.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_required_scan_dpi_for_quad_sheet() {
+        // 1:24000 at 1 m: 1/24 mm of paper per pixel
+        assert_relative_eq!(required_scan_dpi(24000.0, 1.0), 609.6, epsilon = 1e-9);
+        // Halving the ground pixel doubles the DPI
+        assert_relative_eq!(required_scan_dpi(24000.0, 0.5), 1219.2, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_scale_for_paper_rounds_to_standard_scale() {
+        // 5 km across 0.5 m of paper
+        let exact = scale_for_paper(5000.0, 0.5);
+        assert_relative_eq!(exact, 10000.0, epsilon = 1e-9);
+        assert_eq!(nice_map_scale(exact), 10000.0);
+        // Slightly more ground needs the next scale up to still fit
+        assert_eq!(nice_map_scale(scale_for_paper(5200.0, 0.5)), 20000.0);
+        assert_eq!(nice_map_scale(21000.0), 25000.0);
+        assert_eq!(nice_map_scale(25000.0), 25000.0);
+        assert_eq!(nice_map_scale(60000.0), 100000.0);
+    }
+
+    #[test]
+    fn test_axis_rmse_reports_larger_y_error() {
+        let t = Affine {
+            params: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+        };
+        // Signed errors: x off by ±0.5, y off by ±2.0
+        let pairs = vec![
+            ([0.0, 0.0], [0.5, 2.0]),
+            ([10.0, 0.0], [9.5, -2.0]),
+            ([0.0, 10.0], [0.5, 8.0]),
+            ([10.0, 10.0], [9.5, 12.0]),
+        ];
+        let (rx, ry) = axis_rmse(&t, &pairs);
+        assert_relative_eq!(rx, 0.5, epsilon = 1e-12);
+        assert_relative_eq!(ry, 2.0, epsilon = 1e-12);
+        assert_eq!(axis_rmse(&t, &[]), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_bootstrap_spread_grows_with_noise() {
+        let truth = Similarity {
+            params: [1.5, 0.3, 20.0, -10.0],
+        };
+        let make = |noise: f64| -> Vec<([f64; 2], [f64; 2])> {
+            (0..20)
+                .map(|i| {
+                    let src = [(i % 5) as f64 * 25.0, (i / 5) as f64 * 30.0];
+                    let p = truth.apply(&Vector2::from(src));
+                    // Deterministic pseudo-noise
+                    let e = [
+                        (i as f64 * 1.7).sin() * noise,
+                        (i as f64 * 2.3).cos() * noise,
+                    ];
+                    (src, [p.x + e[0], p.y + e[1]])
+                })
+                .collect()
+        };
+        let tight = bootstrap_similarity(&make(0.01), 200, 7).unwrap();
+        let noisy = bootstrap_similarity(&make(3.0), 200, 7).unwrap();
+        assert_eq!(tight.n_fits, 200);
+        for k in 0..4 {
+            assert!(noisy.std_dev[k] > 10.0 * tight.std_dev[k], "param {}", k);
+            assert!(tight.lower[k] <= tight.mean[k] && tight.mean[k] <= tight.upper[k]);
+        }
+        assert_relative_eq!(tight.mean[0], 1.5, epsilon = 1e-3);
+        assert_relative_eq!(tight.mean[1], 0.3, epsilon = 1e-3);
+
+        // Same seed, same answer
+        let again = bootstrap_similarity(&make(3.0), 200, 7).unwrap();
+        assert_eq!(again.std_dev, noisy.std_dev);
+    }
+
+    #[test]
+    fn test_blend_similarity_endpoints_and_wrap() {
+        use std::f64::consts::PI;
+        let a = Similarity {
+            params: [1.0, 170f64.to_radians(), 0.0, 10.0],
+        };
+        let b = Similarity {
+            params: [4.0, -170f64.to_radians(), 20.0, -10.0],
+        };
+        for (t, want) in [(0.0, &a), (1.0, &b)] {
+            let got = blend_similarity(&a, &b, t);
+            for k in 0..4 {
+                assert_relative_eq!(got.params[k], want.params[k], epsilon = 1e-9);
+            }
+        }
+        // Shortest arc from 170° to -170° passes through 180°, not 0°
+        let mid = blend_similarity(&a, &b, 0.5);
+        assert_relative_eq!(mid.params[1].abs(), PI, epsilon = 1e-9);
+        assert_relative_eq!(mid.params[0], 2.0, epsilon = 1e-12);
+        assert_relative_eq!(mid.params[2], 10.0, epsilon = 1e-12);
+        assert_relative_eq!(mid.params[3], 0.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_quick_align_two_pairs_is_exact() {
+        // Scale 2, rotate 90° CCW, translate (10, 5)
+        let pairs = vec![([0.0, 0.0], [10.0, 5.0]), ([1.0, 0.0], [10.0, 7.0])];
+        let (t, note) = quick_align(&pairs).unwrap();
+        assert_relative_eq!(t.params[0], 2.0, epsilon = 1e-9);
+        assert_relative_eq!(t.params[1], std::f64::consts::FRAC_PI_2, epsilon = 1e-9);
+        assert_relative_eq!(t.params[2], 10.0, epsilon = 1e-9);
+        assert_relative_eq!(t.params[3], 5.0, epsilon = 1e-9);
+        assert!(note.unwrap().contains("Exact"));
+
+        assert!(quick_align(&pairs[..1]).is_err());
+        let mut three = pairs.clone();
+        three.push(([0.0, 1.0], [8.0, 5.0]));
+        assert!(quick_align(&three).unwrap().1.is_none());
+    }
+
+    #[test]
+    fn test_corner_origin_shifts_input_by_half_pixel() {
+        let sim = Similarity {
+            params: [2.0, 0.4, 7.0, -3.0],
+        };
+        let center = similarity_to_proj(&sim, PixelOrigin::Center);
+        let corner = similarity_to_proj(&sim, PixelOrigin::Corner);
+        assert_eq!(
+            corner,
+            center.replacen(
+                "+proj=pipeline",
+                "+proj=pipeline +step +proj=affine +xoff=-0.5 +yoff=-0.5",
+                1
+            )
+        );
+        let aff = Affine {
+            params: [1.0, 0.2, -0.1, 1.5, 5.0, -2.0],
+        };
+        let corner = affine_to_proj(&aff, PixelOrigin::Corner);
+        assert!(corner.starts_with("+proj=pipeline +step +proj=affine +xoff=-0.5 +yoff=-0.5 +step"));
+        assert!(!affine_to_proj(&aff, PixelOrigin::Center).contains("-0.5"));
+    }
+
+    #[test]
+    fn test_affine_about_point_maps_identically() {
+        let aff = Affine {
+            params: [1.2, -0.3, 0.25, 0.9, 431000.0, 5270000.0],
+        };
+        let center = [960.0, 540.0];
+        let about = affine_about_point(&aff, center);
+        assert_eq!(about.params[..4], aff.params[..4]);
+        for p in [[0.0, 0.0], [960.0, 540.0], [1919.0, 12.5], [-40.0, 2000.0]] {
+            let direct = aff.apply(&Vector2::from(p));
+            let shifted = about.apply(&Vector2::new(p[0] - center[0], p[1] - center[1]));
+            assert_relative_eq!(direct, shifted, epsilon = 1e-6);
+        }
+        // Translation is now the world position of the center
+        let at_center = aff.apply(&Vector2::from(center));
+        assert_relative_eq!(about.params[4], at_center.x);
+        assert_relative_eq!(about.params[5], at_center.y);
+    }
 }
@@ -0,0 +1,60 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use solver::{detect_swapped, swap_pairs};
+
+/// 4000x3600 map scan and 1000x900 reference.
+const MAP_WH: [f64; 2] = [4000.0, 3600.0];
+const REF_WH: [f64; 2] = [1000.0, 900.0];
+
+/// Map pixels to reference pixels at 0.25 ref px per map px (a detailed scan
+/// against a coarser reference), with ~0.5 px of click noise on both sides.
+fn digitized_pairs() -> Vec<([f64; 2], [f64; 2])> {
+    let mut rng = StdRng::seed_from_u64(7);
+    let (c, s) = (0.1f64.cos() * 0.25, 0.1f64.sin() * 0.25);
+    let mut noise = || rng.gen_range(-0.5..0.5);
+    let mut v = Vec::new();
+    for i in 0..5 {
+        for j in 0..4 {
+            let (u, w) = (i as f64 * 800.0 + 100.0, j as f64 * 900.0 + 50.0);
+            let src = [u + noise(), w + noise()];
+            let dst = [
+                c * u - s * w + 100.0 + noise(),
+                s * u + c * w + 60.0 + noise(),
+            ];
+            v.push((src, dst));
+        }
+    }
+    v
+}
+
+#[test]
+fn test_detects_reversed_pairs() {
+    let pairs = digitized_pairs();
+    assert!(!detect_swapped(&pairs, MAP_WH, REF_WH));
+    let reversed = swap_pairs(&pairs);
+    assert!(detect_swapped(&reversed, MAP_WH, REF_WH));
+    assert_eq!(swap_pairs(&reversed), pairs);
+}
+
+#[test]
+fn test_coarse_map_at_scale_4_is_not_flagged() {
+    // A 2 m/px scan against a 0.5 m/px ortho: 4 reference px per map px,
+    // correctly oriented, with the same click noise
+    let pairs = swap_pairs(&digitized_pairs());
+    assert!(!detect_swapped(&pairs, REF_WH, MAP_WH));
+}
+
+#[test]
+fn test_exact_or_degenerate_pairs_are_not_flagged() {
+    let exact: Vec<_> = (0..4)
+        .map(|i| {
+            let p = [i as f64 * 10.0, (i * i) as f64];
+            (p, [p[0] * 4.0, p[1] * 4.0])
+        })
+        .collect();
+    assert!(!detect_swapped(&exact, [100.0, 100.0], [400.0, 400.0]));
+    assert!(!detect_swapped(&[], MAP_WH, REF_WH));
+    // Same-size images give no evidence either way
+    let reversed = swap_pairs(&digitized_pairs());
+    assert!(!detect_swapped(&reversed, MAP_WH, MAP_WH));
+}
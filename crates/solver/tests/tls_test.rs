@@ -0,0 +1,95 @@
+use nalgebra::Vector2;
+use rand::{Rng, SeedableRng};
+use solver::{fit_affine_from_pairs, fit_affine_tls, SolverError, Transform};
+use types::Affine;
+
+/// Standard normal sample (Box-Muller)
+fn gauss(rng: &mut impl Rng) -> f64 {
+    let u1: f64 = rng.gen_range(1e-12..1.0);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+#[test]
+fn test_tls_less_biased_than_ols_with_noise_on_both_sides() {
+    let truth = Affine {
+        params: [1.2, 0.3, -0.2, 0.9, 40.0, -15.0],
+    };
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+    let noise = 4.0;
+    let pairs: Vec<([f64; 2], [f64; 2])> = (0..4000)
+        .map(|_| {
+            // True positions spread ~N(0, 10²): noise is large relative to spread
+            let s = Vector2::new(10.0 * gauss(&mut rng), 10.0 * gauss(&mut rng));
+            let d = truth.apply(&s);
+            (
+                [s.x + noise * gauss(&mut rng), s.y + noise * gauss(&mut rng)],
+                [d.x + noise * gauss(&mut rng), d.y + noise * gauss(&mut rng)],
+            )
+        })
+        .collect();
+    let ols = fit_affine_from_pairs(&pairs).unwrap();
+    let tls = fit_affine_tls(&pairs).unwrap();
+    let err = |a: &Affine| -> f64 {
+        (0..4)
+            .map(|k| (a.params[k] - truth.params[k]).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    };
+    // OLS shrinks the linear part by ~100/(100+16)
+    assert!(ols.params[0] < 1.1, "OLS a = {}", ols.params[0]);
+    assert!(
+        err(&tls) < 0.5 * err(&ols),
+        "tls {} vs ols {}",
+        err(&tls),
+        err(&ols)
+    );
+    assert!(
+        (tls.params[0] - 1.2).abs() < 0.05,
+        "TLS a = {}",
+        tls.params[0]
+    );
+}
+
+#[test]
+fn test_tls_exact_on_noise_free_data() {
+    let truth = Affine {
+        params: [0.8, -0.1, 0.25, 1.1, 3.0, 7.0],
+    };
+    let pairs: Vec<([f64; 2], [f64; 2])> = [[0.0, 0.0], [10.0, 0.0], [0.0, 10.0], [7.0, 3.0]]
+        .iter()
+        .map(|s| {
+            let d = truth.apply(&Vector2::from(*s));
+            (*s, [d.x, d.y])
+        })
+        .collect();
+    let tls = fit_affine_tls(&pairs).unwrap();
+    for k in 0..6 {
+        assert!((tls.params[k] - truth.params[k]).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_tls_rejects_non_finite_pairs() {
+    let mut pairs = vec![
+        ([0.0, 0.0], [1.0, 2.0]),
+        ([10.0, 0.0], [21.0, 2.0]),
+        ([0.0, 10.0], [1.0, 22.0]),
+        ([3.0, 3.0], [7.0, 8.0]),
+    ];
+    pairs[3].1[0] = f64::NAN;
+    let err = fit_affine_tls(&pairs).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<SolverError>(),
+        Some(SolverError::Numerical(_))
+    ));
+    // Finite inputs whose centroid sums overflow
+    let big = 1.5e308;
+    let overflow = vec![
+        ([0.0, 0.0], [big, big]),
+        ([10.0, 0.0], [big, -big]),
+        ([0.0, 10.0], [-big, big]),
+        ([10.0, 10.0], [big, big]),
+    ];
+    assert!(fit_affine_tls(&overflow).is_err());
+}
@@ -0,0 +1,101 @@
+use approx::assert_relative_eq;
+use nalgebra::Vector2;
+use solver::Transform;
+use types::{Affine, Ffd, Homography, Tps, TransformKind};
+
+const AFFINE: [f64; 6] = [1.2, 0.1, -0.2, 0.9, 15.0, -4.0];
+
+#[test]
+fn test_homography_with_unit_row_is_affine() {
+    let [a, b, c, d, tx, ty] = AFFINE;
+    // Scaling H leaves the projective map unchanged
+    let h = Homography {
+        params: [
+            2.0 * a,
+            2.0 * b,
+            2.0 * tx,
+            2.0 * c,
+            2.0 * d,
+            2.0 * ty,
+            0.0,
+            0.0,
+            2.0,
+        ],
+    };
+    let aff = Affine { params: AFFINE };
+    for p in [[0.0, 0.0], [120.0, -30.0], [7.5, 300.0]] {
+        let p = Vector2::from(p);
+        assert_relative_eq!(h.apply(&p), aff.apply(&p), epsilon = 1e-9);
+    }
+    // Perspective row: (0,0) -> (h2, h5) / h8, (1,0) divides by h6 + h8
+    let h = Homography {
+        params: [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0],
+    };
+    assert_relative_eq!(h.apply(&Vector2::new(1.0, 4.0)), Vector2::new(0.5, 2.0));
+}
+
+#[test]
+fn test_tps_interpolates_radial_term() {
+    let mut tps = Tps {
+        control_points: vec![[0.0, 0.0], [10.0, 0.0]],
+        lambda: 0.0,
+        weights: vec![],
+        affine: AFFINE,
+        scale: 1.0,
+    };
+    let p = Vector2::new(3.0, 4.0);
+    // Unfitted: just the affine part
+    assert_relative_eq!(tps.apply(&p), Affine { params: AFFINE }.apply(&p));
+    // ... which is the identity for a spline saved before it was fitted
+    let saved: Tps =
+        serde_json::from_str(r#"{"control_points":[[1.0,2.0]],"lambda":0.5}"#).unwrap();
+    assert_eq!(saved.apply(&p), p);
+    tps.weights = vec![[1.0, -2.0], [0.0, 0.0]];
+    // r = 5 from the first control point: U = 25 ln 5
+    let u = 25.0 * 5f64.ln();
+    let expected = Affine { params: AFFINE }.apply(&p) + Vector2::new(u, -2.0 * u);
+    assert_relative_eq!(tps.apply(&p), expected, epsilon = 1e-9);
+    // U(0) = 0 at a control point
+    let at_cp = Vector2::new(0.0, 0.0);
+    assert_relative_eq!(tps.apply(&at_cp), Vector2::new(15.0, -4.0), epsilon = 1e-9);
+}
+
+fn lattice(nx: usize, ny: usize, spacing: f64) -> Vec<[f64; 2]> {
+    (0..ny)
+        .flat_map(|j| (0..nx).map(move |i| [i as f64 * spacing, j as f64 * spacing]))
+        .collect()
+}
+
+#[test]
+fn test_ffd_constant_displacement_translates() {
+    let mut ffd = Ffd {
+        control_points: lattice(5, 4, 50.0),
+        grid_size: [5, 4],
+        displacements: vec![],
+    };
+    let p = Vector2::new(73.0, 61.0);
+    assert_eq!(ffd.apply(&p), p, "unfitted FFD is the identity");
+    // B-spline weights sum to 1, so a uniform field is a pure shift,
+    // including outside the lattice where nodes are clamped
+    ffd.displacements = vec![[2.5, -1.0]; 20];
+    for q in [p, Vector2::new(-40.0, 500.0)] {
+        assert_relative_eq!(ffd.apply(&q), q + Vector2::new(2.5, -1.0), epsilon = 1e-12);
+    }
+    // A single bumped node moves nearby points only
+    ffd.displacements = vec![[0.0, 0.0]; 20];
+    ffd.displacements[2 * 5 + 2] = [6.0, 0.0];
+    let near = ffd.apply(&Vector2::new(100.0, 100.0));
+    assert_relative_eq!(
+        near.x - 100.0,
+        6.0 * (4.0 / 6.0) * (4.0 / 6.0),
+        epsilon = 1e-12
+    );
+    assert_eq!(ffd.apply(&Vector2::new(0.0, 0.0)), Vector2::new(0.0, 0.0));
+}
+
+#[test]
+fn test_transform_kind_dispatches() {
+    let p = Vector2::new(12.0, -7.0);
+    let kind = TransformKind::Affine(Affine { params: AFFINE });
+    assert_eq!(kind.apply(&p), Affine { params: AFFINE }.apply(&p));
+}
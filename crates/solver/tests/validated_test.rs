@@ -0,0 +1,45 @@
+use solver::{fit_affine_validated, fit_similarity_validated};
+use types::ConstraintKind;
+
+fn pp(id: u64, src: [f64; 2], dst: [f64; 2]) -> ConstraintKind {
+    ConstraintKind::PointPair {
+        id,
+        src,
+        dst,
+        dst_real: None,
+        dst_local: None,
+        src_z: None,
+        dst_z: None,
+        weight: 1.0,
+    }
+}
+
+/// dst = 2*src + (5, -3), with point 4 clicked far off
+fn with_gross_error() -> Vec<ConstraintKind> {
+    let mut v: Vec<ConstraintKind> = [[0.0, 0.0], [100.0, 0.0], [0.0, 100.0], [100.0, 100.0]]
+        .iter()
+        .enumerate()
+        .map(|(i, s)| pp(i as u64 + 1, *s, [2.0 * s[0] + 5.0, 2.0 * s[1] - 3.0]))
+        .collect();
+    v.push(pp(5, [50.0, 50.0], [105.0, 97.0]));
+    v.push(pp(6, [30.0, 70.0], [500.0, -200.0]));
+    v
+}
+
+#[test]
+fn test_validated_fit_names_gross_error() {
+    let list = with_gross_error();
+    let err = fit_similarity_validated(&list, Some(10.0)).unwrap_err();
+    assert!(err.to_string().contains("point 6"), "{}", err);
+    let err = fit_affine_validated(&list, Some(10.0)).unwrap_err();
+    assert!(err.to_string().contains("point 6"), "{}", err);
+}
+
+#[test]
+fn test_validated_fit_passes_clean_data_and_when_disabled() {
+    let mut list = with_gross_error();
+    assert!(fit_similarity_validated(&list, None).is_ok());
+    list.pop();
+    let t = fit_similarity_validated(&list, Some(1e-6)).unwrap();
+    assert!((t.params[0] - 2.0).abs() < 1e-9);
+}
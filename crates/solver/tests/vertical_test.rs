@@ -0,0 +1,64 @@
+use approx::assert_relative_eq;
+use solver::vertical_residuals;
+use types::ConstraintKind;
+
+fn pair(id: u64, src: [f64; 2], src_z: Option<f64>, dst_z: Option<f64>) -> ConstraintKind {
+    ConstraintKind::PointPair {
+        id,
+        src,
+        dst: [src[0] + 1.0, src[1] + 1.0],
+        dst_real: None,
+        dst_local: None,
+        src_z,
+        dst_z,
+        weight: 1.0,
+    }
+}
+
+#[test]
+fn test_vertical_residuals_on_tilted_plane() {
+    // dz = 0.01*u - 0.02*v + 5
+    let plane = |p: [f64; 2]| 0.01 * p[0] - 0.02 * p[1] + 5.0;
+    let srcs = [
+        [0.0, 0.0],
+        [100.0, 0.0],
+        [0.0, 100.0],
+        [100.0, 100.0],
+        [50.0, 20.0],
+    ];
+    let mut cs: Vec<ConstraintKind> = srcs
+        .iter()
+        .enumerate()
+        .map(|(i, s)| pair(i as u64, *s, Some(10.0), Some(10.0 + plane(*s))))
+        .collect();
+    let fit = vertical_residuals(&cs).unwrap().unwrap();
+    assert_relative_eq!(fit.plane[0], 0.01, epsilon = 1e-9);
+    assert_relative_eq!(fit.plane[1], -0.02, epsilon = 1e-9);
+    assert_relative_eq!(fit.plane[2], 5.0, epsilon = 1e-9);
+    assert!(fit.rmse < 1e-9);
+
+    // Lift one point off the plane; it should carry the largest residual
+    if let ConstraintKind::PointPair { dst_z, .. } = &mut cs[4] {
+        *dst_z = dst_z.map(|z| z + 2.0);
+    }
+    let fit = vertical_residuals(&cs).unwrap().unwrap();
+    let worst = fit
+        .residuals_by_id
+        .iter()
+        .max_by(|a, b| a.1.abs().partial_cmp(&b.1.abs()).unwrap())
+        .unwrap();
+    assert_eq!(worst.0, 4);
+    assert!(worst.1 > 0.0);
+}
+
+#[test]
+fn test_vertical_residuals_absent_without_z() {
+    let cs = vec![
+        pair(1, [0.0, 0.0], None, None),
+        pair(2, [10.0, 0.0], None, None),
+        pair(3, [0.0, 10.0], None, None),
+    ];
+    assert!(vertical_residuals(&cs).unwrap().is_none());
+    let few = vec![pair(1, [0.0, 0.0], None, Some(1.0))];
+    assert!(vertical_residuals(&few).is_err());
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum ConstraintKind {
     Point {
         id: u64,
@@ -15,6 +15,10 @@ pub enum ConstraintKind {
         dst_real: Option<[f64; 2]>,
         /// Local meter-plane coordinates relative to the reference origin
         dst_local: Option<[f64; 2]>,
+        /// Optional elevation of the map point (vertical QC only; fits stay 2D)
+        src_z: Option<f64>,
+        /// Optional elevation of the reference point (vertical QC only; fits stay 2D)
+        dst_z: Option<f64>,
         weight: f64,
     },
     Polyline {
@@ -65,6 +69,12 @@ pub struct TransformStack {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct QualityMetrics {
     pub rmse: f64,
+    /// RMSE of the x and y residual components; a large gap between them
+    /// suggests an aspect (anisotropic scale) problem
+    #[serde(default)]
+    pub rmse_x: f64,
+    #[serde(default)]
+    pub rmse_y: f64,
     pub p90_error: f64,
     pub residuals: Vec<f64>,
     pub residuals_by_id: Vec<(u64, f64)>,
@@ -78,6 +88,8 @@ impl Default for QualityMetrics {
     fn default() -> Self {
         Self {
             rmse: 0.0,
+            rmse_x: 0.0,
+            rmse_y: 0.0,
             p90_error: 0.0,
             residuals: Vec::new(),
             residuals_by_id: Vec::new(),
@@ -88,7 +100,9 @@ impl Default for QualityMetrics {
     }
 }
 
-// Placeholder structs for transform kinds
+// Transform parameter structs. Solved coefficients that are added to an
+// existing struct must be `#[serde(default)]` so previously saved stacks
+// (which lack the field) still deserialize.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Similarity {
     pub params: [f64; 4],
@@ -105,11 +119,33 @@ pub struct Homography {
 pub struct Tps {
     pub control_points: Vec<[f64; 2]>,
     pub lambda: f64,
+    /// Solved radial-basis weights, one (wx, wy) per control point; empty until fitted
+    #[serde(default)]
+    pub weights: Vec<[f64; 2]>,
+    /// Solved affine part [a,b,c,d,tx,ty]; the identity until fitted
+    #[serde(default = "identity_affine")]
+    pub affine: [f64; 6],
+    /// Length unit of the radial basis: distances to the control points are
+    /// divided by it before the kernel is applied, so it is evaluated on the
+    /// unit-extent frame the spline was fitted in. 1 (raw pixels) if absent
+    #[serde(default = "unit_scale")]
+    pub scale: f64,
+}
+
+fn identity_affine() -> [f64; 6] {
+    [1.0, 0.0, 0.0, 1.0, 0.0, 0.0]
+}
+
+fn unit_scale() -> f64 {
+    1.0
 }
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Ffd {
     pub control_points: Vec<[f64; 2]>,
     pub grid_size: [usize; 2],
+    /// Solved control-point displacements, row-major over `grid_size`; empty until fitted
+    #[serde(default)]
+    pub displacements: Vec<[f64; 2]>,
 }
 
 impl ConstraintKind {
@@ -158,6 +194,8 @@ impl QualityMetrics {
             _ => {}
         }
         self.rmse *= factor;
+        self.rmse_x *= factor;
+        self.rmse_y *= factor;
         self.p90_error *= factor;
         for r in &mut self.residuals {
             *r *= factor;
@@ -170,4 +208,100 @@ impl QualityMetrics {
             self.map_scale = map_scale;
         }
     }
+
+    /// Ids in `residuals_by_id` whose residual exceeds `factor` × `rmse`, in
+    /// their listed order, for highlighting likely bad points. A ratio, so
+    /// it works in any unit. Empty when the RMSE is zero or not finite.
+    pub fn outlier_ids(&self, factor: f64) -> Vec<u64> {
+        if !(self.rmse.is_finite() && self.rmse > 0.0) {
+            return Vec::new();
+        }
+        let limit = factor * self.rmse;
+        self.residuals_by_id
+            .iter()
+            .filter(|(_, r)| *r > limit)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+/// Traffic-light summary of a fit's quality.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Good,
+    Warning,
+    Bad,
+}
+
+/// Limits used by `fit_verdict`, in reference pixels. A fit is `Good` when
+/// RMSE and P90 are at or below the `good_*` limits and it has no warnings,
+/// `Bad` when either exceeds its `bad_*` limit, and `Warning` otherwise.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct VerdictThresholds {
+    pub good_rmse_px: f64,
+    pub good_p90_px: f64,
+    pub bad_rmse_px: f64,
+    pub bad_p90_px: f64,
+}
+
+impl Default for VerdictThresholds {
+    fn default() -> Self {
+        Self {
+            good_rmse_px: 1.0,
+            good_p90_px: 2.0,
+            bad_rmse_px: 3.0,
+            bad_p90_px: 5.0,
+        }
+    }
+}
+
+/// Classify `metrics` with the default thresholds. `pixel_size` is the ground
+/// size of one reference pixel in meters, used to bring metrics reported in
+/// meters or map millimeters back to pixels.
+pub fn fit_verdict(metrics: &QualityMetrics, pixel_size: f64) -> Verdict {
+    fit_verdict_with(metrics, pixel_size, &VerdictThresholds::default())
+}
+
+/// `fit_verdict` with explicit thresholds.
+pub fn fit_verdict_with(
+    metrics: &QualityMetrics,
+    pixel_size: f64,
+    thresholds: &VerdictThresholds,
+) -> Verdict {
+    let mut m = metrics.clone();
+    m.convert_units(pixel_size, metrics.map_scale, ErrorUnit::Pixels);
+    if m.rmse > thresholds.bad_rmse_px || m.p90_error > thresholds.bad_p90_px {
+        Verdict::Bad
+    } else if m.rmse <= thresholds.good_rmse_px
+        && m.p90_error <= thresholds.good_p90_px
+        && m.warnings.is_empty()
+    {
+        Verdict::Good
+    } else {
+        Verdict::Warning
+    }
+}
+
+/// Size of one reference pixel in every unit the UI can report errors in.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct ScaleReport {
+    /// Ground meters per pixel
+    pub meters_per_pixel: f64,
+    /// Millimeters on the printed map per pixel; None without a map scale
+    pub map_mm_per_pixel: Option<f64>,
+    /// Native CRS units (meters, feet, degrees, ...) per pixel
+    pub crs_units_per_pixel: f64,
+}
+
+impl ScaleReport {
+    /// Build a report using the same factors as `QualityMetrics::convert_units`,
+    /// so a 1 px error converts to exactly these values. `map_scale` is the
+    /// scale denominator (e.g. 10000 for 1:10000).
+    pub fn new(meters_per_pixel: f64, crs_units_per_pixel: f64, map_scale: Option<f64>) -> Self {
+        Self {
+            meters_per_pixel,
+            map_mm_per_pixel: map_scale.map(|s| meters_per_pixel * (1000.0 / s)),
+            crs_units_per_pixel,
+        }
+    }
 }
@@ -0,0 +1,24 @@
+use types::{ErrorUnit, QualityMetrics};
+
+#[test]
+fn test_outlier_ids_flags_point_above_factor_times_rmse() {
+    // 29 residuals of 1 and one of 20: RMSE = sqrt(429 / 30) ≈ 3.78, so the
+    // odd point sits at ≈ 5.3 × RMSE
+    let mut residuals_by_id: Vec<(u64, f64)> = (0..29).map(|id| (id, 1.0)).collect();
+    residuals_by_id.push((99, 20.0));
+    let sum_sq: f64 = residuals_by_id.iter().map(|(_, r)| r * r).sum();
+    let mut m = QualityMetrics {
+        rmse: (sum_sq / residuals_by_id.len() as f64).sqrt(),
+        residuals_by_id,
+        ..Default::default()
+    };
+    assert_eq!(m.outlier_ids(5.0), vec![99]);
+    assert_eq!(m.outlier_ids(6.0), Vec::<u64>::new());
+    assert_eq!(m.outlier_ids(0.1).len(), 30);
+
+    // Unit-agnostic: converting rescales residuals and RMSE alike
+    m.convert_units(0.25, None, ErrorUnit::Meters);
+    assert_eq!(m.outlier_ids(5.0), vec![99]);
+
+    assert!(QualityMetrics::default().outlier_ids(1.0).is_empty());
+}
@@ -0,0 +1,34 @@
+use types::{ErrorUnit, QualityMetrics, ScaleReport};
+
+fn one_pixel() -> QualityMetrics {
+    QualityMetrics {
+        rmse: 1.0,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_scale_report_matches_unit_conversion() {
+    let (pixel_size, map_scale) = (2.5, 25000.0);
+    let report = ScaleReport::new(pixel_size, 8.2, Some(map_scale));
+
+    let mut m = one_pixel();
+    m.convert_units(pixel_size, Some(map_scale), ErrorUnit::Meters);
+    assert!((report.meters_per_pixel - m.rmse).abs() < 1e-12);
+
+    let mut m = one_pixel();
+    m.convert_units(pixel_size, Some(map_scale), ErrorUnit::MapMillimeters);
+    let mm = report.map_mm_per_pixel.unwrap();
+    assert!((mm - m.rmse).abs() < 1e-12);
+    // 2.5 m on the ground at 1:25000 is 0.1 mm on paper
+    assert!((mm - 0.1).abs() < 1e-12);
+    // Meters and map mm agree through the scale alone
+    assert!((mm * map_scale / 1000.0 - report.meters_per_pixel).abs() < 1e-12);
+    assert_eq!(report.crs_units_per_pixel, 8.2);
+}
+
+#[test]
+fn test_scale_report_without_map_scale() {
+    let report = ScaleReport::new(1.0, 1.0, None);
+    assert_eq!(report.map_mm_per_pixel, None);
+}
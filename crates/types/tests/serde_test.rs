@@ -0,0 +1,70 @@
+use types::{Affine, Ffd, Homography, Similarity, Tps, TransformKind, TransformStack};
+
+fn round_trip(t: &TransformKind) -> TransformKind {
+    let json = serde_json::to_string(t).unwrap();
+    let back: TransformKind = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&back).unwrap(), json);
+    back
+}
+
+#[test]
+fn test_round_trip_every_transform_kind() {
+    let stack = TransformStack {
+        transforms: vec![
+            TransformKind::Similarity(Similarity {
+                params: [1.5, 0.2, 5.0, -3.0],
+            }),
+            TransformKind::Affine(Affine {
+                params: [1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+            }),
+            TransformKind::Homography(Homography {
+                params: [1.0, 0.1, 2.0, 0.0, 1.0, 3.0, 1e-4, 2e-4, 1.0],
+            }),
+            TransformKind::Tps(Tps {
+                control_points: vec![[0.0, 0.0], [10.0, 0.0], [0.0, 10.0]],
+                lambda: 0.01,
+                weights: vec![[0.5, -0.25], [0.1, 0.2], [-0.6, 0.05]],
+                affine: [1.0, 0.0, 0.0, 1.0, 2.0, -1.0],
+                scale: 1.0,
+            }),
+            TransformKind::Ffd(Ffd {
+                control_points: vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]],
+                grid_size: [2, 2],
+                displacements: vec![[0.0, 0.1], [0.2, 0.0], [0.0, 0.0], [-0.1, 0.3]],
+            }),
+        ],
+    };
+    for t in &stack.transforms {
+        round_trip(t);
+    }
+    let json = serde_json::to_string(&stack).unwrap();
+    let back: TransformStack = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.transforms.len(), 5);
+    match &back.transforms[3] {
+        TransformKind::Tps(tps) => {
+            assert_eq!(tps.weights[0], [0.5, -0.25]);
+            assert_eq!(tps.affine[4], 2.0);
+        }
+        other => panic!("expected Tps, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_legacy_tps_and_ffd_without_solved_fields() {
+    let tps: TransformKind =
+        serde_json::from_str(r#"{"Tps":{"control_points":[[1.0,2.0]],"lambda":0.5}}"#).unwrap();
+    match tps {
+        TransformKind::Tps(t) => {
+            assert!(t.weights.is_empty());
+            // Unfitted means identity, not a collapse onto the origin
+            assert_eq!(t.affine, [1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        }
+        other => panic!("expected Tps, got {:?}", other),
+    }
+    let ffd: TransformKind =
+        serde_json::from_str(r#"{"Ffd":{"control_points":[],"grid_size":[4,4]}}"#).unwrap();
+    match ffd {
+        TransformKind::Ffd(f) => assert!(f.displacements.is_empty()),
+        other => panic!("expected Ffd, got {:?}", other),
+    }
+}
@@ -0,0 +1,44 @@
+use types::{fit_verdict, fit_verdict_with, ErrorUnit, QualityMetrics, Verdict, VerdictThresholds};
+
+fn metrics(rmse: f64, p90: f64) -> QualityMetrics {
+    QualityMetrics {
+        rmse,
+        p90_error: p90,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_verdict_rmse_boundaries() {
+    // Defaults: good ≤ 1.0 px, bad > 3.0 px (P90 kept below both limits)
+    assert_eq!(fit_verdict(&metrics(1.0, 1.0), 1.0), Verdict::Good);
+    assert_eq!(fit_verdict(&metrics(1.0001, 1.0), 1.0), Verdict::Warning);
+    assert_eq!(fit_verdict(&metrics(3.0, 1.0), 1.0), Verdict::Warning);
+    assert_eq!(fit_verdict(&metrics(3.0001, 1.0), 1.0), Verdict::Bad);
+}
+
+#[test]
+fn test_verdict_p90_and_warnings() {
+    assert_eq!(fit_verdict(&metrics(0.5, 2.5), 1.0), Verdict::Warning);
+    assert_eq!(fit_verdict(&metrics(0.5, 5.5), 1.0), Verdict::Bad);
+    let mut m = metrics(0.5, 0.5);
+    m.warnings.push("Low variance in source points".into());
+    assert_eq!(fit_verdict(&m, 1.0), Verdict::Warning);
+}
+
+#[test]
+fn test_verdict_uses_pixel_size_for_meter_metrics() {
+    // 4 m RMSE at 2 m/px is 2 px
+    let mut m = metrics(4.0, 4.0);
+    m.unit = ErrorUnit::Meters;
+    assert_eq!(fit_verdict(&m, 2.0), Verdict::Warning);
+    assert_eq!(fit_verdict(&m, 8.0), Verdict::Good);
+
+    let strict = VerdictThresholds {
+        good_rmse_px: 0.1,
+        good_p90_px: 0.1,
+        bad_rmse_px: 0.2,
+        bad_p90_px: 0.2,
+    };
+    assert_eq!(fit_verdict_with(&m, 8.0, &strict), Verdict::Bad);
+}